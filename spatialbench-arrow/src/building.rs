@@ -1,10 +1,11 @@
 use crate::conversions::string_view_array_from_display_iter;
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
-use arrow::array::{BinaryArray, Int64Array, RecordBatch};
+use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator, batch_or_error, try_record_batch};
+use arrow::array::{ArrayRef, BinaryArray, Float64Array, Int32Array, Int64Array, RecordBatch};
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
-use geo::Geometry;
-use geozero::{CoordDimensions, ToWkb};
-use spatialbench::generators::{BuildingGenerator, BuildingGeneratorIterator};
+use spatialbench::SpatialBenchError;
+use spatialbench::generators::{
+    Building, BuildingGenerator, GeometryZ, TryBuildingGeneratorIterator,
+};
 use std::sync::{Arc, LazyLock};
 
 /// Generate [`Building`]s in [`RecordBatch`] format
@@ -23,16 +24,34 @@ use std::sync::{Arc, LazyLock};
 /// // Read the first batch
 /// let batch = arrow_generator.next().unwrap();
 /// ```
+///
+/// Buildings generated in 3D have the additional `b_height` and `b_floors`
+/// columns. If a building cannot be generated, e.g. from a custom distribution
+/// that does not generate polygons, the iterator ends and the error is returned
+/// by [`RecordBatchIterator::take_error`].
 pub struct BuildingArrow {
-    inner: BuildingGeneratorIterator<'static>,
+    inner: TryBuildingGeneratorIterator<'static>,
     batch_size: usize,
+    schema: SchemaRef,
+    /// Whether the buildings are generated in 3D, with height and floors
+    is_3d: bool,
+    error: Option<SpatialBenchError>,
 }
 
 impl BuildingArrow {
     pub fn new(generator: BuildingGenerator<'static>) -> Self {
+        let is_3d = generator.is_3d();
+        let schema = if is_3d {
+            Arc::clone(&BUILDING_3D_SCHEMA)
+        } else {
+            Arc::clone(&BUILDING_SCHEMA)
+        };
         Self {
-            inner: generator.iter(),
+            inner: generator.try_iter(),
             batch_size: DEFAULT_BATCH_SIZE,
+            schema,
+            is_3d,
+            error: None,
         }
     }
 
@@ -45,7 +64,11 @@ impl BuildingArrow {
 
 impl RecordBatchIterator for BuildingArrow {
     fn schema(&self) -> &SchemaRef {
-        &BUILDING_SCHEMA
+        &self.schema
+    }
+
+    fn take_error(&mut self) -> Option<SpatialBenchError> {
+        self.error.take()
     }
 }
 
@@ -54,27 +77,43 @@ impl Iterator for BuildingArrow {
 
     fn next(&mut self) -> Option<Self::Item> {
         // Get next rows to convert
-        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
-        if rows.is_empty() {
-            return None;
-        }
+        let rows = self
+            .inner
+            .by_ref()
+            .take(self.batch_size)
+            .collect::<Result<Vec<_>, _>>();
+        let batch = match rows {
+            Ok(rows) if rows.is_empty() => return None,
+            Ok(rows) => self.make_batch(&rows),
+            Err(e) => Err(e),
+        };
+        batch_or_error(batch, &mut self.error)
+    }
+}
 
+impl BuildingArrow {
+    /// Converts the rows column by column
+    fn make_batch(&self, rows: &[Building<'static>]) -> Result<RecordBatch, SpatialBenchError> {
         let buildingkey = Int64Array::from_iter_values(rows.iter().map(|r| r.b_buildingkey));
         let name = string_view_array_from_display_iter(rows.iter().map(|r| &r.b_name));
 
-        // Convert geo::Polygon to WKB binary format
-        let wkb_array = BinaryArray::from_iter_values(rows.iter().map(|r| {
-            Geometry::Polygon(r.b_boundary.clone())
-                .to_wkb(CoordDimensions::xy())
-                .unwrap()
-        }));
+        // Convert the footprints to WKB binary format
+        let wkb = rows
+            .iter()
+            .map(|r| GeometryZ::new(&r.footprint(), r.b_elevation).to_wkb())
+            .collect::<Result<Vec<_>, _>>()?;
+        let wkb_array = BinaryArray::from_iter_values(wkb);
+
+        let mut columns: Vec<ArrayRef> =
+            vec![Arc::new(buildingkey), Arc::new(name), Arc::new(wkb_array)];
+        if self.is_3d {
+            let height = Float64Array::from_iter_values(rows.iter().map(|r| r.b_height.unwrap()));
+            let floors = Int32Array::from_iter_values(rows.iter().map(|r| r.b_floors.unwrap()));
+            columns.push(Arc::new(height));
+            columns.push(Arc::new(floors));
+        }
 
-        let batch = RecordBatch::try_new(
-            Arc::clone(self.schema()),
-            vec![Arc::new(buildingkey), Arc::new(name), Arc::new(wkb_array)],
-        )
-        .unwrap();
-        Some(batch)
+        try_record_batch(self.schema(), columns)
     }
 }
 
@@ -87,3 +126,12 @@ fn make_building_schema() -> SchemaRef {
         Field::new("b_boundary", DataType::Binary, false),
     ]))
 }
+
+/// Schema for the Building generated in 3D
+static BUILDING_3D_SCHEMA: LazyLock<SchemaRef> = LazyLock::new(make_building_3d_schema);
+fn make_building_3d_schema() -> SchemaRef {
+    let mut fields = make_building_schema().fields().to_vec();
+    fields.push(Arc::new(Field::new("b_height", DataType::Float64, false)));
+    fields.push(Arc::new(Field::new("b_floors", DataType::Int32, false)));
+    Arc::new(Schema::new(fields))
+}
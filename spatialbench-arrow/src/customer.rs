@@ -1,8 +1,12 @@
 use crate::conversions::string_view_array_from_display_iter;
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
-use arrow::array::{Int64Array, RecordBatch, StringViewArray};
+use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator, batch_or_error, try_record_batch};
+use arrow::array::{ArrayRef, BinaryArray, Int64Array, RecordBatch, StringViewArray};
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
-use spatialbench::generators::{CustomerGenerator, CustomerGeneratorIterator};
+use geo::Geometry;
+use spatialbench::SpatialBenchError;
+use spatialbench::generators::{
+    Customer, CustomerGenerator, GeometryZ, TryCustomerGeneratorIterator,
+};
 use std::sync::{Arc, LazyLock};
 
 /// Generate [`Customer`]s in [`RecordBatch`] format
@@ -42,16 +46,34 @@ use std::sync::{Arc, LazyLock};
 ///   "+-----------+--------------------+---------------------------------------+-------------+--------------+-----------------+"
 ///   ]);
 /// ```
+///
+/// Customers generated with home locations have the additional `c_homeloc` column. If a
+/// home location cannot be generated, e.g. from a custom distribution that does
+/// not generate points, the iterator ends and the error is returned by
+/// [`RecordBatchIterator::take_error`].
 pub struct CustomerArrow {
-    inner: CustomerGeneratorIterator<'static>,
+    inner: TryCustomerGeneratorIterator<'static>,
     batch_size: usize,
+    schema: SchemaRef,
+    /// Whether the customers are generated with home locations
+    has_home_locations: bool,
+    error: Option<SpatialBenchError>,
 }
 
 impl CustomerArrow {
     pub fn new(generator: CustomerGenerator<'static>) -> Self {
+        let has_home_locations = generator.has_home_locations();
+        let schema = if has_home_locations {
+            Arc::clone(&CUSTOMER_LOCATION_SCHEMA)
+        } else {
+            Arc::clone(&CUSTOMER_SCHEMA)
+        };
         Self {
-            inner: generator.iter(),
+            inner: generator.try_iter(),
             batch_size: DEFAULT_BATCH_SIZE,
+            schema,
+            has_home_locations,
+            error: None,
         }
     }
 
@@ -64,7 +86,11 @@ impl CustomerArrow {
 
 impl RecordBatchIterator for CustomerArrow {
     fn schema(&self) -> &SchemaRef {
-        &CUSTOMER_SCHEMA
+        &self.schema
+    }
+
+    fn take_error(&mut self) -> Option<SpatialBenchError> {
+        self.error.take()
     }
 }
 
@@ -73,11 +99,23 @@ impl Iterator for CustomerArrow {
 
     fn next(&mut self) -> Option<Self::Item> {
         // Get next rows to convert
-        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
-        if rows.is_empty() {
-            return None;
-        }
+        let rows = self
+            .inner
+            .by_ref()
+            .take(self.batch_size)
+            .collect::<Result<Vec<_>, _>>();
+        let batch = match rows {
+            Ok(rows) if rows.is_empty() => return None,
+            Ok(rows) => self.make_batch(&rows),
+            Err(e) => Err(e),
+        };
+        batch_or_error(batch, &mut self.error)
+    }
+}
 
+impl CustomerArrow {
+    /// Converts the rows column by column
+    fn make_batch(&self, rows: &[Customer<'static>]) -> Result<RecordBatch, SpatialBenchError> {
         let c_custkey = Int64Array::from_iter_values(rows.iter().map(|r| r.c_custkey));
         let c_name = string_view_array_from_display_iter(rows.iter().map(|r| r.c_name));
         let c_address = string_view_array_from_display_iter(rows.iter().map(|r| &r.c_address));
@@ -85,19 +123,24 @@ impl Iterator for CustomerArrow {
         let c_nation = StringViewArray::from_iter_values(rows.iter().map(|r| r.c_nation));
         let c_phone = string_view_array_from_display_iter(rows.iter().map(|r| &r.c_phone));
 
-        let batch = RecordBatch::try_new(
-            Arc::clone(self.schema()),
-            vec![
-                Arc::new(c_custkey),
-                Arc::new(c_name),
-                Arc::new(c_address),
-                Arc::new(c_region),
-                Arc::new(c_nation),
-                Arc::new(c_phone),
-            ],
-        )
-        .unwrap();
-        Some(batch)
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(c_custkey),
+            Arc::new(c_name),
+            Arc::new(c_address),
+            Arc::new(c_region),
+            Arc::new(c_nation),
+            Arc::new(c_phone),
+        ];
+        if self.has_home_locations {
+            let c_homeloc = rows
+                .iter()
+                .map(|r| GeometryZ::new(&Geometry::Point(r.c_homeloc.unwrap()), None).to_wkb())
+                .collect::<Result<Vec<_>, _>>()?;
+            let c_homeloc = BinaryArray::from_iter_values(c_homeloc);
+            columns.push(Arc::new(c_homeloc));
+        }
+
+        try_record_batch(self.schema(), columns)
     }
 }
 
@@ -113,3 +156,11 @@ fn make_customer_schema() -> SchemaRef {
         Field::new("c_phone", DataType::Utf8View, false),
     ]))
 }
+
+/// Schema for the Customer generated with home locations
+static CUSTOMER_LOCATION_SCHEMA: LazyLock<SchemaRef> = LazyLock::new(make_customer_location_schema);
+fn make_customer_location_schema() -> SchemaRef {
+    let mut fields = make_customer_schema().fields().to_vec();
+    fields.push(Arc::new(Field::new("c_homeloc", DataType::Binary, false)));
+    Arc::new(Schema::new(fields))
+}
@@ -1,8 +1,10 @@
 use crate::conversions::string_view_array_from_display_iter;
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
-use arrow::array::{Int64Array, RecordBatch, StringViewArray};
+use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator, batch_or_error, try_record_batch};
+use arrow::array::{ArrayRef, BinaryArray, Int64Array, RecordBatch, StringViewArray};
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
-use spatialbench::generators::{DriverGenerator, DriverGeneratorIterator};
+use geo::Geometry;
+use spatialbench::SpatialBenchError;
+use spatialbench::generators::{Driver, DriverGenerator, GeometryZ, TryDriverGeneratorIterator};
 use std::sync::{Arc, LazyLock};
 
 /// Generate [`Driver`]s in [`RecordBatch`] format
@@ -42,16 +44,34 @@ use std::sync::{Arc, LazyLock};
 ///   "+-------------+------------------+-------------------------------------+-------------+----------------+-----------------+"
 /// ]);
 /// ```
+///
+/// Drivers generated with base locations have the additional `d_baseloc` column. If a
+/// base location cannot be generated, e.g. from a custom distribution that does
+/// not generate points, the iterator ends and the error is returned by
+/// [`RecordBatchIterator::take_error`].
 pub struct DriverArrow {
-    inner: DriverGeneratorIterator<'static>,
+    inner: TryDriverGeneratorIterator<'static>,
     batch_size: usize,
+    schema: SchemaRef,
+    /// Whether the drivers are generated with base locations
+    has_base_locations: bool,
+    error: Option<SpatialBenchError>,
 }
 
 impl DriverArrow {
     pub fn new(generator: DriverGenerator<'static>) -> Self {
+        let has_base_locations = generator.has_base_locations();
+        let schema = if has_base_locations {
+            Arc::clone(&DRIVER_LOCATION_SCHEMA)
+        } else {
+            Arc::clone(&DRIVER_SCHEMA)
+        };
         Self {
-            inner: generator.iter(),
+            inner: generator.try_iter(),
             batch_size: DEFAULT_BATCH_SIZE,
+            schema,
+            has_base_locations,
+            error: None,
         }
     }
 
@@ -64,7 +84,11 @@ impl DriverArrow {
 
 impl RecordBatchIterator for DriverArrow {
     fn schema(&self) -> &SchemaRef {
-        &DRIVER_SCHEMA
+        &self.schema
+    }
+
+    fn take_error(&mut self) -> Option<SpatialBenchError> {
+        self.error.take()
     }
 }
 
@@ -73,11 +97,23 @@ impl Iterator for DriverArrow {
 
     fn next(&mut self) -> Option<Self::Item> {
         // Get next rows to convert
-        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
-        if rows.is_empty() {
-            return None;
-        }
+        let rows = self
+            .inner
+            .by_ref()
+            .take(self.batch_size)
+            .collect::<Result<Vec<_>, _>>();
+        let batch = match rows {
+            Ok(rows) if rows.is_empty() => return None,
+            Ok(rows) => self.make_batch(&rows),
+            Err(e) => Err(e),
+        };
+        batch_or_error(batch, &mut self.error)
+    }
+}
 
+impl DriverArrow {
+    /// Converts the rows column by column
+    fn make_batch(&self, rows: &[Driver]) -> Result<RecordBatch, SpatialBenchError> {
         let d_driverkey = Int64Array::from_iter_values(rows.iter().map(|r| r.d_driverkey));
         let d_name = string_view_array_from_display_iter(rows.iter().map(|r| r.d_name));
         let d_address = string_view_array_from_display_iter(rows.iter().map(|r| &r.d_address));
@@ -85,19 +121,24 @@ impl Iterator for DriverArrow {
         let d_nation = StringViewArray::from_iter_values(rows.iter().map(|r| &r.d_nation));
         let d_phone = string_view_array_from_display_iter(rows.iter().map(|r| &r.d_phone));
 
-        let batch = RecordBatch::try_new(
-            Arc::clone(self.schema()),
-            vec![
-                Arc::new(d_driverkey),
-                Arc::new(d_name),
-                Arc::new(d_address),
-                Arc::new(d_region),
-                Arc::new(d_nation),
-                Arc::new(d_phone),
-            ],
-        )
-        .unwrap();
-        Some(batch)
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(d_driverkey),
+            Arc::new(d_name),
+            Arc::new(d_address),
+            Arc::new(d_region),
+            Arc::new(d_nation),
+            Arc::new(d_phone),
+        ];
+        if self.has_base_locations {
+            let d_baseloc = rows
+                .iter()
+                .map(|r| GeometryZ::new(&Geometry::Point(r.d_baseloc.unwrap()), None).to_wkb())
+                .collect::<Result<Vec<_>, _>>()?;
+            let d_baseloc = BinaryArray::from_iter_values(d_baseloc);
+            columns.push(Arc::new(d_baseloc));
+        }
+
+        try_record_batch(self.schema(), columns)
     }
 }
 
@@ -113,3 +154,11 @@ fn make_driver_schema() -> SchemaRef {
         Field::new("d_phone", DataType::Utf8View, false),
     ]))
 }
+
+/// Schema for the Driver generated with base locations
+static DRIVER_LOCATION_SCHEMA: LazyLock<SchemaRef> = LazyLock::new(make_driver_location_schema);
+fn make_driver_location_schema() -> SchemaRef {
+    let mut fields = make_driver_schema().fields().to_vec();
+    fields.push(Arc::new(Field::new("d_baseloc", DataType::Binary, false)));
+    Arc::new(Schema::new(fields))
+}
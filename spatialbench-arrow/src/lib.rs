@@ -43,11 +43,13 @@ mod trip;
 mod vehicle;
 mod zone;
 
-use arrow::array::RecordBatch;
+use arrow::array::{ArrayRef, RecordBatch};
 use arrow::datatypes::SchemaRef;
 pub use building::BuildingArrow;
 pub use customer::CustomerArrow;
 pub use driver::DriverArrow;
+use spatialbench::SpatialBenchError;
+use std::sync::Arc;
 pub use trip::TripArrow;
 pub use vehicle::VehicleArrow;
 pub use zone::ZoneArrow;
@@ -55,6 +57,34 @@ pub use zone::ZoneArrow;
 /// Iterator of Arrow [`RecordBatch`] that also knows its schema
 pub trait RecordBatchIterator: Iterator<Item = RecordBatch> + Send {
     fn schema(&self) -> &SchemaRef;
+
+    /// Returns the error that ended the iteration early, if any
+    ///
+    /// Generators fail when they read data, like [`ZoneArrow`], when a custom
+    /// distribution generates the wrong geometry type, or when a batch cannot be
+    /// built. Check this after the iterator returns `None` to tell a complete
+    /// table from a failed one.
+    fn take_error(&mut self) -> Option<SpatialBenchError> {
+        None
+    }
+}
+
+/// Builds the record batch of the columns, or returns the Arrow error
+fn try_record_batch(
+    schema: &SchemaRef,
+    columns: Vec<ArrayRef>,
+) -> Result<RecordBatch, SpatialBenchError> {
+    RecordBatch::try_new(Arc::clone(schema), columns)
+        .map_err(|e| SpatialBenchError::Output(Box::new(e)))
+}
+
+/// Returns the batch, or keeps the error for [`RecordBatchIterator::take_error`]
+/// and ends the iteration
+fn batch_or_error(
+    batch: Result<RecordBatch, SpatialBenchError>,
+    error: &mut Option<SpatialBenchError>,
+) -> Option<RecordBatch> {
+    batch.map_err(|e| *error = Some(e)).ok()
 }
 
 /// The default number of rows in each Batch
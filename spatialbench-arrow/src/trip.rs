@@ -1,25 +1,25 @@
 use crate::conversions::{decimal128_array_from_iter, to_arrow_timestamp_millis};
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
+use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator, batch_or_error, try_record_batch};
 use arrow::array::{BinaryArray, Int64Array, RecordBatch, TimestampMillisecondArray};
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
 use geo::Geometry;
-use geozero::{CoordDimensions, ToWkb};
-use spatialbench::generators::{Trip, TripGenerator, TripGeneratorIterator};
+use spatialbench::SpatialBenchError;
+use spatialbench::generators::{GeometryZ, Trip, TripGenerator, TryTripGeneratorIterator};
 use std::sync::{Arc, LazyLock, Mutex};
 
-// Thread-safe wrapper for TripGeneratorIterator
+// Thread-safe wrapper for TryTripGeneratorIterator
 struct ThreadSafeTripGenerator {
-    generator: Mutex<TripGeneratorIterator>,
+    generator: Mutex<TryTripGeneratorIterator>,
 }
 
 impl ThreadSafeTripGenerator {
     fn new(generator: TripGenerator) -> Self {
         Self {
-            generator: Mutex::new(generator.iter()),
+            generator: Mutex::new(generator.try_iter()),
         }
     }
 
-    fn next_batch(&self, batch_size: usize) -> Vec<Trip> {
+    fn next_batch(&self, batch_size: usize) -> Result<Vec<Trip>, SpatialBenchError> {
         let mut generator = self.generator.lock().unwrap();
         generator.by_ref().take(batch_size).collect()
     }
@@ -29,10 +29,16 @@ impl ThreadSafeTripGenerator {
 unsafe impl Send for ThreadSafeTripGenerator {}
 unsafe impl Sync for ThreadSafeTripGenerator {}
 
+/// Generate [`Trip`]s in [`RecordBatch`] format
+///
+/// If a trip cannot be generated, e.g. from a custom distribution that does not
+/// generate points, the iterator ends and the error is returned by
+/// [`RecordBatchIterator::take_error`].
 pub struct TripArrow {
     generator: ThreadSafeTripGenerator,
     batch_size: usize,
     schema: SchemaRef,
+    error: Option<SpatialBenchError>,
 }
 
 impl TripArrow {
@@ -41,6 +47,7 @@ impl TripArrow {
             generator: ThreadSafeTripGenerator::new(generator),
             batch_size: DEFAULT_BATCH_SIZE,
             schema: TRIP_SCHEMA.clone(),
+            error: None,
         }
     }
 
@@ -54,6 +61,10 @@ impl RecordBatchIterator for TripArrow {
     fn schema(&self) -> &SchemaRef {
         &self.schema
     }
+
+    fn take_error(&mut self) -> Option<SpatialBenchError> {
+        self.error.take()
+    }
 }
 
 impl Iterator for TripArrow {
@@ -61,11 +72,18 @@ impl Iterator for TripArrow {
 
     fn next(&mut self) -> Option<Self::Item> {
         // Get next rows to convert
-        let rows = self.generator.next_batch(self.batch_size);
-        if rows.is_empty() {
-            return None;
-        }
+        let batch = match self.generator.next_batch(self.batch_size) {
+            Ok(rows) if rows.is_empty() => return None,
+            Ok(rows) => self.make_batch(&rows),
+            Err(e) => Err(e),
+        };
+        batch_or_error(batch, &mut self.error)
+    }
+}
 
+impl TripArrow {
+    /// Converts the rows column by column
+    fn make_batch(&self, rows: &[Trip]) -> Result<RecordBatch, SpatialBenchError> {
         // Convert column by column
         let t_tripkey = Int64Array::from_iter_values(rows.iter().map(|row| row.t_tripkey));
         let t_custkey = Int64Array::from_iter_values(rows.iter().map(|row| row.t_custkey));
@@ -83,19 +101,19 @@ impl Iterator for TripArrow {
         let t_tip = decimal128_array_from_iter(rows.iter().map(|row| row.t_tip));
         let t_totalamount = decimal128_array_from_iter(rows.iter().map(|row| row.t_totalamount));
         let t_distance = decimal128_array_from_iter(rows.iter().map(|row| row.t_distance));
-        let t_pickuploc = BinaryArray::from_iter_values(rows.iter().map(|row| {
-            Geometry::Point(row.t_pickuploc)
-                .to_wkb(CoordDimensions::xy())
-                .expect("Failed to convert pickup location to WKB")
-        }));
-        let t_dropoffloc = BinaryArray::from_iter_values(rows.iter().map(|row| {
-            Geometry::Point(row.t_dropoffloc)
-                .to_wkb(CoordDimensions::xy())
-                .expect("Failed to convert dropoff location to WKB")
-        }));
+        let t_pickuploc = rows
+            .iter()
+            .map(|row| GeometryZ::new(&Geometry::Point(row.t_pickuploc), row.t_pickup_z).to_wkb())
+            .collect::<Result<Vec<_>, _>>()?;
+        let t_dropoffloc = rows
+            .iter()
+            .map(|row| GeometryZ::new(&Geometry::Point(row.t_dropoffloc), row.t_dropoff_z).to_wkb())
+            .collect::<Result<Vec<_>, _>>()?;
+        let t_pickuploc = BinaryArray::from_iter_values(t_pickuploc);
+        let t_dropoffloc = BinaryArray::from_iter_values(t_dropoffloc);
 
-        let batch = RecordBatch::try_new(
-            Arc::clone(&self.schema),
+        try_record_batch(
+            &self.schema,
             vec![
                 Arc::new(t_tripkey),
                 Arc::new(t_custkey),
@@ -111,9 +129,6 @@ impl Iterator for TripArrow {
                 Arc::new(t_dropoffloc),
             ],
         )
-        .unwrap();
-
-        Some(batch)
     }
 }
 
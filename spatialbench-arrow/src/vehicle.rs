@@ -1,7 +1,8 @@
 use crate::conversions::string_view_array_from_display_iter;
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
+use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator, batch_or_error, try_record_batch};
 use arrow::array::{Int64Array, RecordBatch, StringViewArray};
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use spatialbench::SpatialBenchError;
 use spatialbench::generators::{VehicleGenerator, VehicleGeneratorIterator};
 use std::sync::{Arc, LazyLock};
 
@@ -45,6 +46,7 @@ use std::sync::{Arc, LazyLock};
 pub struct VehicleArrow {
     inner: VehicleGeneratorIterator<'static>,
     batch_size: usize,
+    error: Option<SpatialBenchError>,
 }
 
 impl VehicleArrow {
@@ -52,6 +54,7 @@ impl VehicleArrow {
         Self {
             inner: generator.iter(),
             batch_size: DEFAULT_BATCH_SIZE,
+            error: None,
         }
     }
 
@@ -66,6 +69,10 @@ impl RecordBatchIterator for VehicleArrow {
     fn schema(&self) -> &SchemaRef {
         &VEHICLE_SCHEMA
     }
+
+    fn take_error(&mut self) -> Option<SpatialBenchError> {
+        self.error.take()
+    }
 }
 
 impl Iterator for VehicleArrow {
@@ -84,8 +91,8 @@ impl Iterator for VehicleArrow {
         let v_type = StringViewArray::from_iter_values(rows.iter().map(|r| r.v_type));
         let v_license = StringViewArray::from_iter_values(rows.iter().map(|r| r.v_license));
 
-        let batch = RecordBatch::try_new(
-            Arc::clone(self.schema()),
+        let batch = try_record_batch(
+            self.schema(),
             vec![
                 Arc::new(v_vehiclekey),
                 Arc::new(v_mfgr),
@@ -93,9 +100,8 @@ impl Iterator for VehicleArrow {
                 Arc::new(v_type),
                 Arc::new(v_license),
             ],
-        )
-        .unwrap();
-        Some(batch)
+        );
+        batch_or_error(batch, &mut self.error)
     }
 }
 
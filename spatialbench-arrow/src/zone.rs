@@ -1,13 +1,21 @@
 use crate::conversions::string_view_array_from_display_iter;
-use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator};
-use arrow::array::{BinaryArray, Int64Array, RecordBatch};
-use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
-use geozero::{CoordDimensions, ToWkb};
-use spatialbench::generators::{ZoneGenerator, ZoneGeneratorIterator};
+use crate::{DEFAULT_BATCH_SIZE, RecordBatchIterator, batch_or_error, try_record_batch};
+use arrow::array::{
+    ArrayRef, BinaryArray, Float64Array, Int32Array, Int64Array, RecordBatch, StructArray,
+};
+use arrow::buffer::NullBuffer;
+use arrow::datatypes::{DataType, Field, Fields, Schema, SchemaRef};
+use geo::Rect;
+use spatialbench::SpatialBenchError;
+use spatialbench::generators::{TryZoneGeneratorIterator, ZoneGenerator, ZoneWkb};
 use std::sync::{Arc, LazyLock};
 
 /// Generate [`Zone`]s in [`RecordBatch`] format
 ///
+/// Boundaries are written as WKB; without geometry processing, the WKB stored
+/// in the zone cache is copied as is. If the zone source cannot be read, the
+/// iterator ends and the error is returned by [`RecordBatchIterator::take_error`].
+///
 /// [`Zone`]: spatialbench::generators::Zone
 ///
 /// # Example
@@ -27,16 +35,18 @@ use std::sync::{Arc, LazyLock};
 ///   .to_string();
 /// ```
 pub struct ZoneArrow {
-    inner: ZoneGeneratorIterator,
+    inner: TryZoneGeneratorIterator<ZoneWkb>,
     batch_size: usize,
+    error: Option<SpatialBenchError>,
 }
 
 impl ZoneArrow {
     pub fn new(generator: ZoneGenerator) -> Self {
-        let inner = generator.clone().into_iter();
+        let inner = generator.try_iter_wkb();
         Self {
             inner,
             batch_size: DEFAULT_BATCH_SIZE,
+            error: None,
         }
     }
 
@@ -50,6 +60,10 @@ impl RecordBatchIterator for ZoneArrow {
     fn schema(&self) -> &SchemaRef {
         &ZONE_SCHEMA
     }
+
+    fn take_error(&mut self) -> Option<SpatialBenchError> {
+        self.error.take()
+    }
 }
 
 impl Iterator for ZoneArrow {
@@ -57,7 +71,18 @@ impl Iterator for ZoneArrow {
 
     fn next(&mut self) -> Option<Self::Item> {
         // Get next rows to convert
-        let rows: Vec<_> = self.inner.by_ref().take(self.batch_size).collect();
+        let rows = match self
+            .inner
+            .by_ref()
+            .take(self.batch_size)
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                self.error = Some(e);
+                return None;
+            }
+        };
         if rows.is_empty() {
             return None;
         }
@@ -68,16 +93,15 @@ impl Iterator for ZoneArrow {
         let z_region = string_view_array_from_display_iter(rows.iter().map(|r| &r.z_region));
         let z_name = string_view_array_from_display_iter(rows.iter().map(|r| &r.z_name));
         let z_subtype = string_view_array_from_display_iter(rows.iter().map(|r| &r.z_subtype));
+        let z_level = Int32Array::from_iter_values(rows.iter().map(|r| r.z_level));
+        let z_parentkey = Int64Array::from_iter(rows.iter().map(|r| r.z_parentkey));
+
+        let z_boundary = BinaryArray::from_iter_values(rows.iter().map(|r| &r.z_boundary));
 
-        // Convert geo::Polygon to WKB binary format
-        let z_boundary = BinaryArray::from_iter_values(rows.iter().map(|r| {
-            r.z_boundary
-                .to_wkb(CoordDimensions::xy())
-                .expect("Failed to encode WKB")
-        }));
+        let z_bbox = bbox_array(&rows);
 
-        let batch = RecordBatch::try_new(
-            Arc::clone(self.schema()),
+        let batch = try_record_batch(
+            self.schema(),
             vec![
                 Arc::new(z_zonekey),
                 Arc::new(z_gersid),
@@ -85,11 +109,13 @@ impl Iterator for ZoneArrow {
                 Arc::new(z_region),
                 Arc::new(z_name),
                 Arc::new(z_subtype),
+                Arc::new(z_level),
+                Arc::new(z_parentkey),
                 Arc::new(z_boundary),
+                Arc::new(z_bbox),
             ],
-        )
-        .unwrap();
-        Some(batch)
+        );
+        batch_or_error(batch, &mut self.error)
     }
 }
 
@@ -103,6 +129,37 @@ fn make_zone_schema() -> SchemaRef {
         Field::new("z_region", DataType::Utf8View, false),
         Field::new("z_name", DataType::Utf8View, false),
         Field::new("z_subtype", DataType::Utf8View, false),
+        Field::new("z_level", DataType::Int32, false),
+        Field::new("z_parentkey", DataType::Int64, true),
         Field::new("z_boundary", DataType::Binary, false),
+        Field::new("z_bbox", DataType::Struct(bbox_fields()), true),
     ]))
 }
+
+/// Fields of the bounding box column, as in a GeoParquet bbox covering column
+fn bbox_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("xmin", DataType::Float64, false),
+        Field::new("ymin", DataType::Float64, false),
+        Field::new("xmax", DataType::Float64, false),
+        Field::new("ymax", DataType::Float64, false),
+    ])
+}
+
+/// Converts the optional bounding boxes of the rows to a struct array
+fn bbox_array(rows: &[ZoneWkb]) -> StructArray {
+    let coordinate = |f: fn(&Rect) -> f64| -> ArrayRef {
+        Arc::new(Float64Array::from_iter_values(
+            rows.iter()
+                .map(|r| r.z_bbox.as_ref().map(f).unwrap_or_default()),
+        ))
+    };
+    let columns = vec![
+        coordinate(|r| r.min().x),
+        coordinate(|r| r.min().y),
+        coordinate(|r| r.max().x),
+        coordinate(|r| r.max().y),
+    ];
+    let nulls = NullBuffer::from_iter(rows.iter().map(|r| r.z_bbox.is_some()));
+    StructArray::new(bbox_fields(), columns, Some(nulls))
+}
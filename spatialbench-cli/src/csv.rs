@@ -8,8 +8,41 @@ use spatialbench::generators::{
 use std::io::Write;
 
 /// Define a Source that writes the table in CSV format
+///
+/// Rows of generators that can fail, e.g. that read data or use a custom
+/// distribution, are generated with a fallible iterator method (e.g.
+/// `try_iter`), whose errors are returned by [`Source::create`].
+/// Tables whose columns depend on the generator pass a `header` function of the
+/// generator instead of the static header of the formatter.
 macro_rules! define_csv_source {
     ($SOURCE_NAME:ident, $GENERATOR_TYPE:ty, $FORMATTER:ty) => {
+        define_csv_source!(
+            $SOURCE_NAME,
+            $GENERATOR_TYPE,
+            $FORMATTER,
+            into_iter,
+            Ok::<_, std::io::Error>,
+            |_: &$GENERATOR_TYPE| <$FORMATTER>::header()
+        );
+    };
+    ($SOURCE_NAME:ident, $GENERATOR_TYPE:ty, $FORMATTER:ty, $ITER:ident) => {
+        define_csv_source!(
+            $SOURCE_NAME,
+            $GENERATOR_TYPE,
+            $FORMATTER,
+            $ITER,
+            std::convert::identity,
+            |_: &$GENERATOR_TYPE| <$FORMATTER>::header()
+        );
+    };
+    (
+        $SOURCE_NAME:ident,
+        $GENERATOR_TYPE:ty,
+        $FORMATTER:ty,
+        $ITER:ident,
+        $ROW:expr,
+        $HEADER:expr
+    ) => {
         pub struct $SOURCE_NAME {
             inner: $GENERATOR_TYPE,
         }
@@ -23,17 +56,18 @@ macro_rules! define_csv_source {
         impl Source for $SOURCE_NAME {
             fn header(&self, buffer: Vec<u8>) -> Vec<u8> {
                 let mut buffer = buffer;
-                writeln!(&mut buffer, "{}", <$FORMATTER>::header())
+                writeln!(&mut buffer, "{}", ($HEADER)(&self.inner))
                     .expect("writing to memory is infallible");
                 buffer
             }
 
-            fn create(self, mut buffer: Vec<u8>) -> Vec<u8> {
-                for item in self.inner.into_iter() {
+            fn create(self, mut buffer: Vec<u8>) -> std::io::Result<Vec<u8>> {
+                for item in self.inner.$ITER() {
+                    let item = ($ROW)(item)?;
                     let formatter = <$FORMATTER>::new(item);
                     writeln!(&mut buffer, "{formatter}").expect("writing to memory is infallible");
                 }
-                buffer
+                Ok(buffer)
             }
         }
     };
@@ -41,8 +75,36 @@ macro_rules! define_csv_source {
 
 // Define .csv sources for all tables
 define_csv_source!(VehicleCsvSource, VehicleGenerator<'static>, VehicleCsv);
-define_csv_source!(DriverCsvSource, DriverGenerator<'static>, DriverCsv);
-define_csv_source!(CustomerCsvSource, CustomerGenerator<'static>, CustomerCsv);
-define_csv_source!(TripCsvSource, TripGenerator, TripCsv);
-define_csv_source!(BuildingCsvSource, BuildingGenerator<'static>, BuildingCsv);
-define_csv_source!(ZoneCsvSource, ZoneGenerator, ZoneCsv);
+define_csv_source!(
+    DriverCsvSource,
+    DriverGenerator<'static>,
+    DriverCsv,
+    try_iter,
+    std::convert::identity,
+    DriverCsv::header_for
+);
+define_csv_source!(
+    CustomerCsvSource,
+    CustomerGenerator<'static>,
+    CustomerCsv,
+    try_iter,
+    std::convert::identity,
+    CustomerCsv::header_for
+);
+define_csv_source!(TripCsvSource, TripGenerator, TripCsv, try_iter);
+define_csv_source!(
+    BuildingCsvSource,
+    BuildingGenerator<'static>,
+    BuildingCsv,
+    try_iter,
+    std::convert::identity,
+    BuildingCsv::header_for
+);
+define_csv_source!(
+    ZoneCsvSource,
+    ZoneGenerator,
+    ZoneCsv,
+    try_iter,
+    std::convert::identity,
+    ZoneCsv::header_for
+);
@@ -16,7 +16,9 @@ use tokio::task::JoinSet;
 /// crate
 pub trait Source: Send {
     /// generates the data for this generator into the buffer, returning the buffer.
-    fn create(self, buffer: Vec<u8>) -> Vec<u8>;
+    ///
+    /// Fails if the generator reads data (like the Zone table) and the read fails.
+    fn create(self, buffer: Vec<u8>) -> Result<Vec<u8>, io::Error>;
 
     /// Create the first line for the output, into the buffer
     ///
@@ -43,7 +45,8 @@ pub trait Sink: Send {
 ///
 /// This function will run the [`Source`]es in parallel up to num_threads.
 /// Data is written to the [`Sink`] in the order of the [`Source`]es in
-/// the input iterator.
+/// the input iterator. If a [`Source`] fails, no further buffers are written
+/// and its error is returned.
 ///
 /// G: Generator
 /// I: Iterator<Item = G>
@@ -101,9 +104,10 @@ where
             // Note we ignore errors writing because if the write errors it
             // means the channel is closed / the program is exiting so there
             // is nothing listening to send errors
-            if let Err(e) = tx.send(buffer).await {
+            if let Err(e) = tx.send(buffer?).await {
                 debug!("Error sending buffer to writer: {e}");
             }
+            Ok::<_, io::Error>(())
         });
 
     // The writer task runs in a blocking thread to avoid blocking the async
@@ -119,20 +123,27 @@ where
     });
 
     // drive the stream to completion
+    let mut result = Ok(());
     while let Some(write_task) = stream.next().await {
         // break early if the writer stream is done (errored)
         if writer_task.is_finished() {
             debug!("writer task is done early, stopping writer");
             break;
         }
-        write_task.await; // sends the buffer to the writer task
+        // sends the buffer to the writer task, stop at the first failed source
+        result = write_task.await;
+        if result.is_err() {
+            debug!("source failed, stopping writer");
+            break;
+        }
     }
     drop(stream); // drop any stream references
     drop(tx); // drop last tx reference to tell the writer it is done.
 
     // wait for writer to finish
     debug!("waiting for writer task to complete");
-    writer_task.await.expect("writer task panicked")
+    let written = writer_task.await.expect("writer task panicked");
+    result.and(written)
 }
 
 /// A simple buffer recycler to avoid allocating new buffers for each part
@@ -14,6 +14,8 @@
 //!     -s, --scale-factor <FACTOR>  Scale factor for the data generation (default: 1)
 //!     -T, --tables <TABLES>        Comma-separated list of tables to generate (default: all)
 //!     -f, --format <FORMAT>        Output format: tbl, csv, or parquet (default: tbl)
+//!         --zone-source <SOURCE>   Where to load zones from: overture, synthetic, parquet:<path>, geojson:<path> or ndjson:<path> (default: overture)
+//!         --zone-cache-dir <DIR>   Directory of the local Zone table cache (default: no cache)
 //!     -o, --output-dir <DIR>       Output directory (default: current directory)
 //!     -p, --parts <N>              Number of parts to split generation into (default: 1)
 //!         --part <N>               Which part to generate (1-based, default: 1)
@@ -64,8 +66,11 @@ use spatialbench::generators::{
     BuildingGenerator, CustomerGenerator, DriverGenerator, TripGenerator, VehicleGenerator,
     ZoneGenerator,
 };
-use spatialbench::spatial::overrides::{set_overrides, SpatialOverrides};
+use spatialbench::spatial::overrides::{
+    self as spatial_overrides, set_overrides, SpatialOverrides,
+};
 use spatialbench::text::TextPool;
+use spatialbench::zone::{ZoneConfig, ZoneSampler, ZoneSource};
 use spatialbench_arrow::{
     BuildingArrow, CustomerArrow, DriverArrow, RecordBatchIterator, TripArrow, VehicleArrow,
     ZoneArrow,
@@ -98,6 +103,25 @@ struct Cli {
     #[arg(long = "config")]
     config: Option<PathBuf>,
 
+    /// Where to load the Zone table from (default: overture)
+    ///
+    /// Supported values: `overture[:<release>]` reads the Overture release from S3,
+    /// `synthetic[:<seed>]` generates zones without any input data,
+    /// `parquet:<path>` reads a local GeoParquet file, directory or glob,
+    /// `geojson:<path>` reads a GeoJSON FeatureCollection and `ndjson:<path>`
+    /// reads newline-delimited GeoJSON features. A bare path is classified by
+    /// its extension.
+    #[arg(long = "zone-source")]
+    zone_source: Option<ZoneSource>,
+
+    /// Directory of the local Zone table cache (default: no cache)
+    ///
+    /// The zone source is scanned once and the sorted table is stored here, so
+    /// all parts, and later runs with the same source and subtypes, read it
+    /// from the cache.
+    #[arg(long = "zone-cache-dir")]
+    zone_cache_dir: Option<PathBuf>,
+
     /// Number of part(itions) to generate (manual parallel generation)
     #[arg(short, long)]
     parts: Option<i32>,
@@ -252,10 +276,19 @@ enum OutputFormat {
 }
 
 #[tokio::main]
-async fn main() -> io::Result<()> {
+async fn main() {
     // Parse command line arguments
     let cli = Cli::parse();
-    cli.main().await
+    if let Err(e) = cli.main().await {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Returns a function wrapping an error of the configuration file in an
+/// [`io::ErrorKind::InvalidInput`] error, prefixed with `context`
+fn invalid_input(context: &str) -> impl Fn(anyhow::Error) -> io::Error + '_ {
+    move |e| io::Error::new(io::ErrorKind::InvalidInput, format!("{context}: {e}"))
 }
 
 /// macro to create a Cli function for generating a table
@@ -329,6 +362,7 @@ impl Cli {
             }
         };
 
+        let mut overrides = SpatialOverrides::default();
         if let Some(path) = config_path {
             let text = std::fs::read_to_string(&path).map_err(|e| {
                 io::Error::new(
@@ -339,9 +373,41 @@ impl Cli {
 
             match parse_yaml(&text) {
                 Ok(file_cfg) => {
-                    let trip = file_cfg.trip.as_ref().map(|c| c.to_generator());
-                    let building = file_cfg.building.as_ref().map(|c| c.to_generator());
-                    set_overrides(SpatialOverrides { trip, building });
+                    overrides.trip = file_cfg
+                        .trip_generator()
+                        .map_err(invalid_input("Invalid trip configuration"))?;
+                    overrides.building = file_cfg
+                        .building_generator(self.scale_factor)
+                        .map_err(invalid_input("Invalid building configuration"))?;
+                    overrides.customer = file_cfg
+                        .customer_generator()
+                        .map_err(invalid_input("Invalid customer configuration"))?;
+                    overrides.driver = file_cfg
+                        .driver_generator()
+                        .map_err(invalid_input("Invalid driver configuration"))?;
+                    overrides.footprint = file_cfg
+                        .building_footprint()
+                        .map_err(invalid_input("Invalid building configuration"))?;
+                    overrides.validity = file_cfg
+                        .building_validity()
+                        .map_err(invalid_input("Invalid building configuration"))?;
+                    overrides.zone = file_cfg
+                        .zone
+                        .as_ref()
+                        .map(|c| c.to_zone_config())
+                        .transpose()
+                        .map_err(invalid_input("Invalid zone configuration"))?;
+                    overrides.trip_zones = file_cfg
+                        .trip_zones
+                        .as_ref()
+                        .map(|c| c.to_zone_placement())
+                        .transpose()
+                        .map_err(invalid_input("Invalid trip zone placement"))?;
+                    overrides.regions = file_cfg
+                        .to_regions()
+                        .map_err(invalid_input("Invalid regions"))?;
+                    overrides.land_mask = file_cfg.land_mask;
+                    overrides.non_overlapping = file_cfg.building_non_overlapping();
                     info!("Loaded spider configuration from {}", path.display());
                 }
                 Err(e) => {
@@ -355,6 +421,23 @@ impl Cli {
             info!("Using default spider configuration from spider_defaults.rs");
         }
 
+        // command line flags take precedence over the zone section of the config
+        if let Some(zone_source) = &self.zone_source {
+            info!("Loading zones from {zone_source}");
+            overrides
+                .zone
+                .get_or_insert_with(ZoneConfig::default)
+                .source = zone_source.clone();
+        }
+        if let Some(zone_cache_dir) = &self.zone_cache_dir {
+            info!("Caching zones in {}", zone_cache_dir.display());
+            overrides
+                .zone
+                .get_or_insert_with(ZoneConfig::default)
+                .cache_dir = Some(zone_cache_dir.clone());
+        }
+        set_overrides(overrides);
+
         // Determine which tables to generate
         let tables: Vec<Table> = if let Some(tables) = self.tables.as_ref() {
             tables.clone()
@@ -378,6 +461,18 @@ impl Cli {
         let elapsed = start.elapsed();
         info!("Created static distributions and text pools in {elapsed:?}");
 
+        // load the zones trips are placed in up front, so a zone source that
+        // cannot be read fails before any trip is written
+        if tables.contains(&Table::Trip) {
+            if let Some(placement) = spatial_overrides::trip_zone_placement() {
+                ZoneSampler::shared(
+                    self.scale_factor,
+                    spatial_overrides::zone_config_or_default(),
+                    &placement,
+                )?;
+            }
+        }
+
         // Warn if parquet specific options are set but not generating parquet
         if self.format != OutputFormat::Parquet {
             if self.parquet_compression != Compression::SNAPPY {
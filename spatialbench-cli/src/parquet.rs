@@ -26,7 +26,9 @@ pub trait IntoSize {
 /// Uses num_threads to generate the data in parallel
 ///
 /// Note the input is an iterator of [`RecordBatchIterator`]; The batches
-/// produced by each iterator is encoded as its own row group.
+/// produced by each iterator is encoded as its own row group. If an iterator
+/// fails (see [`RecordBatchIterator::take_error`]), no further row groups are
+/// written and its error is returned.
 pub async fn generate_parquet<W: Write + Send + IntoSize + 'static, I>(
     writer: W,
     iter_iter: I,
@@ -56,8 +58,7 @@ where
     let parquet_schema = Arc::new(
         ArrowSchemaConverter::new()
             .with_coerce_types(writer_properties.coerce_types())
-            .convert(&schema)
-            .unwrap(),
+            .convert(&schema)?,
     );
 
     // create a stream that computes the data for each row group
@@ -89,17 +90,17 @@ where
     let writer_task = tokio::task::spawn_blocking(move || {
         // Create parquet writer
         let mut writer =
-            SerializedFileWriter::new(writer, root_schema, writer_properties_captured).unwrap();
+            SerializedFileWriter::new(writer, root_schema, writer_properties_captured)?;
 
         while let Some(chunks) = rx.blocking_recv() {
             // Start row group
-            let mut row_group_writer = writer.next_row_group().unwrap();
+            let mut row_group_writer = writer.next_row_group()?;
 
             // Slap the chunks into the row group
             for chunk in chunks {
-                chunk.append_to_row_group(&mut row_group_writer).unwrap();
+                chunk.append_to_row_group(&mut row_group_writer)?;
             }
-            row_group_writer.close().unwrap();
+            row_group_writer.close()?;
             statistics.increment_chunks(1);
         }
         let size = writer.into_inner()?.into_size()?;
@@ -108,7 +109,15 @@ where
     });
 
     // now, drive the input stream and send results to the writer task
+    let mut result = Ok(());
     while let Some(chunks) = row_group_stream.next().await {
+        let chunks = match chunks {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                result = Err(e);
+                break; // stop at the first failed row group
+            }
+        };
         // send the chunks to the writer task
         if let Err(e) = tx.send(chunks).await {
             debug!("Error sending chunks to writer: {e}");
@@ -121,7 +130,7 @@ where
     // Wait for the writer task to finish
     writer_task.await??;
 
-    Ok(())
+    result
 }
 
 /// Creates the data for a particular row group
@@ -129,34 +138,39 @@ where
 /// Note at the moment it does not use multiple tasks/threads but it could
 /// potentially encode multiple columns with different threads .
 ///
-/// Returns an array of [`ArrowColumnChunk`]
+/// Returns an array of [`ArrowColumnChunk`], or the error of the iterator or
+/// of the encoding
 fn encode_row_group<I>(
     parquet_schema: SchemaDescPtr,
     writer_properties: Arc<WriterProperties>,
     schema: SchemaRef,
-    iter: I,
-) -> Vec<ArrowColumnChunk>
+    mut iter: I,
+) -> Result<Vec<ArrowColumnChunk>, io::Error>
 where
     I: RecordBatchIterator,
 {
     // Create writers for each of the leaf columns
-    let mut col_writers = get_column_writers(&parquet_schema, &writer_properties, &schema).unwrap();
+    let mut col_writers = get_column_writers(&parquet_schema, &writer_properties, &schema)?;
 
     // generate the data and send it to the tasks (via the sender channels)
-    for batch in iter {
+    for batch in iter.by_ref() {
         let columns = batch.columns().iter();
         let col_writers = col_writers.iter_mut();
         let fields = schema.fields().iter();
 
         for ((col_writer, field), arr) in col_writers.zip(fields).zip(columns) {
-            for leaves in compute_leaves(field.as_ref(), arr).unwrap() {
-                col_writer.write(&leaves).unwrap();
+            for leaves in compute_leaves(field.as_ref(), arr)? {
+                col_writer.write(&leaves)?;
             }
         }
     }
+    if let Some(e) = iter.take_error() {
+        return Err(e.into());
+    }
     // finish the writers and create the column chunks
-    col_writers
+    let chunks = col_writers
         .into_iter()
-        .map(|col_writer| col_writer.close().unwrap())
-        .collect()
+        .map(|col_writer| col_writer.close())
+        .collect::<Result<_, _>>()?;
+    Ok(chunks)
 }
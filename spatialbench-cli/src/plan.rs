@@ -138,7 +138,7 @@ impl GenerationPlan {
 
         // scale down the row count by the number of partitions being generated
         // so that the output is consistent with the original part count
-        let num_chunks = OutputSize::new(table, scale_factor, format, parquet_row_group_bytes)
+        let num_chunks = OutputSize::try_new(table, scale_factor, format, parquet_row_group_bytes)?
             .with_scaled_row_count(cli_part_count)
             .part_count();
 
@@ -175,7 +175,8 @@ impl GenerationPlan {
         scale_factor: f64,
         parquet_row_group_bytes: i64,
     ) -> Result<Self, String> {
-        let output_size = OutputSize::new(table, scale_factor, format, parquet_row_group_bytes);
+        let output_size =
+            OutputSize::try_new(table, scale_factor, format, parquet_row_group_bytes)?;
         let num_parts = output_size.part_count();
 
         Ok(Self {
@@ -218,13 +219,14 @@ struct OutputSize {
 }
 
 impl OutputSize {
-    pub fn new(
+    /// Returns the output size of the table, or the error counting its rows
+    pub fn try_new(
         table: &Table,
         scale_factor: f64,
         format: OutputFormat,
         parquet_row_group_bytes: i64,
-    ) -> Self {
-        let row_count = Self::row_count_for_table(table, scale_factor);
+    ) -> Result<Self, String> {
+        let row_count = Self::row_count_for_table(table, scale_factor)?;
 
         // The average row size in bytes for each table in the TPC-H schema
         // this was determined by sampling the data
@@ -280,12 +282,12 @@ impl OutputSize {
                 target_chunk_size_bytes={target_chunk_size_bytes}, max_part_count={max_part_count:?}",
         );
 
-        OutputSize {
+        Ok(OutputSize {
             avg_row_size_bytes,
             row_count,
             target_chunk_size_bytes,
             max_part_count,
-        }
+        })
     }
 
     /// Return the number of parts to generate
@@ -321,9 +323,9 @@ impl OutputSize {
         }
     }
 
-    fn row_count_for_table(table: &Table, scale_factor: f64) -> i64 {
+    fn row_count_for_table(table: &Table, scale_factor: f64) -> Result<i64, String> {
         //let (avg_row_size_bytes, row_count) = match table {
-        match table {
+        Ok(match table {
             Table::Vehicle => VehicleGenerator::calculate_row_count(scale_factor, 1, 1),
             Table::Driver => DriverGenerator::calculate_row_count(scale_factor, 1, 1),
             Table::Customer => CustomerGenerator::calculate_row_count(scale_factor, 1, 1),
@@ -331,9 +333,11 @@ impl OutputSize {
             Table::Building => BuildingGenerator::calculate_row_count(scale_factor, 1, 1),
             Table::Zone => {
                 let generator = ZoneGenerator::new(scale_factor, 1, 1);
-                generator.calculate_row_count()
+                generator
+                    .try_calculate_row_count()
+                    .map_err(|e| format!("Failed to count zones: {e}"))?
             }
-        }
+        })
     }
 }
 
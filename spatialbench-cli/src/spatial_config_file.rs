@@ -1,11 +1,18 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer};
+use spatialbench::generators::BuildingGenerator;
 use spatialbench::spatial::{
-    DistributionParams, DistributionType, GeomType, SpatialConfig, SpatialGenerator,
+    validate_regions, CityModel, DistributionParams, DistributionType, FootprintOptions, GeomType,
+    HotspotBackground, HotspotComponent, PolygonStyle, Region, SizeUnit, SpatialConfig,
+    SpatialGenerator, Validity,
 };
+use spatialbench::zone::{
+    SubtypeTier, ZoneConfig, ZoneGeometryOptions, ZonePlacement, ZoneSource, ZoneWeight,
+};
+use std::collections::BTreeMap;
 use std::fmt;
-use std::sync::OnceLock;
+use std::path::PathBuf;
 
 // Deserializer for DistributionType
 fn deserialize_distribution_type<'de, D>(deserializer: D) -> Result<DistributionType, D::Error>
@@ -33,6 +40,10 @@ where
                 "sierpinski" => Ok(DistributionType::Sierpinski),
                 "thomas" => Ok(DistributionType::Thomas),
                 "hierarchicalthomas" => Ok(DistributionType::HierarchicalThomas),
+                "parcel" => Ok(DistributionType::Parcel),
+                "densitygrid" => Ok(DistributionType::DensityGrid),
+                "hotspot" => Ok(DistributionType::Hotspot),
+                "citymodel" => Ok(DistributionType::CityModel),
                 _ => Err(E::custom(format!("unknown distribution type: {}", value))),
             }
         }
@@ -63,6 +74,7 @@ where
                 "point" => Ok(GeomType::Point),
                 "box" => Ok(GeomType::Box),
                 "polygon" => Ok(GeomType::Polygon),
+                "linestring" => Ok(GeomType::LineString),
                 _ => Err(E::custom(format!("unknown geometry type: {}", value))),
             }
         }
@@ -75,6 +87,81 @@ where
 pub struct SpatialConfigFile {
     pub trip: Option<InlineSpatialConfig>,
     pub building: Option<InlineSpatialConfig>,
+    // home locations of customers, default: no c_homeloc column
+    pub customer: Option<InlineSpatialConfig>,
+    // base locations of drivers, default: no d_baseloc column
+    pub driver: Option<InlineSpatialConfig>,
+    pub zone: Option<InlineZoneConfig>,
+    pub trip_zones: Option<InlineTripZones>,
+    // regions trips and buildings are placed in, default: the built-in continents
+    pub regions: Option<Vec<InlineRegion>>,
+    // redraw trips and buildings that fall in water, default: false
+    #[serde(default)]
+    pub land_mask: bool,
+    // strict | raw validity of building footprints, default: raw
+    pub validity: Option<String>,
+    // cities and subclusters shared by the citymodel distributions of trip and building
+    pub city_model: Option<InlineCityModel>,
+}
+
+#[derive(Deserialize)]
+pub struct InlineCityModel {
+    pub seed: u32,
+    pub cities: u32, // top-level “city” centers
+    pub sub_mean: f64,
+    pub sub_sd: f64,
+    pub sub_min: u32,
+    pub sub_max: u32,
+    pub sigma_city: f64,        // spread of subcluster centers around their city
+    pub pareto_alpha_city: f64, // Pareto tail for city weights
+    pub pareto_xm_city: f64,    // Pareto scale (xmin) for city weights
+    pub pareto_alpha_sub: f64,  // Pareto tail for subcluster weights (within a city)
+    pub pareto_xm_sub: f64,     // Pareto scale (xmin) for subcluster weights
+}
+
+#[derive(Deserialize)]
+pub struct InlineZoneConfig {
+    // same syntax as --zone-source, default: overture
+    pub source: Option<String>,
+    // Overture release, for overture sources
+    pub release: Option<String>,
+    pub cache_dir: Option<PathBuf>,
+    // subtype tiers, default: the built-in tiers
+    pub subtypes: Option<Vec<InlineSubtypeTier>>,
+    // boundary processing, default: none
+    pub geometry: Option<InlineZoneGeometry>,
+}
+
+#[derive(Deserialize)]
+pub struct InlineSubtypeTier {
+    pub min_scale_factor: f64,
+    pub subtypes: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct InlineZoneGeometry {
+    pub simplify_tolerance: Option<f64>,
+    pub max_vertices: Option<usize>,
+    #[serde(default)]
+    pub bbox: bool,
+}
+
+#[derive(Deserialize)]
+pub struct InlineTripZones {
+    // area | uniform, default: area
+    pub weight: Option<String>,
+    // share of pickups per subtype, default: every subtype of the zone table equally
+    pub subtypes: Option<BTreeMap<String, f64>>,
+}
+
+#[derive(Deserialize)]
+pub struct InlineRegion {
+    pub name: String,
+    // either an affine from the unit square or a [min_lon, min_lat, max_lon, max_lat] bbox
+    pub affine: Option<[f64; 6]>,
+    pub bbox: Option<[f64; 4]>,
+    // relative share of the records, default: the area of the region
+    pub weight: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -88,10 +175,40 @@ pub struct InlineSpatialConfig {
     // geometry = box
     pub width: f64,
     pub height: f64,
-    // geometry = polygon
+    // geometry = polygon | linestring
     pub maxseg: i32,
     pub polysize: f64,
+    // geometry = polygon: star | footprint, default: star
+    pub polygon_style: Option<InlinePolygonStyle>,
+    // unit | meters: unit of width, height, polysize and the Thomas sigmas,
+    // default: unit (the unit square of the region)
+    pub units: Option<String>,
     pub params: InlineParams,
+    // buildings only: courtyards and campuses, default: simple polygons
+    pub footprint: Option<InlineFootprint>,
+    // buildings only: redraw buildings that intersect one of a lower key, default: false
+    #[serde(default)]
+    pub non_overlapping: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum InlinePolygonStyle {
+    Star,
+    // rectangles, L, T and U shapes with a log-normal size
+    Footprint {
+        median_size: f64, // median length of the longer side, in meters
+        size_sigma: f64,  // standard deviation of the log of the length
+    },
+}
+
+#[derive(Deserialize)]
+pub struct InlineFootprint {
+    #[serde(default)]
+    pub courtyard: f64,
+    #[serde(default)]
+    pub multipolygon: f64,
+    pub max_parts: Option<u32>,
 }
 
 #[derive(Deserialize)]
@@ -134,11 +251,240 @@ pub enum InlineParams {
         pareto_alpha_sub: f64,  // Pareto tail for subcluster weights (within a city)
         pareto_xm_sub: f64,     // Pareto scale (xmin) for subcluster weights
     },
+
+    // Parcel: recursive splits of the unit square into one box per record
+    Parcel {
+        srange: f64, // split position range, in [0, 0.5]: split at [srange, 1 - srange]
+        dither: f64, // max fraction (0–1) each box is shrunk by
+    },
+
+    // DensityGrid: points sampled from the cells of a local lon/lat raster by weight
+    DensityGrid {
+        path: String,           // .asc, .pgm or .csv raster
+        bbox: Option<[f64; 4]>, // [min_lon, min_lat, max_lon, max_lat], required for .pgm/.csv
+    },
+
+    // Hotspot: Gaussian clusters around lon/lat centers, plus an optional background
+    Hotspot {
+        components: Vec<InlineHotspotComponent>,
+        background: Option<Box<InlineHotspotBackground>>,
+    },
+
+    // CityModel: subclusters of the top-level city_model, shared with the other tables
+    CityModel {
+        sigma_sub: f64, // spread of the records of this table around their subcluster
+    },
 }
 
-impl InlineSpatialConfig {
-    pub fn to_generator(&self) -> SpatialGenerator {
-        let params = match &self.params {
+#[derive(Deserialize)]
+pub struct InlineHotspotComponent {
+    pub lon: f64,
+    pub lat: f64,
+    pub sigma_km: f64, // standard deviation of the distance to the center
+    pub weight: f64,   // relative share of the points
+}
+
+#[derive(Deserialize)]
+pub struct InlineHotspotBackground {
+    pub weight: f64, // relative share of the points, in the unit of the component weights
+    #[serde(deserialize_with = "deserialize_distribution_type")]
+    pub dist_type: DistributionType,
+    pub params: InlineParams,
+}
+
+impl SpatialConfigFile {
+    /// Spatial generator of the trip section, which must generate points or
+    /// line strings
+    pub fn trip_generator(&self) -> Result<Option<SpatialGenerator>> {
+        let Some(trip) = &self.trip else {
+            return Ok(None);
+        };
+        if !matches!(trip.geom_type, GeomType::Point | GeomType::LineString) {
+            bail!(
+                "trip geometry must be point or linestring, got {:?}",
+                trip.geom_type
+            );
+        }
+        if trip.footprint.is_some() {
+            bail!("footprint options apply to buildings only");
+        }
+        if trip.non_overlapping {
+            bail!("non_overlapping applies to buildings only");
+        }
+        trip.to_generator(self.city_model()?.as_ref(), 0).map(Some)
+    }
+
+    /// Spatial generator of the building section at the scale factor, which
+    /// must generate polygons
+    pub fn building_generator(&self, scale_factor: f64) -> Result<Option<SpatialGenerator>> {
+        let Some(building) = &self.building else {
+            return Ok(None);
+        };
+        if !matches!(building.geom_type, GeomType::Box | GeomType::Polygon) {
+            bail!(
+                "building geometry must be box or polygon, got {:?}",
+                building.geom_type
+            );
+        }
+        if building.non_overlapping && matches!(building.dist_type, DistributionType::Parcel) {
+            bail!(
+                "parcels never overlap, non_overlapping does not apply to the parcel distribution"
+            );
+        }
+        // the parcel distribution splits the unit square into one parcel per
+        // building; an empty table still needs a parcel
+        let cardinality = BuildingGenerator::calculate_row_count(scale_factor, 1, 1).max(1);
+        building
+            .to_generator(self.city_model()?.as_ref(), cardinality as u64)
+            .map(Some)
+    }
+
+    /// Spatial generator of the customer home locations, which must be 2D points
+    pub fn customer_generator(&self) -> Result<Option<SpatialGenerator>> {
+        self.location_generator("customer", self.customer.as_ref())
+    }
+
+    /// Spatial generator of the driver base locations, which must be 2D points
+    pub fn driver_generator(&self) -> Result<Option<SpatialGenerator>> {
+        self.location_generator("driver", self.driver.as_ref())
+    }
+
+    /// Spatial generator of the locations of the customer or driver section
+    fn location_generator(
+        &self,
+        table: &str,
+        section: Option<&InlineSpatialConfig>,
+    ) -> Result<Option<SpatialGenerator>> {
+        let Some(section) = section else {
+            return Ok(None);
+        };
+        if !matches!(section.geom_type, GeomType::Point) {
+            bail!(
+                "{table} geometry must be point, got {:?}",
+                section.geom_type
+            );
+        }
+        if section.dim != 2 {
+            bail!("{table} locations must be 2D, got dim {}", section.dim);
+        }
+        // locations are drawn in the nation of each record, which the hotspot
+        // centers and spreads in meters would not stay in
+        if matches!(section.dist_type, DistributionType::Hotspot) {
+            bail!("{table} locations cannot use the hotspot distribution");
+        }
+        if section.size_unit()? == SizeUnit::Meters {
+            bail!("{table} locations must use unit sizes, got units: meters");
+        }
+        if section.footprint.is_some() {
+            bail!("footprint options apply to buildings only");
+        }
+        if section.non_overlapping {
+            bail!("non_overlapping applies to buildings only");
+        }
+        section
+            .to_generator(self.city_model()?.as_ref(), 0)
+            .map(Some)
+    }
+
+    /// City model of the `city_model` section, if any
+    ///
+    /// Tables drawing from the model must use the same units, so that their
+    /// subcluster centers coincide.
+    pub fn city_model(&self) -> Result<Option<CityModel>> {
+        let Some(m) = &self.city_model else {
+            return Ok(None);
+        };
+        let model = CityModel {
+            seed: m.seed,
+            cities: m.cities,
+            sub_mean: m.sub_mean,
+            sub_sd: m.sub_sd,
+            sub_min: m.sub_min,
+            sub_max: m.sub_max,
+            sigma_city: m.sigma_city,
+            pareto_alpha_city: m.pareto_alpha_city,
+            pareto_xm_city: m.pareto_xm_city,
+            pareto_alpha_sub: m.pareto_alpha_sub,
+            pareto_xm_sub: m.pareto_xm_sub,
+        };
+        model.validate()?;
+
+        let units: Vec<SizeUnit> = [&self.trip, &self.building, &self.customer, &self.driver]
+            .into_iter()
+            .flatten()
+            .filter(|t| matches!(t.params, InlineParams::CityModel { .. }))
+            .map(|t| t.size_unit())
+            .collect::<Result<_>>()?;
+        if let Some((first, second)) = units.iter().zip(&units[1..]).find(|(a, b)| a != b) {
+            bail!(
+                "tables sharing the city model must use the same units, got {first} and {second}"
+            );
+        }
+        Ok(Some(model))
+    }
+
+    /// Whether buildings are placed without overlaps
+    pub fn building_non_overlapping(&self) -> bool {
+        self.building.as_ref().is_some_and(|b| b.non_overlapping)
+    }
+
+    /// Footprint options of the building section, if any
+    pub fn building_footprint(&self) -> Result<Option<FootprintOptions>> {
+        let Some(footprint) = self.building.as_ref().and_then(|b| b.footprint.as_ref()) else {
+            return Ok(None);
+        };
+        let options = FootprintOptions {
+            courtyard_probability: footprint.courtyard,
+            multipolygon_probability: footprint.multipolygon,
+            max_parts: footprint
+                .max_parts
+                .unwrap_or(FootprintOptions::default().max_parts),
+        };
+        options.validate()?;
+        Ok(Some(options))
+    }
+
+    /// Validity of building footprints, if set
+    pub fn building_validity(&self) -> Result<Option<Validity>> {
+        self.validity
+            .as_ref()
+            .map(|v| v.parse::<Validity>().map_err(|e| anyhow!(e)))
+            .transpose()
+    }
+
+    /// Returns the regions of the `regions` section, if any
+    pub fn to_regions(&self) -> Result<Option<Vec<Region>>> {
+        let Some(regions) = &self.regions else {
+            return Ok(None);
+        };
+        let regions = regions
+            .iter()
+            .map(|r| match (r.affine, r.bbox) {
+                (Some(affine), None) => Ok(Region {
+                    name: r.name.clone(),
+                    affine,
+                    weight: r.weight,
+                }),
+                (None, Some(bbox)) => {
+                    let [min_lon, min_lat, max_lon, max_lat] = bbox;
+                    if !(min_lon < max_lon && min_lat < max_lat) {
+                        bail!("Invalid bbox of region '{}'", r.name);
+                    }
+                    Ok(Region::from_bbox(&r.name, bbox, r.weight))
+                }
+                _ => bail!("Region '{}' needs either an affine or a bbox", r.name),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        validate_regions(&regions)?;
+        Ok(Some(regions))
+    }
+}
+
+impl InlineParams {
+    /// Distribution parameters, with the shared city model for the citymodel
+    /// distribution
+    pub fn to_params(&self, city_model: Option<&CityModel>) -> Result<DistributionParams> {
+        Ok(match self {
             InlineParams::None => DistributionParams::None,
             InlineParams::Normal { mu, sigma } => DistributionParams::Normal {
                 mu: *mu,
@@ -193,8 +539,77 @@ impl InlineSpatialConfig {
                 pareto_alpha_sub: *pareto_alpha_sub, // Pareto tail for subcluster weights (within a city)
                 pareto_xm_sub: *pareto_xm_sub,       // Pareto scale (xmin) for subcluster weights
             },
+            InlineParams::Parcel { srange, dither } => DistributionParams::Parcel {
+                srange: *srange,
+                dither: *dither,
+            },
+            InlineParams::DensityGrid { path, bbox } => DistributionParams::DensityGrid {
+                path: path.clone(),
+                bbox: *bbox,
+            },
+            InlineParams::Hotspot {
+                components,
+                background,
+            } => DistributionParams::Hotspot {
+                components: components
+                    .iter()
+                    .map(|c| HotspotComponent {
+                        lon: c.lon,
+                        lat: c.lat,
+                        sigma_km: c.sigma_km,
+                        weight: c.weight,
+                    })
+                    .collect(),
+                background: match background {
+                    Some(b) => Some(Box::new(HotspotBackground {
+                        weight: b.weight,
+                        dist_type: b.dist_type,
+                        params: b.params.to_params(city_model)?,
+                    })),
+                    None => None,
+                },
+            },
+            InlineParams::CityModel { sigma_sub } => DistributionParams::CityModel {
+                model: *city_model
+                    .ok_or_else(|| anyhow!("citymodel distribution needs a city_model section"))?,
+                sigma_sub: *sigma_sub,
+            },
+        })
+    }
+}
+
+impl InlineSpatialConfig {
+    /// Unit of the sizes and spreads, by default unit square coordinates
+    fn size_unit(&self) -> Result<SizeUnit> {
+        self.units
+            .as_ref()
+            .map(|u| u.parse::<SizeUnit>().map_err(|e| anyhow!(e)))
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+
+    /// Spatial generator of the section, generating `cardinality` records (0 if
+    /// unknown, which the parcel distribution rejects)
+    pub fn to_generator(
+        &self,
+        city_model: Option<&CityModel>,
+        cardinality: u64,
+    ) -> Result<SpatialGenerator> {
+        let params = self.params.to_params(city_model)?;
+
+        let polygon_style = match &self.polygon_style {
+            None | Some(InlinePolygonStyle::Star) => PolygonStyle::Star,
+            Some(InlinePolygonStyle::Footprint {
+                median_size,
+                size_sigma,
+            }) => PolygonStyle::Footprint {
+                median_size: *median_size,
+                size_sigma: *size_sigma,
+            },
         };
 
+        let units = self.size_unit()?;
+
         let cfg = SpatialConfig {
             dist_type: self.dist_type,
             geom_type: self.geom_type,
@@ -204,9 +619,70 @@ impl InlineSpatialConfig {
             height: self.height,
             maxseg: self.maxseg,
             polysize: self.polysize,
+            polygon_style,
             params,
+            validity: Validity::Raw,
+            units,
         };
-        SpatialGenerator::new(cfg, OnceLock::new(), OnceLock::new())
+        Ok(SpatialGenerator::try_new_with_cardinality(
+            cfg,
+            cardinality,
+        )?)
+    }
+}
+
+impl InlineZoneConfig {
+    pub fn to_zone_config(&self) -> Result<ZoneConfig> {
+        let mut config = ZoneConfig::default();
+        if let Some(source) = &self.source {
+            config.source = source.parse().map_err(|e: String| anyhow!(e))?;
+        }
+        if let Some(release) = &self.release {
+            match &mut config.source {
+                ZoneSource::Overture { release: r } => *r = release.clone(),
+                source => {
+                    bail!("zone release '{release}' given for non-Overture source '{source}'")
+                }
+            }
+        }
+        if let Some(dir) = &self.cache_dir {
+            config.cache_dir = Some(dir.clone());
+        }
+        if let Some(tiers) = &self.subtypes {
+            config.subtype_tiers = tiers
+                .iter()
+                .map(|t| SubtypeTier {
+                    min_scale_factor: t.min_scale_factor,
+                    subtypes: t.subtypes.clone(),
+                })
+                .collect();
+        }
+        if let Some(geometry) = &self.geometry {
+            config.geometry = ZoneGeometryOptions {
+                simplify_tolerance: geometry.simplify_tolerance,
+                max_vertices: geometry.max_vertices,
+                bbox: geometry.bbox,
+            };
+        }
+        config.validate().map_err(|e| anyhow!(e))?;
+        Ok(config)
+    }
+}
+
+impl InlineTripZones {
+    pub fn to_zone_placement(&self) -> Result<ZonePlacement> {
+        let mut placement = ZonePlacement::default();
+        if let Some(weight) = &self.weight {
+            placement.weight = weight.parse::<ZoneWeight>().map_err(|e| anyhow!(e))?;
+        }
+        if let Some(subtypes) = &self.subtypes {
+            placement.subtype_weights = subtypes
+                .iter()
+                .map(|(subtype, share)| (subtype.clone(), *share))
+                .collect();
+        }
+        placement.validate().map_err(|e| anyhow!(e))?;
+        Ok(placement)
     }
 }
 
@@ -8,8 +8,18 @@ use spatialbench::generators::{
 use std::io::Write;
 
 /// Define a Source that writes the table in TBL format
+///
+/// Rows of generators that can fail, e.g. that read data or use a custom
+/// distribution, are generated with a fallible iterator method (e.g.
+/// `try_iter`), whose errors are returned by [`Source::create`].
 macro_rules! define_tbl_source {
     ($SOURCE_NAME:ident, $GENERATOR_TYPE:ty) => {
+        define_tbl_source!($SOURCE_NAME, $GENERATOR_TYPE, iter, Ok::<_, std::io::Error>);
+    };
+    ($SOURCE_NAME:ident, $GENERATOR_TYPE:ty, $ITER:ident) => {
+        define_tbl_source!($SOURCE_NAME, $GENERATOR_TYPE, $ITER, std::convert::identity);
+    };
+    ($SOURCE_NAME:ident, $GENERATOR_TYPE:ty, $ITER:ident, $ROW:expr) => {
         pub struct $SOURCE_NAME {
             inner: $GENERATOR_TYPE,
         }
@@ -26,12 +36,13 @@ macro_rules! define_tbl_source {
                 buffer
             }
 
-            fn create(self, mut buffer: Vec<u8>) -> Vec<u8> {
-                for item in self.inner.iter() {
+            fn create(self, mut buffer: Vec<u8>) -> std::io::Result<Vec<u8>> {
+                for item in self.inner.$ITER() {
+                    let item = ($ROW)(item)?;
                     // The default Display impl writes TBL format
                     writeln!(&mut buffer, "{item}").expect("writing to memory is infallible");
                 }
-                buffer
+                Ok(buffer)
             }
         }
     };
@@ -39,8 +50,8 @@ macro_rules! define_tbl_source {
 
 // Define .tbl sources for all tables
 define_tbl_source!(VehicleTblSource, VehicleGenerator<'static>);
-define_tbl_source!(DriverTblSource, DriverGenerator<'static>);
-define_tbl_source!(CustomerTblSource, CustomerGenerator<'static>);
-define_tbl_source!(TripTblSource, TripGenerator);
-define_tbl_source!(BuildingTblSource, BuildingGenerator<'static>);
-define_tbl_source!(ZoneTblSource, ZoneGenerator);
+define_tbl_source!(DriverTblSource, DriverGenerator<'static>, try_iter);
+define_tbl_source!(CustomerTblSource, CustomerGenerator<'static>, try_iter);
+define_tbl_source!(TripTblSource, TripGenerator, try_iter);
+define_tbl_source!(BuildingTblSource, BuildingGenerator<'static>, try_iter);
+define_tbl_source!(ZoneTblSource, ZoneGenerator, try_iter);
@@ -402,6 +402,928 @@ async fn test_incompatible_options_warnings() {
         ));
 }
 
+#[test]
+fn test_zone_config_section() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("zone-config.yml");
+    fs::write(
+        &config_path,
+        "zone:\n  source: synthetic\n  subtypes:\n    - min_scale_factor: 0\n      subtypes: [country]\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--tables")
+        .arg("zone")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+
+    let zones = fs::read_to_string(temp_dir.path().join("zone.tbl")).unwrap();
+    assert_eq!(zones.lines().count(), 378);
+    assert!(zones.lines().all(|line| line.contains("|country|0||")));
+}
+
+#[test]
+fn test_zone_config_unknown_subtype() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("zone-config.yml");
+    fs::write(
+        &config_path,
+        "zone:\n  subtypes:\n    - min_scale_factor: 0\n      subtypes: [postcode]\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--tables")
+        .arg("zone")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Invalid zone configuration: Unknown zone subtype 'postcode'",
+        ));
+}
+
+#[test]
+fn test_trip_zones_config_section() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("trip-zones-config.yml");
+    fs::write(
+        &config_path,
+        "zone:\n  source: synthetic\ntrip_zones:\n  weight: uniform\n  subtypes:\n    county: 1.0\n",
+    )
+    .unwrap();
+
+    for dir in ["default", "zones"] {
+        let mut command = Command::cargo_bin("spatialbench-cli").expect("Binary not found");
+        command
+            .arg("--scale-factor")
+            .arg("0.001")
+            .arg("--tables")
+            .arg("trip")
+            .arg("--output-dir")
+            .arg(temp_dir.path().join(dir));
+        if dir == "zones" {
+            command.arg("--config").arg(&config_path);
+        }
+        command.assert().success();
+    }
+
+    let default = fs::read_to_string(temp_dir.path().join("default/trip.tbl")).unwrap();
+    let zones = fs::read_to_string(temp_dir.path().join("zones/trip.tbl")).unwrap();
+    assert_eq!(zones.lines().count(), default.lines().count());
+    assert_ne!(zones, default);
+}
+
+#[test]
+fn test_trip_zones_unknown_weight() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("trip-zones-config.yml");
+    fs::write(&config_path, "trip_zones:\n  weight: population\n").unwrap();
+
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--tables")
+        .arg("trip")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Invalid trip zone placement: Unknown zone weight 'population'",
+        ));
+}
+
+#[test]
+fn test_zone_unreadable_source() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let source = format!(
+        "ndjson:{}",
+        temp_dir.path().join("missing.ndjson").to_str().unwrap()
+    );
+
+    for format in ["tbl", "parquet"] {
+        Command::cargo_bin("spatialbench-cli")
+            .expect("Binary not found")
+            .arg("--tables")
+            .arg("zone")
+            .arg("--format")
+            .arg(format)
+            .arg("--zone-source")
+            .arg(&source)
+            .arg("--output-dir")
+            .arg(temp_dir.path())
+            .assert()
+            .failure()
+            .code(1)
+            .stderr(predicates::str::contains("Error: Failed to count zones"));
+    }
+}
+
+#[test]
+fn test_building_parcel_distribution() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("parcel-config.yml");
+    fs::write(
+        &config_path,
+        "building:\n  dist_type: parcel\n  geom_type: box\n  dim: 2\n  seed: 12345\n  width: 0.0\n  height: 0.0\n  maxseg: 0\n  polysize: 0.0\n  params:\n    type: parcel\n    srange: 0.3\n    dither: 0.2\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+
+    let buildings = fs::read_to_string(temp_dir.path().join("building.tbl")).unwrap();
+    assert_eq!(buildings.lines().count(), 571);
+    assert!(buildings.lines().all(|line| line.contains("|POLYGON((")));
+}
+
+#[test]
+fn test_building_footprint_style() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("style-config.yml");
+    fs::write(
+        &config_path,
+        "building:\n  dist_type: uniform\n  geom_type: polygon\n  dim: 2\n  seed: 12345\n  width: 0.0\n  height: 0.0\n  maxseg: 6\n  polysize: 0.0\n  polygon_style:\n    type: footprint\n    median_size: 15.0\n    size_sigma: 0.6\n  params:\n    type: none\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+
+    // most footprints are rectangles, with five coordinates
+    let buildings = fs::read_to_string(temp_dir.path().join("building.tbl")).unwrap();
+    assert_eq!(buildings.lines().count(), 571);
+    let rectangles = buildings
+        .lines()
+        .filter(|line| line.matches(',').count() == 4)
+        .count();
+    assert!((250..400).contains(&rectangles), "{rectangles} rectangles");
+}
+
+#[test]
+fn test_building_3d() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("3d-config.yml");
+    fs::write(
+        &config_path,
+        "building:\n  dist_type: uniform\n  geom_type: polygon\n  dim: 3\n  seed: 12345\n  width: 0.0\n  height: 0.0\n  maxseg: 5\n  polysize: 0.00005\n  params:\n    type: none\n",
+    )
+    .unwrap();
+
+    for format in ["tbl", "csv", "parquet"] {
+        Command::cargo_bin("spatialbench-cli")
+            .expect("Binary not found")
+            .arg("--scale-factor")
+            .arg("0.51")
+            .arg("--tables")
+            .arg("building")
+            .arg("--format")
+            .arg(format)
+            .arg("--config")
+            .arg(&config_path)
+            .arg("--output-dir")
+            .arg(temp_dir.path())
+            .assert()
+            .success();
+    }
+
+    let buildings = fs::read_to_string(temp_dir.path().join("building.tbl")).unwrap();
+    assert_eq!(buildings.lines().count(), 571);
+    for line in buildings.lines() {
+        let fields: Vec<_> = line.split('|').collect();
+        assert_eq!(fields.len(), 6, "{line}");
+        assert!(fields[2].starts_with("POLYGON Z(("), "{line}");
+        let height: f64 = fields[3].parse().unwrap();
+        let floors: i32 = fields[4].parse().unwrap();
+        assert!(floors >= 1 && height >= floors as f64 * 3.2, "{line}");
+    }
+
+    let csv = fs::read_to_string(temp_dir.path().join("building.csv")).unwrap();
+    assert_eq!(
+        csv.lines().next(),
+        Some("b_buildingkey,b_name,b_boundary,b_height,b_floors")
+    );
+
+    let file = File::open(temp_dir.path().join("building.parquet")).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+    let fields: Vec<_> = reader
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            "b_buildingkey",
+            "b_name",
+            "b_boundary",
+            "b_height",
+            "b_floors"
+        ]
+    );
+}
+
+#[test]
+fn test_building_hotspot() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("hotspot-config.yml");
+    fs::write(
+        &config_path,
+        "building:\n  dist_type: hotspot\n  geom_type: box\n  dim: 2\n  seed: 12345\n  width: 0.001\n  height: 0.001\n  maxseg: 0\n  polysize: 0.0\n  params:\n    type: hotspot\n    components:\n      - { lon: -111.76, lat: 34.87, sigma_km: 5.0, weight: 3.0 }\n    background:\n      weight: 1.0\n      dist_type: uniform\n      params:\n        type: none\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+
+    // about three quarters of the buildings are within 25 km of Sedona
+    let buildings = fs::read_to_string(temp_dir.path().join("building.tbl")).unwrap();
+    assert_eq!(buildings.lines().count(), 571);
+    let sedona = buildings
+        .lines()
+        .filter(|line| {
+            let wkt = line.split('|').nth(2).unwrap();
+            let first = wkt
+                .trim_start_matches("POLYGON((")
+                .split(',')
+                .next()
+                .unwrap();
+            let (lon, lat) = first.split_once(' ').unwrap();
+            let (lon, lat): (f64, f64) = (lon.parse().unwrap(), lat.parse().unwrap());
+            (lon + 111.76).abs() < 0.3 && (lat - 34.87).abs() < 0.25
+        })
+        .count();
+    assert!((380..480).contains(&sedona), "{sedona} buildings in Sedona");
+}
+
+#[test]
+fn test_building_non_overlapping() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("non-overlapping-config.yml");
+    let config = "regions:\n  - name: block\n    bbox: [0.0, 0.0, 0.01, 0.01]\nbuilding:\n  dist_type: uniform\n  geom_type: box\n  dim: 2\n  seed: 12345\n  width: 0.04\n  height: 0.04\n  maxseg: 0\n  polysize: 0.0\n  params:\n    type: none\n";
+    let boxes = |non_overlapping: bool| {
+        let text = if non_overlapping {
+            format!("{config}  non_overlapping: true\n")
+        } else {
+            config.to_string()
+        };
+        fs::write(&config_path, text).unwrap();
+        Command::cargo_bin("spatialbench-cli")
+            .expect("Binary not found")
+            .arg("--scale-factor")
+            .arg("0.51")
+            .arg("--tables")
+            .arg("building")
+            .arg("--config")
+            .arg(&config_path)
+            .arg("--output-dir")
+            .arg(temp_dir.path())
+            .assert()
+            .success();
+
+        // [min_x, min_y, max_x, max_y] of every box
+        let buildings = fs::read_to_string(temp_dir.path().join("building.tbl")).unwrap();
+        buildings
+            .lines()
+            .map(|line| {
+                let wkt = line.split('|').nth(2).unwrap();
+                let coords = wkt.trim_start_matches("POLYGON((").trim_end_matches("))");
+                coords.split(',').fold(
+                    [f64::MAX, f64::MAX, f64::MIN, f64::MIN],
+                    |[x0, y0, x1, y1], coord| {
+                        let (x, y) = coord.split_once(' ').unwrap();
+                        let (x, y): (f64, f64) = (x.parse().unwrap(), y.parse().unwrap());
+                        [x0.min(x), y0.min(y), x1.max(x), y1.max(y)]
+                    },
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    let overlaps = |boxes: &[[f64; 4]]| {
+        let mut count = 0;
+        for (i, a) in boxes.iter().enumerate() {
+            for b in &boxes[i + 1..] {
+                if a[0] <= b[2] && b[0] <= a[2] && a[1] <= b[3] && b[1] <= a[3] {
+                    count += 1;
+                }
+            }
+        }
+        count
+    };
+
+    let overlapping = boxes(false);
+    assert_eq!(overlapping.len(), 571);
+    assert!(overlaps(&overlapping) > 100);
+
+    // every building is placed, at another location if needed
+    let placed = boxes(true);
+    assert_eq!(placed.len(), 571);
+    assert_eq!(overlaps(&placed), 0);
+
+    // boxes too large for the region cannot all be placed
+    fs::write(
+        &config_path,
+        format!("{config}  non_overlapping: true\n")
+            .replace("width: 0.04", "width: 0.5")
+            .replace("height: 0.04", "height: 0.5"),
+    )
+    .unwrap();
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("No location without overlaps"));
+
+    // parcels never overlap
+    fs::write(
+        &config_path,
+        config.replace("uniform", "parcel").replace(
+            "type: none",
+            "type: parcel\n    srange: 0.1\n    dither: 0.0\n  non_overlapping: true",
+        ),
+    )
+    .unwrap();
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_building_metric_units() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("metric-config.yml");
+    fs::write(
+        &config_path,
+        "building:\n  dist_type: hierarchicalthomas\n  geom_type: polygon\n  dim: 2\n  seed: 12345\n  width: 0.0\n  height: 0.0\n  maxseg: 5\n  polysize: 15.0\n  units: meters\n  params:\n    type: hierarchicalthomas\n    cities: 10000\n    sub_mean: 5.0\n    sub_sd: 3.0\n    sub_min: 1\n    sub_max: 15\n    sigma_city: 8000.0\n    sigma_sub: 500.0\n    pareto_alpha_city: 1.2\n    pareto_xm_city: 1.0\n    pareto_alpha_sub: 1.0\n    pareto_xm_sub: 1.0\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+
+    // every polygon fits in a 30 m square at its latitude, in every continent
+    let buildings = fs::read_to_string(temp_dir.path().join("building.tbl")).unwrap();
+    assert_eq!(buildings.lines().count(), 571);
+    for line in buildings.lines() {
+        let wkt = line.split('|').nth(2).unwrap();
+        let coords: Vec<(f64, f64)> = wkt
+            .trim_start_matches("POLYGON((")
+            .trim_end_matches("))")
+            .split(',')
+            .map(|xy| {
+                let (x, y) = xy.split_once(' ').unwrap();
+                (x.parse().unwrap(), y.parse().unwrap())
+            })
+            .collect();
+        let extent = |values: Vec<f64>| {
+            values.iter().cloned().fold(f64::MIN, f64::max)
+                - values.iter().cloned().fold(f64::MAX, f64::min)
+        };
+        let lat = coords[0].1.to_radians();
+        let width = extent(coords.iter().map(|c| c.0).collect()) * 111_195.0 * lat.cos();
+        let height = extent(coords.iter().map(|c| c.1).collect()) * 111_195.0;
+        assert!(
+            width <= 30.5 && height <= 30.5,
+            "{width} x {height} m: {wkt}"
+        );
+    }
+
+    fs::write(&config_path, "building:\n  dist_type: uniform\n  geom_type: box\n  dim: 2\n  seed: 1\n  width: 10.0\n  height: 10.0\n  maxseg: 0\n  polysize: 0.0\n  units: feet\n  params:\n    type: none\n").unwrap();
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_shared_city_model() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("city-model-config.yml");
+    let city_model = "city_model:\n  seed: 7\n  cities: 20\n  sub_mean: 2.0\n  sub_sd: 1.0\n  sub_min: 1\n  sub_max: 3\n  sigma_city: 0.05\n  pareto_alpha_city: 1.2\n  pareto_xm_city: 1.0\n  pareto_alpha_sub: 1.0\n  pareto_xm_sub: 1.0\n";
+    let tables = "trip:\n  dist_type: citymodel\n  geom_type: point\n  dim: 2\n  seed: 56789\n  width: 0.0\n  height: 0.0\n  maxseg: 0\n  polysize: 0.0\n  params:\n    type: citymodel\n    sigma_sub: 0.0005\nbuilding:\n  dist_type: citymodel\n  geom_type: polygon\n  dim: 2\n  seed: 12345\n  width: 0.0\n  height: 0.0\n  maxseg: 5\n  polysize: 0.00001\n  params:\n    type: citymodel\n    sigma_sub: 0.0005\n";
+    fs::write(&config_path, format!("{city_model}{tables}")).unwrap();
+    let generate = |table: &str, scale_factor: &str| {
+        Command::cargo_bin("spatialbench-cli")
+            .expect("Binary not found")
+            .arg("--scale-factor")
+            .arg(scale_factor)
+            .arg("--tables")
+            .arg(table)
+            .arg("--config")
+            .arg(&config_path)
+            .arg("--output-dir")
+            .arg(temp_dir.path())
+            .assert()
+    };
+    generate("trip", "0.001").success();
+    generate("building", "0.51").success();
+
+    let lon_lat = |wkt: &str| -> (f64, f64) {
+        let xy = wkt
+            .trim_start_matches("POINT(")
+            .trim_start_matches("POLYGON((")
+            .split([',', ')'])
+            .next()
+            .unwrap();
+        let (x, y) = xy.split_once(' ').unwrap();
+        (x.parse().unwrap(), y.parse().unwrap())
+    };
+    let buildings: Vec<_> = fs::read_to_string(temp_dir.path().join("building.tbl"))
+        .unwrap()
+        .lines()
+        .map(|line| lon_lat(line.split('|').nth(2).unwrap()))
+        .collect();
+    let trips = fs::read_to_string(temp_dir.path().join("trip.tbl")).unwrap();
+
+    // pickups are drawn around the same subclusters as the buildings
+    let near = |trips: &str| {
+        trips
+            .lines()
+            .map(|line| lon_lat(line.split('|').nth(10).unwrap()))
+            .filter(|(x, y)| {
+                buildings
+                    .iter()
+                    .any(|(bx, by)| (bx - x).hypot(by - y) < 0.5)
+            })
+            .count()
+    };
+    assert_eq!(trips.lines().count(), 6000);
+    let shared = near(&trips);
+
+    // trips of a hierarchical Thomas distribution with the same parameters have
+    // cities of their own
+    let own_cities = "trip:\n  dist_type: hierarchicalthomas\n  geom_type: point\n  dim: 2\n  seed: 56789\n  width: 0.0\n  height: 0.0\n  maxseg: 0\n  polysize: 0.0\n  params:\n    type: hierarchicalthomas\n    cities: 20\n    sub_mean: 2.0\n    sub_sd: 1.0\n    sub_min: 1\n    sub_max: 3\n    sigma_city: 0.05\n    sigma_sub: 0.0005\n    pareto_alpha_city: 1.2\n    pareto_xm_city: 1.0\n    pareto_alpha_sub: 1.0\n    pareto_xm_sub: 1.0\n";
+    fs::write(&config_path, own_cities).unwrap();
+    generate("trip", "0.001").success();
+    let independent = near(&fs::read_to_string(temp_dir.path().join("trip.tbl")).unwrap());
+    assert!(shared > 4800, "{shared} pickups near a building");
+    assert!(
+        independent < shared / 2,
+        "{independent} pickups near a building"
+    );
+
+    // the citymodel distribution needs the city_model section
+    fs::write(&config_path, tables).unwrap();
+    generate("building", "0.51").failure();
+}
+
+#[test]
+fn test_building_regions() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("regions-config.yml");
+    fs::write(
+        &config_path,
+        "regions:\n  - name: iberia\n    bbox: [-9.5, 36.0, 3.3, 43.8]\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+
+    let buildings = fs::read_to_string(temp_dir.path().join("building.tbl")).unwrap();
+    assert_eq!(buildings.lines().count(), 571);
+    for line in buildings.lines() {
+        let wkt = line.split('|').nth(2).unwrap();
+        let coords = wkt.trim_start_matches("POLYGON((").trim_end_matches("))");
+        for coord in coords.split(',') {
+            let (x, y) = coord.split_once(' ').unwrap();
+            let (x, y): (f64, f64) = (x.parse().unwrap(), y.parse().unwrap());
+            assert!(
+                (-10.0..=4.0).contains(&x) && (35.5..=44.5).contains(&y),
+                "{line}"
+            );
+        }
+    }
+
+    // a region needs either a bbox or an affine
+    fs::write(
+        &config_path,
+        "regions:\n  - name: iberia\n    weight: 1.0\n",
+    )
+    .unwrap();
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Invalid regions"));
+
+    // weights are set on every region or on none
+    fs::write(
+        &config_path,
+        "regions:\n  - name: iberia\n    bbox: [-9.5, 36.0, 3.3, 43.8]\n    weight: 1.0\n  - name: italy\n    bbox: [6.6, 36.6, 18.5, 47.1]\n",
+    )
+    .unwrap();
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Region 'italy' has no weight; set the weight of every region or of none",
+        ));
+}
+
+#[test]
+fn test_building_land_mask() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("land-config.yml");
+    fs::write(&config_path, "land_mask: true\n").unwrap();
+
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+
+    // buildings in water are redrawn, the others are unchanged
+    let masked = fs::read_to_string(temp_dir.path().join("building.tbl")).unwrap();
+    let default = read_reference_file("building", "v1");
+    assert_eq!(masked.lines().count(), 571);
+    let unchanged = masked
+        .lines()
+        .zip(default.lines())
+        .filter(|(a, b)| a == b)
+        .count();
+    assert!(unchanged > 0 && unchanged < 571, "{unchanged} unchanged");
+}
+
+#[test]
+fn test_building_footprints() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("footprint-config.yml");
+    fs::write(
+        &config_path,
+        "building:\n  dist_type: uniform\n  geom_type: box\n  dim: 2\n  seed: 12345\n  width: 0.001\n  height: 0.001\n  maxseg: 0\n  polysize: 0.0\n  params:\n    type: none\n  footprint:\n    courtyard: 1.0\n    multipolygon: 0.5\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+
+    // every polygon has a courtyard, and about half the buildings are campuses
+    let buildings = fs::read_to_string(temp_dir.path().join("building.tbl")).unwrap();
+    assert_eq!(buildings.lines().count(), 571);
+    assert!(buildings.lines().all(|line| line.contains("),(")));
+    let campuses = buildings
+        .lines()
+        .filter(|line| line.contains("|MULTIPOLYGON((("))
+        .count();
+    assert!((200..370).contains(&campuses), "{campuses} campuses");
+
+    // footprint options belong to the building section
+    fs::write(
+        &config_path,
+        "trip:\n  dist_type: uniform\n  geom_type: point\n  dim: 2\n  seed: 1\n  width: 0.0\n  height: 0.0\n  maxseg: 0\n  polysize: 0.0\n  params:\n    type: none\n  footprint:\n    courtyard: 1.0\n",
+    )
+    .unwrap();
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("apply to buildings only"));
+}
+
+#[test]
+fn test_building_validity() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("validity-config.yml");
+    fs::write(&config_path, "validity: strict\n").unwrap();
+
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+
+    // strict footprints are drawn differently from the default raw ones
+    let strict = fs::read_to_string(temp_dir.path().join("building.tbl")).unwrap();
+    let raw = read_reference_file("building", "v1");
+    assert_eq!(strict.lines().count(), 571);
+    assert!(strict.lines().all(|line| line.contains("|POLYGON((")));
+    assert_ne!(strict, raw);
+
+    fs::write(&config_path, "validity: sometimes\n").unwrap();
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("building")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Unknown validity 'sometimes'"));
+}
+
+#[test]
+fn test_customer_and_driver_locations() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("locations-config.yml");
+    let section = |table: &str, geom_type: &str| {
+        format!("{table}:\n  dist_type: uniform\n  geom_type: {geom_type}\n  dim: 2\n  seed: 24680\n  width: 0.0\n  height: 0.0\n  maxseg: 0\n  polysize: 0.0\n  params:\n    type: none\n")
+    };
+    fs::write(
+        &config_path,
+        format!(
+            "{}{}",
+            section("customer", "point"),
+            section("driver", "point")
+        ),
+    )
+    .unwrap();
+
+    for format in ["tbl", "csv", "parquet"] {
+        Command::cargo_bin("spatialbench-cli")
+            .expect("Binary not found")
+            .arg("--scale-factor")
+            .arg("0.51")
+            .arg("--tables")
+            .arg("customer,driver")
+            .arg("--format")
+            .arg(format)
+            .arg("--config")
+            .arg(&config_path)
+            .arg("--output-dir")
+            .arg(temp_dir.path())
+            .assert()
+            .success();
+    }
+
+    // the location is appended to the default columns
+    for table in ["customer", "driver"] {
+        let located = fs::read_to_string(temp_dir.path().join(format!("{table}.tbl"))).unwrap();
+        let reference = read_reference_file(table, "v1");
+        assert_eq!(located.lines().count(), reference.lines().count());
+        for (line, default) in located.lines().zip(reference.lines()) {
+            let location = line.strip_prefix(default).unwrap();
+            assert!(
+                location.starts_with("POINT(") && location.ends_with(")|"),
+                "{line}"
+            );
+        }
+    }
+
+    let csv = fs::read_to_string(temp_dir.path().join("customer.csv")).unwrap();
+    assert_eq!(
+        csv.lines().next(),
+        Some("c_custkey,c_name,c_address,c_region,c_nation,c_phone,c_homeloc")
+    );
+    let csv = fs::read_to_string(temp_dir.path().join("driver.csv")).unwrap();
+    assert_eq!(
+        csv.lines().next(),
+        Some("d_driverkey,d_name,d_address,d_region,d_nation,d_phone,d_baseloc")
+    );
+
+    let file = File::open(temp_dir.path().join("customer.parquet")).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+    let last = reader.schema().fields().last().unwrap().name().clone();
+    assert_eq!(last, "c_homeloc");
+
+    fs::write(&config_path, section("driver", "polygon")).unwrap();
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("driver")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("driver geometry must be point"));
+
+    // only trips take line strings
+    fs::write(&config_path, section("driver", "linestring")).unwrap();
+    Command::cargo_bin("spatialbench-cli")
+        .expect("Binary not found")
+        .arg("--scale-factor")
+        .arg("0.51")
+        .arg("--tables")
+        .arg("driver")
+        .arg("--config")
+        .arg(&config_path)
+        .arg("--output-dir")
+        .arg(temp_dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("driver geometry must be point"));
+
+    // locations stay in the nation of each record, which hotspot centers and
+    // spreads in meters would not
+    let hotspot = "customer:\n  dist_type: hotspot\n  geom_type: point\n  dim: 2\n  seed: 24680\n  width: 0.0\n  height: 0.0\n  maxseg: 0\n  polysize: 0.0\n  params:\n    type: hotspot\n    components:\n      - { lon: -111.76, lat: 34.87, sigma_km: 5.0, weight: 1.0 }\n";
+    let meters = section("customer", "point").replace("  params:", "  units: meters\n  params:");
+    for (config, error) in [
+        (
+            hotspot.to_string(),
+            "customer locations cannot use the hotspot distribution",
+        ),
+        (meters, "customer locations must use unit sizes"),
+    ] {
+        fs::write(&config_path, config).unwrap();
+        Command::cargo_bin("spatialbench-cli")
+            .expect("Binary not found")
+            .arg("--scale-factor")
+            .arg("0.51")
+            .arg("--tables")
+            .arg("customer")
+            .arg("--config")
+            .arg(&config_path)
+            .arg("--output-dir")
+            .arg(temp_dir.path())
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(error));
+    }
+}
+
+#[test]
+fn test_trip_linestring_routes() {
+    let temp_dir = tempdir().expect("Failed to create temporary directory");
+    let config_path = temp_dir.path().join("routes-config.yml");
+    let section = |geom_type: &str| {
+        format!("trip:\n  dist_type: uniform\n  geom_type: {geom_type}\n  dim: 2\n  seed: 56789\n  width: 0.0\n  height: 0.0\n  maxseg: 6\n  polysize: 0.01\n  params:\n    type: none\n")
+    };
+    let generate = |geom_type: &str| {
+        fs::write(&config_path, section(geom_type)).unwrap();
+        Command::cargo_bin("spatialbench-cli")
+            .expect("Binary not found")
+            .arg("--scale-factor")
+            .arg("0.001")
+            .arg("--tables")
+            .arg("trip")
+            .arg("--config")
+            .arg(&config_path)
+            .arg("--output-dir")
+            .arg(temp_dir.path())
+            .assert()
+            .success();
+        fs::read_to_string(temp_dir.path().join("trip.tbl")).unwrap()
+    };
+    let point = |wkt: &str| -> (f64, f64) {
+        let xy = wkt.trim_start_matches("POINT(").trim_end_matches(')');
+        let (x, y) = xy.split_once(' ').unwrap();
+        (x.parse().unwrap(), y.parse().unwrap())
+    };
+    let straight = |line: &str| {
+        let fields: Vec<_> = line.split('|').collect();
+        let ((x0, y0), (x1, y1)) = (point(fields[10]), point(fields[11]));
+        (x1 - x0).hypot(y1 - y0)
+    };
+
+    // trips follow a route of the trip distance, so the dropoff is closer to
+    // the pickup than at the end of a straight trip of the same distance
+    let straight_trips = generate("point");
+    let routes = generate("linestring");
+    assert_eq!(routes.lines().count(), 6000);
+    let mut winding = 0;
+    for (route, trip) in routes.lines().zip(straight_trips.lines()) {
+        assert_eq!(route.split('|').nth(9), trip.split('|').nth(9));
+        assert!(straight(route) <= straight(trip) + 1e-7, "{route}");
+        if straight(route) < straight(trip) * 0.9 {
+            winding += 1;
+        }
+    }
+    assert!(winding > 1000, "{winding} winding routes");
+}
+
 fn read_gzipped_file_to_string<P: AsRef<Path>>(path: P) -> Result<String, std::io::Error> {
     let file = File::open(path)?;
     let mut decoder = flate2::read::GzDecoder::new(file);
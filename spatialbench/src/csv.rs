@@ -1,7 +1,11 @@
 //! CSV formatting support for the row struct objects generated by the library.
 
-use crate::generators::{Building, Customer, Driver, Trip, Vehicle, Zone};
+use crate::generators::{
+    Building, BuildingGenerator, Customer, CustomerGenerator, Driver, DriverGenerator, GeometryZ,
+    OptionalKey, OptionalRect, Trip, Vehicle, Zone, ZoneGenerator,
+};
 use core::fmt;
+use geo::Geometry;
 use std::fmt::Display;
 
 /// Write [`Vehicle`]s in CSV format.
@@ -93,6 +97,16 @@ impl DriverCsv {
     pub fn header() -> &'static str {
         "d_driverkey,d_name,d_address,d_region,d_nation,d_phone"
     }
+
+    /// Returns the CSV header for the drivers of `generator`, which has the
+    /// `d_baseloc` column when drivers are generated with base locations
+    pub fn header_for(generator: &DriverGenerator) -> &'static str {
+        if generator.has_base_locations() {
+            "d_driverkey,d_name,d_address,d_region,d_nation,d_phone,d_baseloc"
+        } else {
+            Self::header()
+        }
+    }
 }
 
 impl Display for DriverCsv {
@@ -107,7 +121,15 @@ impl Display for DriverCsv {
             self.inner.d_region,
             self.inner.d_nation,
             self.inner.d_phone,
-        )
+        )?;
+        if let Some(baseloc) = self.inner.d_baseloc {
+            write!(
+                f,
+                ",\"{}\"",
+                GeometryZ::new(&Geometry::Point(baseloc), None)
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -147,6 +169,16 @@ impl<'a> CustomerCsv<'a> {
     pub fn header() -> &'static str {
         "c_custkey,c_name,c_address,c_region,c_nation,c_phone"
     }
+
+    /// Returns the CSV header for the customers of `generator`, which has the
+    /// `c_homeloc` column when customers are generated with home locations
+    pub fn header_for(generator: &CustomerGenerator) -> &'static str {
+        if generator.has_home_locations() {
+            "c_custkey,c_name,c_address,c_region,c_nation,c_phone,c_homeloc"
+        } else {
+            Self::header()
+        }
+    }
 }
 
 impl Display for CustomerCsv<'_> {
@@ -161,7 +193,15 @@ impl Display for CustomerCsv<'_> {
             self.inner.c_region,
             self.inner.c_nation,
             self.inner.c_phone,
-        )
+        )?;
+        if let Some(homeloc) = self.inner.c_homeloc {
+            write!(
+                f,
+                ",\"{}\"",
+                GeometryZ::new(&Geometry::Point(homeloc), None)
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -201,7 +241,7 @@ impl Display for TripCsv {
         write!(
             f,
             // note must quote location and comment fields as they may contain commas
-            "{},{},{},{},{},{},{},{},{},{},\"{:?}\",\"{:?}\"",
+            "{},{},{},{},{},{},{},{},{},{},\"{}\",\"{}\"",
             self.inner.t_tripkey,
             self.inner.t_custkey,
             self.inner.t_driverkey,
@@ -212,8 +252,14 @@ impl Display for TripCsv {
             self.inner.t_tip,
             self.inner.t_totalamount,
             self.inner.t_distance,
-            self.inner.t_pickuploc,
-            self.inner.t_dropoffloc,
+            GeometryZ::new(
+                &Geometry::Point(self.inner.t_pickuploc),
+                self.inner.t_pickup_z
+            ),
+            GeometryZ::new(
+                &Geometry::Point(self.inner.t_dropoffloc),
+                self.inner.t_dropoff_z
+            ),
         )
     }
 }
@@ -247,6 +293,16 @@ impl<'a> BuildingCsv<'a> {
     pub fn header() -> &'static str {
         "b_buildingkey,b_name,b_boundary"
     }
+
+    /// Returns the CSV header for the buildings of `generator`, which has the
+    /// `b_height` and `b_floors` columns when buildings are generated in 3D
+    pub fn header_for(generator: &BuildingGenerator) -> &'static str {
+        if generator.is_3d() {
+            "b_buildingkey,b_name,b_boundary,b_height,b_floors"
+        } else {
+            Self::header()
+        }
+    }
 }
 
 impl Display for BuildingCsv<'_> {
@@ -254,9 +310,15 @@ impl Display for BuildingCsv<'_> {
         write!(
             f,
             // note must quote the comment field as it may contain commas
-            "{},{},\"{:?}\"",
-            self.inner.b_buildingkey, self.inner.b_name, self.inner.b_boundary,
-        )
+            "{},{},\"{}\"",
+            self.inner.b_buildingkey,
+            self.inner.b_name,
+            GeometryZ::new(&self.inner.footprint(), self.inner.b_elevation),
+        )?;
+        if let (Some(height), Some(floors)) = (self.inner.b_height, self.inner.b_floors) {
+            write!(f, ",{height:.2},{floors}")?;
+        }
+        Ok(())
     }
 }
 
@@ -287,7 +349,17 @@ impl ZoneCsv {
 
     /// Returns the CSV header for the Zone table
     pub fn header() -> &'static str {
-        "z_zonekey,z_gersid,z_country,z_region,z_name,z_subtype,z_boundary"
+        "z_zonekey,z_gersid,z_country,z_region,z_name,z_subtype,z_level,z_parentkey,z_boundary"
+    }
+
+    /// Returns the CSV header for the zones of `generator`, which has the
+    /// `z_bbox` column when bounding boxes are enabled
+    pub fn header_for(generator: &ZoneGenerator) -> &'static str {
+        if generator.has_bbox() {
+            "z_zonekey,z_gersid,z_country,z_region,z_name,z_subtype,z_level,z_parentkey,z_boundary,z_bbox"
+        } else {
+            Self::header()
+        }
     }
 }
 
@@ -295,14 +367,21 @@ impl Display for ZoneCsv {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "{},{},{},{},{},{},\"{:?}\"",
+            "{},{},{},{},{},{},{},{},\"{:?}\"",
             self.inner.z_zonekey,
             self.inner.z_gersid,
             self.inner.z_country,
             self.inner.z_region,
             self.inner.z_name,
             self.inner.z_subtype,
+            self.inner.z_level,
+            OptionalKey(self.inner.z_parentkey),
             self.inner.z_boundary,
-        )
+        )?;
+        // the WKT of the bounding box contains commas, so it is quoted
+        if self.inner.z_bbox.is_some() {
+            write!(f, ",\"{}\"", OptionalRect(self.inner.z_bbox))?;
+        }
+        Ok(())
     }
 }
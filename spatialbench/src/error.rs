@@ -0,0 +1,90 @@
+//! [`SpatialBenchError`]: errors of the generators that can fail.
+//!
+//! Most tables are generated from seeds alone and cannot fail. The Zone table (and
+//! trips placed in zones) is read from a [`ZoneSource`] through DuckDB, and spatial
+//! generators can be built from user configuration; these report failures with
+//! this error instead of producing empty or partial tables. Geometries that cannot
+//! be encoded and Arrow batches that cannot be built are reported the same way.
+//!
+//! [`ZoneSource`]: crate::zone::ZoneSource
+
+use geozero::error::GeozeroError;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Result type of the fallible generator APIs
+pub type Result<T, E = SpatialBenchError> = std::result::Result<T, E>;
+
+/// Error returned by generators that read data or are built from configuration
+#[derive(Debug)]
+pub enum SpatialBenchError {
+    /// Invalid configuration, e.g. an unknown zone subtype or distribution
+    /// parameters that do not match the distribution type
+    InvalidConfig(String),
+    /// The zone source could not be read or returned no usable zones
+    ZoneSource(String),
+    /// A DuckDB query on the zone source or the zone cache failed
+    DuckDb(duckdb::Error),
+    /// A geometry could not be encoded or decoded
+    Geometry(GeozeroError),
+    /// Reading or writing a local file failed
+    Io(io::Error),
+    /// Converting the rows to an output format failed, e.g. building an Arrow
+    /// record batch
+    Output(Box<dyn Error + Send + Sync>),
+}
+
+impl fmt::Display for SpatialBenchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpatialBenchError::InvalidConfig(msg) => write!(f, "Invalid configuration: {msg}"),
+            SpatialBenchError::ZoneSource(msg) => write!(f, "Zone source error: {msg}"),
+            SpatialBenchError::DuckDb(e) => write!(f, "DuckDB error: {e}"),
+            SpatialBenchError::Geometry(e) => write!(f, "Geometry error: {e}"),
+            SpatialBenchError::Io(e) => write!(f, "IO error: {e}"),
+            SpatialBenchError::Output(e) => write!(f, "Output error: {e}"),
+        }
+    }
+}
+
+impl Error for SpatialBenchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SpatialBenchError::InvalidConfig(_) | SpatialBenchError::ZoneSource(_) => None,
+            SpatialBenchError::DuckDb(e) => Some(e),
+            SpatialBenchError::Geometry(e) => Some(e),
+            SpatialBenchError::Io(e) => Some(e),
+            SpatialBenchError::Output(e) => Some(e.as_ref()),
+        }
+    }
+}
+
+impl From<duckdb::Error> for SpatialBenchError {
+    fn from(e: duckdb::Error) -> Self {
+        SpatialBenchError::DuckDb(e)
+    }
+}
+
+impl From<GeozeroError> for SpatialBenchError {
+    fn from(e: GeozeroError) -> Self {
+        SpatialBenchError::Geometry(e)
+    }
+}
+
+impl From<io::Error> for SpatialBenchError {
+    fn from(e: io::Error) -> Self {
+        SpatialBenchError::Io(e)
+    }
+}
+
+/// Lets output code that works with [`io::Error`] propagate generator errors
+impl From<SpatialBenchError> for io::Error {
+    fn from(e: SpatialBenchError) -> Self {
+        match e {
+            SpatialBenchError::Io(e) => e,
+            SpatialBenchError::InvalidConfig(_) => io::Error::new(io::ErrorKind::InvalidInput, e),
+            e => io::Error::other(e),
+        }
+    }
+}
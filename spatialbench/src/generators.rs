@@ -4,26 +4,46 @@ use crate::dates::{GenerateUtils, TPCHDate};
 use crate::decimal::TPCHDecimal;
 use crate::distribution::Distribution;
 use crate::distribution::Distributions;
+use crate::error::SpatialBenchError;
 use crate::random::RandomPhoneNumber;
 use crate::random::RowRandomInt;
 use crate::random::{PhoneNumberInstance, RandomBoundedLong, StringSequenceInstance};
 use crate::random::{RandomAlphaNumeric, RandomAlphaNumericInstance};
 use crate::random::{RandomBoundedInt, RandomString, RandomStringSequence, RandomText};
+use crate::spatial::elevation::{building_floors, building_height, ground_elevation};
+use crate::spatial::geometry::{shape_footprint, strict_footprint};
+use crate::spatial::hardcore::HardCorePlacement;
+use crate::spatial::land::{LandMask, MAX_LAND_ATTEMPTS};
+use crate::spatial::nations::{nation_areas, NationArea, MAX_NATION_ATTEMPTS};
 use crate::spatial::overrides as spatial_overrides;
-use crate::spatial::utils::continent::{build_continent_cdf, WeightedTarget};
+use crate::spatial::utils::continent::{build_continent_cdf, build_region_cdf, WeightedTarget};
 use crate::spatial::utils::{hash_to_unit_u64, spider_seed_for_index};
-use crate::spatial::{ContinentAffines, SpatialDefaults, SpatialGenerator};
+use crate::spatial::{
+    validate_regions, ContinentAffines, DistributionType, FootprintOptions, GeomType, Region,
+    SizeUnit, SpatialDefaults, SpatialDistribution, SpatialGenerator, Validity,
+};
 use crate::text::TextPool;
+use crate::zone::{cache, hierarchy, synthetic};
+use crate::zone::{
+    subtype_rank, subtype_zone_count, GeometryEncoding, ZoneConfig, ZonePlacement, ZoneSampler,
+    ZoneSource, ZONE_SUBTYPES,
+};
 use duckdb::Connection;
+use geo::BoundingRect;
 use geo::Geometry;
 use geo::Point;
-use geozero::{wkb::Wkb, ToGeo};
-use log::{debug, error, info};
+use geo::Rect;
+use geozero::error::GeozeroError;
+use geozero::{
+    geojson::GeoJson, wkb::Wkb, CoordDimensions, GeomProcessor, GeozeroGeometry, ToGeo, ToWkb,
+};
+use log::{debug, info};
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
-use std::convert::TryInto;
 use std::fmt;
 use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
 use std::time::Instant;
 
 /// A Vehicle Manufacturer, formatted as `"Manufacturer#<n>"`
@@ -341,6 +361,9 @@ pub struct Driver {
     pub d_nation: String,
     /// Driver phone number
     pub d_phone: PhoneNumberInstance,
+    /// Base location in the nation of the driver, when drivers are generated
+    /// with base locations
+    pub d_baseloc: Option<Point>,
 }
 
 impl Display for Driver {
@@ -354,7 +377,11 @@ impl Display for Driver {
             self.d_region,
             self.d_nation,
             self.d_phone
-        )
+        )?;
+        if let Some(baseloc) = self.d_baseloc {
+            write!(f, "{}|", GeometryZ::new(&Geometry::Point(baseloc), None))?;
+        }
+        Ok(())
     }
 }
 
@@ -366,6 +393,8 @@ pub struct DriverGenerator<'a> {
     part_count: i32,
     distributions: &'a Distributions,
     text_pool: &'a TextPool,
+    base_locations: Option<SpatialGenerator>,
+    land_mask: Option<&'static LandMask>,
 }
 
 impl<'a> DriverGenerator<'a> {
@@ -392,17 +421,44 @@ impl<'a> DriverGenerator<'a> {
 
     /// Creates a new DriverGenerator with the given scale factor
     ///
+    /// Drivers have base locations if the driver override is set.
+    ///
     /// Note the generator's lifetime is `&'static`. See [`DriverGenerator`] for
     /// more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the driver override cannot generate base locations; see
+    /// [`Self::try_new`].
     pub fn new(scale_factor: f64, part: i32, part_count: i32) -> DriverGenerator<'static> {
+        Self::try_new(scale_factor, part, part_count)
+            .unwrap_or_else(|e| panic!("Invalid driver locations: {e}"))
+    }
+
+    /// Creates a new DriverGenerator with the given scale factor, or the error
+    /// validating the generator of the driver override
+    pub fn try_new(
+        scale_factor: f64,
+        part: i32,
+        part_count: i32,
+    ) -> Result<DriverGenerator<'static>, SpatialBenchError> {
         // Note: use explicit lifetime to ensure this remains `&'static`
-        Self::new_with_distributions_and_text_pool(
+        let generator = Self::new_with_distributions_and_text_pool(
             scale_factor,
             part,
             part_count,
             Distributions::static_default(),
             TextPool::get_or_init_default(),
-        )
+        );
+        let generator = match spatial_overrides::driver_locations() {
+            Some(spatial_gen) => generator.with_base_locations(spatial_gen)?,
+            None => generator,
+        };
+        if spatial_overrides::land_mask() {
+            Ok(generator.with_land_mask())
+        } else {
+            Ok(generator)
+        }
     }
 
     /// Creates a DriverGenerator with specified distributions and text pool
@@ -419,17 +475,58 @@ impl<'a> DriverGenerator<'a> {
             part_count,
             distributions,
             text_pool,
+            base_locations: None,
+            land_mask: None,
         }
     }
 
+    /// Generate the `d_baseloc` column, placing each driver in the outline of
+    /// its nation with `spatial_gen`, which must generate points
+    ///
+    /// See [`SpatialDefaults::driver_default`] for the default distribution.
+    ///
+    /// Returns an error if `spatial_gen` generates other geometries, uses the
+    /// hotspot distribution or sizes in meters, as their locations would not
+    /// be placed in the nation of each driver.
+    pub fn with_base_locations(
+        mut self,
+        spatial_gen: SpatialGenerator,
+    ) -> Result<Self, SpatialBenchError> {
+        NationLocations::validate("driver", &spatial_gen)?;
+        let cardinality = Self::calculate_row_count(self.scale_factor, 1, 1) as u64;
+        self.base_locations = Some(spatial_gen.with_cardinality(cardinality));
+        Ok(self)
+    }
+
+    /// Redraw base locations that fall in water, using the bundled land mask
+    pub fn with_land_mask(mut self) -> Self {
+        self.land_mask = Some(LandMask::bundled());
+        self
+    }
+
+    /// Whether drivers have the `d_baseloc` column
+    pub fn has_base_locations(&self) -> bool {
+        self.base_locations.is_some()
+    }
+
     /// Return the row count for the given scale factor and generator part count
     pub fn calculate_row_count(scale_factor: f64, part: i32, part_count: i32) -> i64 {
         GenerateUtils::calculate_row_count(Self::SCALE_BASE, scale_factor, part, part_count)
     }
 
     /// Returns an iterator over the Driver rows
+    ///
+    /// The iterator panics if a base location cannot be generated; see
+    /// [`Self::try_iter`].
     pub fn iter(&self) -> DriverGeneratorIterator<'a> {
-        DriverGeneratorIterator::new(
+        DriverGeneratorIterator(self.try_iter())
+    }
+
+    /// Returns an iterator over the Driver rows that yields the error generating
+    /// a base location, e.g. from a custom distribution that does not generate
+    /// points, after which it ends
+    pub fn try_iter(&self) -> TryDriverGeneratorIterator<'a> {
+        TryDriverGeneratorIterator::new(
             self.distributions,
             self.text_pool,
             GenerateUtils::calculate_start_index(
@@ -440,6 +537,7 @@ impl<'a> DriverGenerator<'a> {
             ),
             Self::calculate_row_count(self.scale_factor, self.part, self.part_count),
         )
+        .with_locations(self.base_locations.clone(), self.land_mask)
     }
 }
 
@@ -453,8 +551,26 @@ impl<'a> IntoIterator for DriverGenerator<'a> {
 }
 
 /// Iterator that generates Driver rows
+///
+/// Panics if a base location cannot be generated; see
+/// [`DriverGenerator::try_iter`].
+#[derive(Debug)]
+pub struct DriverGeneratorIterator<'a>(TryDriverGeneratorIterator<'a>);
+
+impl Iterator for DriverGeneratorIterator<'_> {
+    type Item = Driver;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .next()
+            .map(|driver| driver.unwrap_or_else(|e| panic!("Failed to generate drivers: {e}")))
+    }
+}
+
+/// Iterator that generates Driver rows, or the error generating a base location
+/// after which it ends
 #[derive(Debug)]
-pub struct DriverGeneratorIterator<'a> {
+pub struct TryDriverGeneratorIterator<'a> {
     address_random: RandomAlphaNumeric,
     nation_key_random: RandomBoundedInt,
     phone_random: RandomPhoneNumber,
@@ -469,12 +585,14 @@ pub struct DriverGeneratorIterator<'a> {
     nations: &'a Distribution,
     regions: &'a Distribution,
 
+    locations: Option<NationLocations>,
+
     start_index: i64,
     row_count: i64,
     index: i64,
 }
 
-impl<'a> DriverGeneratorIterator<'a> {
+impl<'a> TryDriverGeneratorIterator<'a> {
     fn new(
         distributions: &'a Distributions,
         text_pool: &'a TextPool,
@@ -513,7 +631,7 @@ impl<'a> DriverGeneratorIterator<'a> {
         bbb_offset_random.advance_rows(start_index);
         bbb_type_random.advance_rows(start_index);
 
-        DriverGeneratorIterator {
+        TryDriverGeneratorIterator {
             address_random,
             nation_key_random,
             phone_random,
@@ -528,30 +646,50 @@ impl<'a> DriverGeneratorIterator<'a> {
             nations: distributions.nations(),
             regions: distributions.regions(),
 
+            locations: None,
+
             start_index,
             row_count,
             index: 0,
         }
     }
 
+    /// Places the drivers in their nation with `spatial_gen`, if any
+    fn with_locations(
+        mut self,
+        spatial_gen: Option<SpatialGenerator>,
+        land_mask: Option<&'static LandMask>,
+    ) -> Self {
+        self.locations = spatial_gen
+            .map(|spatial_gen| NationLocations::new(spatial_gen, self.nations, land_mask));
+        self
+    }
+
     /// Creates a Driver with the given key
-    fn make_driver(&mut self, driver_key: i64) -> Driver {
+    fn make_driver(&mut self, driver_key: i64) -> Result<Driver, SpatialBenchError> {
         let nation_key = self.nation_key_random.next_value();
         let nation = self.nations.get_value(nation_key as usize);
         let region = self
             .regions
             .get_value(self.nations.get_weight(nation_key as usize) as usize);
 
-        Driver {
+        Ok(Driver {
             d_driverkey: driver_key,
             d_name: DriverName::new(driver_key),
             d_address: self.address_random.next_value(),
             d_region: region.to_string(), // Convert &str to String
             d_nation: nation.to_string(), // Convert &str to String
             d_phone: self.phone_random.next_value(nation_key as i64),
-        }
+            d_baseloc: self
+                .locations
+                .as_ref()
+                .map(|locations| locations.generate(nation_key as usize, driver_key as u64))
+                .transpose()?,
+        })
     }
+}
 
+impl DriverGeneratorIterator<'_> {
     /// Selects a driver for a vehicle, with drivers table 5x the size of vehicles table
     pub fn select_driver(vehicle_key: i64, driver_number: i64, scale_factor: f64) -> i64 {
         // Use supplier generator's scale base
@@ -566,8 +704,8 @@ impl<'a> DriverGeneratorIterator<'a> {
     }
 }
 
-impl Iterator for DriverGeneratorIterator<'_> {
-    type Item = Driver;
+impl Iterator for TryDriverGeneratorIterator<'_> {
+    type Item = Result<Driver, SpatialBenchError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.index >= self.row_count {
@@ -587,6 +725,9 @@ impl Iterator for DriverGeneratorIterator<'_> {
         self.bbb_type_random.row_finished();
 
         self.index += 1;
+        if driver.is_err() {
+            self.index = self.row_count;
+        }
 
         Some(driver)
     }
@@ -632,6 +773,9 @@ pub struct Customer<'a> {
     pub c_nation: &'a str,
     /// Customer phone number
     pub c_phone: PhoneNumberInstance,
+    /// Home location in the nation of the customer, when customers are
+    /// generated with home locations
+    pub c_homeloc: Option<Point>,
 }
 
 impl Display for Customer<'_> {
@@ -640,7 +784,11 @@ impl Display for Customer<'_> {
             f,
             "{}|{}|{}|{}|{}|{}|",
             self.c_custkey, self.c_name, self.c_address, self.c_region, self.c_nation, self.c_phone,
-        )
+        )?;
+        if let Some(homeloc) = self.c_homeloc {
+            write!(f, "{}|", GeometryZ::new(&Geometry::Point(homeloc), None))?;
+        }
+        Ok(())
     }
 }
 
@@ -652,6 +800,8 @@ pub struct CustomerGenerator<'a> {
     part_count: i32,
     distributions: &'a Distributions,
     text_pool: &'a TextPool,
+    home_locations: Option<SpatialGenerator>,
+    land_mask: Option<&'static LandMask>,
 }
 
 impl<'a> CustomerGenerator<'a> {
@@ -666,17 +816,44 @@ impl<'a> CustomerGenerator<'a> {
 
     /// Creates a new CustomerGenerator with the given scale factor
     ///
+    /// Customers have home locations if the customer override is set.
+    ///
     /// Note the generator's lifetime is `&'static`. See [`CustomerGenerator`] for
     /// more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the customer override cannot generate home locations; see
+    /// [`Self::try_new`].
     pub fn new(scale_factor: f64, part: i32, part_count: i32) -> CustomerGenerator<'static> {
+        Self::try_new(scale_factor, part, part_count)
+            .unwrap_or_else(|e| panic!("Invalid customer locations: {e}"))
+    }
+
+    /// Creates a new CustomerGenerator with the given scale factor, or the error
+    /// validating the generator of the customer override
+    pub fn try_new(
+        scale_factor: f64,
+        part: i32,
+        part_count: i32,
+    ) -> Result<CustomerGenerator<'static>, SpatialBenchError> {
         // Note: use explicit lifetime to ensure this remains `&'static`
-        Self::new_with_distributions_and_text_pool(
+        let generator = Self::new_with_distributions_and_text_pool(
             scale_factor,
             part,
             part_count,
             Distributions::static_default(),
             TextPool::get_or_init_default(),
-        )
+        );
+        let generator = match spatial_overrides::customer_locations() {
+            Some(spatial_gen) => generator.with_home_locations(spatial_gen)?,
+            None => generator,
+        };
+        if spatial_overrides::land_mask() {
+            Ok(generator.with_land_mask())
+        } else {
+            Ok(generator)
+        }
     }
 
     /// Creates a CustomerGenerator with specified distributions and text pool
@@ -693,17 +870,58 @@ impl<'a> CustomerGenerator<'a> {
             part_count,
             distributions,
             text_pool,
+            home_locations: None,
+            land_mask: None,
         }
     }
 
+    /// Generate the `c_homeloc` column, placing each customer in the outline of
+    /// its nation with `spatial_gen`, which must generate points
+    ///
+    /// See [`SpatialDefaults::customer_default`] for the default distribution.
+    ///
+    /// Returns an error if `spatial_gen` generates other geometries, uses the
+    /// hotspot distribution or sizes in meters, as their locations would not
+    /// be placed in the nation of each customer.
+    pub fn with_home_locations(
+        mut self,
+        spatial_gen: SpatialGenerator,
+    ) -> Result<Self, SpatialBenchError> {
+        NationLocations::validate("customer", &spatial_gen)?;
+        let cardinality = Self::calculate_row_count(self.scale_factor, 1, 1) as u64;
+        self.home_locations = Some(spatial_gen.with_cardinality(cardinality));
+        Ok(self)
+    }
+
+    /// Redraw home locations that fall in water, using the bundled land mask
+    pub fn with_land_mask(mut self) -> Self {
+        self.land_mask = Some(LandMask::bundled());
+        self
+    }
+
+    /// Whether customers have the `c_homeloc` column
+    pub fn has_home_locations(&self) -> bool {
+        self.home_locations.is_some()
+    }
+
     /// Return the row count for the given scale factor and generator part count
     pub fn calculate_row_count(scale_factor: f64, part: i32, part_count: i32) -> i64 {
         GenerateUtils::calculate_row_count(Self::SCALE_BASE, scale_factor, part, part_count)
     }
 
     /// Returns an iterator over the customer rows
+    ///
+    /// The iterator panics if a home location cannot be generated; see
+    /// [`Self::try_iter`].
     pub fn iter(&self) -> CustomerGeneratorIterator<'a> {
-        CustomerGeneratorIterator::new(
+        CustomerGeneratorIterator(self.try_iter())
+    }
+
+    /// Returns an iterator over the customer rows that yields the error generating
+    /// a home location, e.g. from a custom distribution that does not generate
+    /// points, after which it ends
+    pub fn try_iter(&self) -> TryCustomerGeneratorIterator<'a> {
+        TryCustomerGeneratorIterator::new(
             self.distributions,
             self.text_pool,
             GenerateUtils::calculate_start_index(
@@ -714,6 +932,7 @@ impl<'a> CustomerGenerator<'a> {
             ),
             Self::calculate_row_count(self.scale_factor, self.part, self.part_count),
         )
+        .with_locations(self.home_locations.clone(), self.land_mask)
     }
 }
 
@@ -727,8 +946,26 @@ impl<'a> IntoIterator for CustomerGenerator<'a> {
 }
 
 /// Iterator that generates Customer rows
+///
+/// Panics if a home location cannot be generated; see
+/// [`CustomerGenerator::try_iter`].
+#[derive(Debug)]
+pub struct CustomerGeneratorIterator<'a>(TryCustomerGeneratorIterator<'a>);
+
+impl<'a> Iterator for CustomerGeneratorIterator<'a> {
+    type Item = Customer<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|customer| {
+            customer.unwrap_or_else(|e| panic!("Failed to generate customers: {e}"))
+        })
+    }
+}
+
+/// Iterator that generates Customer rows, or the error generating a home location
+/// after which it ends
 #[derive(Debug)]
-pub struct CustomerGeneratorIterator<'a> {
+pub struct TryCustomerGeneratorIterator<'a> {
     address_random: RandomAlphaNumeric,
     nation_key_random: RandomBoundedInt,
     phone_random: RandomPhoneNumber,
@@ -738,9 +975,10 @@ pub struct CustomerGeneratorIterator<'a> {
     index: i64,
     nations: &'a Distribution,
     regions: &'a Distribution,
+    locations: Option<NationLocations>,
 }
 
-impl<'a> CustomerGeneratorIterator<'a> {
+impl<'a> TryCustomerGeneratorIterator<'a> {
     fn new(
         distributions: &'a Distributions,
         text_pool: &'a TextPool,
@@ -773,35 +1011,52 @@ impl<'a> CustomerGeneratorIterator<'a> {
         market_segment_random.advance_rows(start_index);
         comment_random.advance_rows(start_index);
 
-        CustomerGeneratorIterator {
+        TryCustomerGeneratorIterator {
             address_random,
             phone_random,
             nation_key_random,
             regions: distributions.regions(),
             nations: distributions.nations(),
+            locations: None,
             start_index,
             row_count,
             index: 0,
         }
     }
 
+    /// Places the customers in their nation with `spatial_gen`, if any
+    fn with_locations(
+        mut self,
+        spatial_gen: Option<SpatialGenerator>,
+        land_mask: Option<&'static LandMask>,
+    ) -> Self {
+        self.locations = spatial_gen
+            .map(|spatial_gen| NationLocations::new(spatial_gen, self.nations, land_mask));
+        self
+    }
+
     /// Creates a customer with the given key
-    fn make_customer(&mut self, customer_key: i64) -> Customer<'a> {
+    fn make_customer(&mut self, customer_key: i64) -> Result<Customer<'a>, SpatialBenchError> {
         let nation_key = self.nation_key_random.next_value() as i64;
         let region_key = self.nations.get_weight(nation_key as usize);
-        Customer {
+        Ok(Customer {
             c_custkey: customer_key,
             c_name: CustomerName::new(customer_key),
             c_address: self.address_random.next_value(),
             c_region: self.regions.get_value(region_key as usize),
             c_nation: self.nations.get_value(nation_key as usize),
             c_phone: self.phone_random.next_value(nation_key),
-        }
+            c_homeloc: self
+                .locations
+                .as_ref()
+                .map(|locations| locations.generate(nation_key as usize, customer_key as u64))
+                .transpose()?,
+        })
     }
 }
 
-impl<'a> Iterator for CustomerGeneratorIterator<'a> {
-    type Item = Customer<'a>;
+impl<'a> Iterator for TryCustomerGeneratorIterator<'a> {
+    type Item = Result<Customer<'a>, SpatialBenchError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.index >= self.row_count {
@@ -815,6 +1070,9 @@ impl<'a> Iterator for CustomerGeneratorIterator<'a> {
         self.phone_random.row_finished();
 
         self.index += 1;
+        if customer.is_err() {
+            self.index = self.row_count;
+        }
 
         Some(customer)
     }
@@ -855,13 +1113,19 @@ pub struct Trip {
     pub t_pickuploc: Point,
     /// Trip dropoff coordinates
     pub t_dropoffloc: Point,
+    /// Ground elevation of the pickup in meters, the Z coordinate of
+    /// `t_pickuploc` when trips are generated in 3D
+    pub t_pickup_z: Option<f64>,
+    /// Ground elevation of the dropoff in meters, the Z coordinate of
+    /// `t_dropoffloc` when trips are generated in 3D
+    pub t_dropoff_z: Option<f64>,
 }
 
 impl Display for Trip {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{:?}|{:?}|",
+            "{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|",
             self.t_tripkey,
             self.t_custkey,
             self.t_driverkey,
@@ -872,12 +1136,157 @@ impl Display for Trip {
             self.t_tip,
             self.t_totalamount,
             self.t_distance,
-            self.t_pickuploc,
-            self.t_dropoffloc,
+            GeometryZ::new(&Geometry::Point(self.t_pickuploc), self.t_pickup_z),
+            GeometryZ::new(&Geometry::Point(self.t_dropoffloc), self.t_dropoff_z),
         )
     }
 }
 
+/// Generates the geometry of the record with the given key in a continent picked
+/// from `continent_cdf`
+///
+/// With a land mask, geometries whose center falls in water are drawn again
+/// from keys derived from the record key, see [`draw_on_land`]. Parcels keep the
+/// record key, as each key owns its parcel of the unit square, and are only
+/// drawn again in another continent.
+fn generate_in_continent(
+    spatial_gen: &SpatialGenerator,
+    continent_cdf: &[WeightedTarget],
+    land_mask: Option<&LandMask>,
+    key: u64,
+) -> Result<Geometry, SpatialBenchError> {
+    let generate = |candidate_key: u64| {
+        let u = hash_to_unit_u64(candidate_key, 0xC0DEC0DE);
+        let idx = continent_cdf
+            .iter()
+            .position(|t| u <= t.cdf)
+            .unwrap_or(continent_cdf.len() - 1);
+        let index = if spatial_gen.is_parcel() {
+            key
+        } else {
+            candidate_key
+        };
+        Ok(spatial_gen.generate(index, &continent_cdf[idx].m))
+    };
+    draw_on_land(
+        land_mask,
+        key,
+        generate,
+        |land_mask, geom: &Geometry| match geom {
+            Geometry::Point(point) => land_mask.contains(point),
+            geom => geom
+                .bounding_rect()
+                .is_some_and(|bbox| land_mask.contains(&bbox.center().into())),
+        },
+    )
+}
+
+/// Draws the location of the record with the given key with `draw`, which takes
+/// the key of a candidate
+///
+/// Without a land mask, the record key is drawn. With one, candidates are drawn
+/// from the keys of [`LandMask::candidate_key`] until `on_land` accepts one, or
+/// an error is returned after [`MAX_LAND_ATTEMPTS`] candidates in water, e.g.
+/// for regions without land.
+fn draw_on_land<T>(
+    land_mask: Option<&LandMask>,
+    key: u64,
+    draw: impl Fn(u64) -> Result<T, SpatialBenchError>,
+    on_land: impl Fn(&LandMask, &T) -> bool,
+) -> Result<T, SpatialBenchError> {
+    let Some(land_mask) = land_mask else {
+        return draw(key);
+    };
+    for attempt in 0..MAX_LAND_ATTEMPTS {
+        let candidate = draw(LandMask::candidate_key(key, attempt))?;
+        if on_land(land_mask, &candidate) {
+            return Ok(candidate);
+        }
+    }
+    Err(SpatialBenchError::InvalidConfig(format!(
+        "No location on land found for record {key} in {MAX_LAND_ATTEMPTS} attempts; \
+         the regions may not cover enough land for the land mask"
+    )))
+}
+
+/// Locations of the records of the customer and driver tables, in the outline of
+/// their nation
+#[derive(Debug)]
+struct NationLocations {
+    spatial_gen: SpatialGenerator,
+    nation_areas: Vec<NationArea>,
+    land_mask: Option<&'static LandMask>,
+}
+
+impl NationLocations {
+    fn new(
+        spatial_gen: SpatialGenerator,
+        nations: &Distribution,
+        land_mask: Option<&'static LandMask>,
+    ) -> Self {
+        Self {
+            spatial_gen,
+            nation_areas: nation_areas(nations),
+            land_mask,
+        }
+    }
+
+    /// Checks that the locations of `spatial_gen` are points placed by the
+    /// affine of the nation, so they can be drawn in its outline
+    fn validate(table: &str, spatial_gen: &SpatialGenerator) -> Result<(), SpatialBenchError> {
+        let config = spatial_gen.config();
+        if !matches!(config.geom_type, GeomType::Point) {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "The {table} location geometry type must be point, not {:?}",
+                config.geom_type
+            )));
+        }
+        if matches!(config.dist_type, DistributionType::Hotspot) {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "The {table} locations cannot use the hotspot distribution, whose \
+                 components are not in the nation of each record"
+            )));
+        }
+        if config.units == SizeUnit::Meters {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "The {table} locations must use unit sizes, not meters, to stay in the \
+                 nation of each record"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Generates the location of the record with the given key in the nation
+    /// with the given key
+    ///
+    /// Candidates are drawn from the keys of [`NationArea::candidate_key`] until
+    /// one is in the outline of the nation, or an error is returned after
+    /// [`MAX_NATION_ATTEMPTS`] candidates outside it.
+    fn generate(&self, nation_key: usize, key: u64) -> Result<Point, SpatialBenchError> {
+        let nation = &self.nation_areas[nation_key];
+        for attempt in 0..MAX_NATION_ATTEMPTS {
+            let geom = generate_in_continent(
+                &self.spatial_gen,
+                std::slice::from_ref(&nation.target),
+                self.land_mask,
+                NationArea::candidate_key(key, attempt),
+            )?;
+            let Geometry::Point(point) = geom else {
+                return Err(SpatialBenchError::InvalidConfig(format!(
+                    "The location distribution must generate points, not {geom:?}"
+                )));
+            };
+            if nation.contains(&point) {
+                return Ok(point);
+            }
+        }
+        Err(SpatialBenchError::InvalidConfig(format!(
+            "No location in its nation found for record {key} in {MAX_NATION_ATTEMPTS} \
+             attempts; the distribution may not cover the nation"
+        )))
+    }
+}
+
 /// Generator for Trip table data
 #[derive(Debug, Clone)]
 pub struct TripGenerator {
@@ -889,6 +1298,8 @@ pub struct TripGenerator {
     distance_kde: crate::kde::DistanceKDE,
     spatial_gen: SpatialGenerator,
     continent_cdf: Vec<WeightedTarget>,
+    land_mask: Option<&'static LandMask>,
+    zone_sampler: Option<Arc<ZoneSampler>>,
 }
 
 impl TripGenerator {
@@ -904,16 +1315,89 @@ impl TripGenerator {
     const TRIP_DURATION_MAX_PER_MILE: i32 = 3; // max 3 minutes per mile
 
     /// Creates a new TripGenerator with the given scale factor
+    ///
+    /// Pickups are placed inside zones if the zone placement override is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the zones for the placement cannot be loaded; see [`Self::try_new`].
     pub fn new(scale_factor: f64, part: i32, part_count: i32) -> TripGenerator {
-        Self::new_with_distributions_and_text_pool(
+        Self::try_new(scale_factor, part, part_count)
+            .unwrap_or_else(|e| panic!("Failed to load zones for trip placement: {e}"))
+    }
+
+    /// Creates a new TripGenerator with the given scale factor, or the error
+    /// loading the zones of the zone placement override
+    pub fn try_new(
+        scale_factor: f64,
+        part: i32,
+        part_count: i32,
+    ) -> Result<TripGenerator, SpatialBenchError> {
+        Self::try_new_with_spatial_gen(
+            scale_factor,
+            part,
+            part_count,
+            spatial_overrides::trip_or_default(SpatialDefaults::trip_default),
+        )
+    }
+
+    /// Creates a new TripGenerator whose pickups are sampled from a custom
+    /// distribution, or the error preparing it or loading the zones of the zone
+    /// placement override
+    ///
+    /// The other overrides apply as in [`Self::try_new`], and the configured
+    /// trip distribution still sets the dimension of the geometries.
+    pub fn new_with_spatial_distribution(
+        scale_factor: f64,
+        part: i32,
+        part_count: i32,
+        distribution: Box<dyn SpatialDistribution>,
+    ) -> Result<TripGenerator, SpatialBenchError> {
+        let spatial_gen = spatial_overrides::trip_or_default(SpatialDefaults::trip_default)
+            .with_distribution(distribution)?;
+        Self::try_new_with_spatial_gen(scale_factor, part, part_count, spatial_gen)
+    }
+
+    /// Creates a new TripGenerator with the given spatial generator and the
+    /// overrides
+    fn try_new_with_spatial_gen(
+        scale_factor: f64,
+        part: i32,
+        part_count: i32,
+        spatial_gen: SpatialGenerator,
+    ) -> Result<TripGenerator, SpatialBenchError> {
+        if !matches!(
+            spatial_gen.config().geom_type,
+            GeomType::Point | GeomType::LineString
+        ) {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "The trip geometry type must be point or line string, not {:?}",
+                spatial_gen.config().geom_type
+            )));
+        }
+        let generator = Self::new_with_distributions_and_text_pool(
             scale_factor,
             part,
             part_count,
             Distributions::static_default(),
             TextPool::get_or_init_default(),
             crate::kde::default_distance_kde(),
-            spatial_overrides::trip_or_default(SpatialDefaults::trip_default),
-        )
+            spatial_gen,
+        );
+        let generator = match spatial_overrides::regions() {
+            Some(regions) => generator.with_regions(&regions)?,
+            None => generator,
+        };
+        let generator = if spatial_overrides::land_mask() {
+            generator.with_land_mask()
+        } else {
+            generator
+        };
+        match spatial_overrides::trip_zone_placement() {
+            Some(placement) => generator
+                .with_zone_placement(spatial_overrides::zone_config_or_default(), &placement),
+            None => Ok(generator),
+        }
     }
 
     /// Creates a TripGenerator with specified distributions and text pool
@@ -943,17 +1427,62 @@ impl TripGenerator {
             distance_kde,
             spatial_gen,
             continent_cdf,
+            land_mask: None,
+            zone_sampler: None,
         }
     }
 
+    /// Place pickups in the given regions instead of the default continents, or
+    /// the error if the regions are invalid, see [`validate_regions`]
+    pub fn with_regions(mut self, regions: &[Region]) -> Result<Self, SpatialBenchError> {
+        validate_regions(regions)?;
+        self.continent_cdf = build_region_cdf(regions);
+        Ok(self)
+    }
+
+    /// Redraw trips whose pickup or dropoff falls in water, using the bundled
+    /// land mask
+    ///
+    /// Pickups and dropoffs placed inside zones are on land already; other
+    /// trips keep their distance and are drawn again until both ends are on
+    /// land.
+    pub fn with_land_mask(mut self) -> Self {
+        self.land_mask = Some(LandMask::bundled());
+        self
+    }
+
+    /// Place pickups and dropoffs inside the zones of the Zone table generated
+    /// with `config` at the scale factor of this generator, instead of with the
+    /// spatial generator
+    ///
+    /// The zones are loaded once per process and shared by all parts. Fails if
+    /// the placement is invalid or the zones cannot be loaded.
+    pub fn with_zone_placement(
+        mut self,
+        config: ZoneConfig,
+        placement: &ZonePlacement,
+    ) -> Result<Self, SpatialBenchError> {
+        self.zone_sampler = Some(ZoneSampler::shared(self.scale_factor, config, placement)?);
+        Ok(self)
+    }
+
     /// Return the row count for the given scale factor and generator part count
     pub fn calculate_row_count(scale_factor: f64, part: i32, part_count: i32) -> i64 {
         GenerateUtils::calculate_row_count(Self::SCALE_BASE, scale_factor, part, part_count)
     }
 
     /// Returns an iterator over the trip rows
+    ///
+    /// The iterator panics if a trip cannot be generated; see [`Self::try_iter`].
     pub fn iter(&self) -> TripGeneratorIterator {
-        TripGeneratorIterator::new(
+        TripGeneratorIterator(self.try_iter())
+    }
+
+    /// Returns an iterator over the trip rows that yields the error generating a
+    /// trip, e.g. from a custom distribution that does not generate points, after
+    /// which it ends
+    pub fn try_iter(&self) -> TryTripGeneratorIterator {
+        TryTripGeneratorIterator::new(
             &self.distributions,
             &self.text_pool,
             self.scale_factor,
@@ -972,6 +1501,8 @@ impl TripGenerator {
             self.distance_kde.clone(), // Add the KDE model
             self.spatial_gen.clone(),
             self.continent_cdf.clone(),
+            self.land_mask,
+            self.zone_sampler.clone(),
         )
     }
 }
@@ -986,8 +1517,25 @@ impl IntoIterator for TripGenerator {
 }
 
 /// Iterator that generates Trip rows
+///
+/// Panics if a trip cannot be generated; see [`TripGenerator::try_iter`].
+#[derive(Debug)]
+pub struct TripGeneratorIterator(TryTripGeneratorIterator);
+
+impl Iterator for TripGeneratorIterator {
+    type Item = Trip;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .next()
+            .map(|trip| trip.unwrap_or_else(|e| panic!("Failed to generate trips: {e}")))
+    }
+}
+
+/// Iterator that generates Trip rows, or the error generating a trip after
+/// which it ends
 #[derive(Debug)]
-pub struct TripGeneratorIterator {
+pub struct TryTripGeneratorIterator {
     customer_key_random: RandomBoundedLong,
     driver_key_random: RandomBoundedLong,
     vehicle_key_random: RandomBoundedLong,
@@ -999,6 +1547,8 @@ pub struct TripGeneratorIterator {
     distance_kde: crate::kde::DistanceKDE,
     spatial_gen: SpatialGenerator,
     continent_cdf: Vec<WeightedTarget>,
+    land_mask: Option<&'static LandMask>,
+    zone_sampler: Option<Arc<ZoneSampler>>,
 
     scale_factor: f64,
     start_index: i64,
@@ -1009,7 +1559,7 @@ pub struct TripGeneratorIterator {
     trip_number: i64,
 }
 
-impl TripGeneratorIterator {
+impl TryTripGeneratorIterator {
     #[allow(clippy::too_many_arguments)]
     fn new(
         _distributions: &Distributions,
@@ -1020,6 +1570,8 @@ impl TripGeneratorIterator {
         distance_kde: crate::kde::DistanceKDE,
         spatial_gen: SpatialGenerator,
         continent_cdf: Vec<WeightedTarget>,
+        land_mask: Option<&'static LandMask>,
+        zone_sampler: Option<Arc<ZoneSampler>>,
     ) -> Self {
         // Create all the randomizers
         let max_customer_key = (CustomerGenerator::SCALE_BASE as f64 * scale_factor) as i64;
@@ -1065,7 +1617,7 @@ impl TripGeneratorIterator {
         tip_percent_random.advance_rows(start_index);
         trip_minutes_per_mile_random.advance_rows(start_index);
 
-        TripGeneratorIterator {
+        TryTripGeneratorIterator {
             customer_key_random,
             driver_key_random,
             vehicle_key_random,
@@ -1077,6 +1629,8 @@ impl TripGeneratorIterator {
             distance_kde,
             spatial_gen,
             continent_cdf,
+            land_mask,
+            zone_sampler,
 
             scale_factor,
             start_index,
@@ -1088,8 +1642,53 @@ impl TripGeneratorIterator {
         }
     }
 
-    /// Creates a trip with the given key
-    fn make_trip(&mut self, trip_key: i64) -> Trip {
+    /// Returns the pickup drawn from the given key in a continent, and the
+    /// dropoff at `distance` from it
+    ///
+    /// The dropoff is in a direction drawn from the same key or, if the
+    /// distribution generates line strings, at the end of the line from the
+    /// pickup scaled to `distance`, so the trip follows a winding route.
+    fn trip_locations(&self, key: u64, distance: f64) -> Result<(Point, Point), SpatialBenchError> {
+        let geom = generate_in_continent(&self.spatial_gen, &self.continent_cdf, None, key)?;
+        let (pickuploc, route_end) = match geom {
+            Geometry::Point(pickuploc) => (pickuploc, None),
+            Geometry::LineString(route) => {
+                let length: f64 = route.lines().map(|l| l.dx().hypot(l.dy())).sum();
+                let (start, end) = (route.0[0], route.0[route.0.len() - 1]);
+                let end = (length > 0.0).then(|| start + (end - start) * (distance / length));
+                (Point::from(start), end)
+            }
+            geom => {
+                return Err(SpatialBenchError::InvalidConfig(format!(
+                    "The trip distribution must generate points or line strings, not {geom:?}"
+                )))
+            }
+        };
+
+        let (mut dropoff_x, mut dropoff_y) = match route_end {
+            Some(end) => end.x_y(),
+            None => {
+                // Generate dropoff using angle and distance
+                let angle_seed = spider_seed_for_index(key, 1234);
+                let mut angle_rng = StdRng::seed_from_u64(angle_seed);
+                let angle: f64 = angle_rng.gen::<f64>() * std::f64::consts::TAU;
+                (
+                    pickuploc.x() + distance * angle.cos(),
+                    pickuploc.y() + distance * angle.sin(),
+                )
+            }
+        };
+
+        // Hard code coordinate precision to 8 decimal places - milimeter level precision for WGS 84
+        dropoff_x = (dropoff_x * 100_000_000.0).round() / 100_000_000.0;
+        dropoff_y = (dropoff_y * 100_000_000.0).round() / 100_000_000.0;
+
+        Ok((pickuploc, Point::new(dropoff_x, dropoff_y)))
+    }
+
+    /// Creates a trip with the given key, or the error if the distribution
+    /// generates neither points nor line strings or no location on land is found
+    fn make_trip(&mut self, trip_key: i64) -> Result<Trip, SpatialBenchError> {
         // generate customer key, taking into account customer mortality rate
         let mut customer_key = self.customer_key_random.next_value();
         let mut delta = 1;
@@ -1116,34 +1715,32 @@ impl TripGeneratorIterator {
         distance_value = (distance_value * 100_000_000.0).round() / 100_000_000.0;
         let distance = TPCHDecimal((distance_value * 100.0) as i64);
 
-        // Select continent based on trip_key and generate pickup location
-        let u = hash_to_unit_u64(trip_key as u64, 0xC0DEC0DE);
-        let idx = self
-            .continent_cdf
-            .iter()
-            .position(|t| u <= t.cdf)
-            .unwrap_or(self.continent_cdf.len() - 1);
-        let continent_affine = &self.continent_cdf[idx].m;
-
-        // Pickup
-        let pickuploc_geom = self.spatial_gen.generate(trip_key as u64, continent_affine);
-        let pickuploc: Point = pickuploc_geom
-            .try_into()
-            .expect("Failed to convert to point");
-
-        // Generate dropoff using angle and distance
-        let angle_seed = spider_seed_for_index(trip_key as u64, 1234);
-        let mut angle_rng = StdRng::seed_from_u64(angle_seed);
-        let angle: f64 = angle_rng.gen::<f64>() * std::f64::consts::TAU;
-
-        let mut dropoff_x = pickuploc.x() + distance_value * angle.cos();
-        let mut dropoff_y = pickuploc.y() + distance_value * angle.sin();
-
-        // Hard code coordinate precision to 8 decimal places - milimeter level precision for WGS 84
-        dropoff_x = (dropoff_x * 100_000_000.0).round() / 100_000_000.0;
-        dropoff_y = (dropoff_y * 100_000_000.0).round() / 100_000_000.0;
-
-        let dropoffloc = Point::new(dropoff_x, dropoff_y);
+        // Pickup and dropoff, inside zones or in a continent selected based on
+        // trip_key; with a land mask, both are drawn again until both are on land
+        let (pickuploc, dropoffloc) = match &self.zone_sampler {
+            Some(sampler) => {
+                let pickuploc = sampler.sample(trip_key as u64);
+                // Dropoff inside a zone near the pickup when pickups are placed in zones
+                let dropoffloc = sampler.sample_dropoff(trip_key as u64, pickuploc, distance_value);
+                (pickuploc, dropoffloc)
+            }
+            None => draw_on_land(
+                self.land_mask,
+                trip_key as u64,
+                |candidate_key| self.trip_locations(candidate_key, distance_value),
+                |land_mask, (pickuploc, dropoffloc)| {
+                    land_mask.contains(pickuploc) && land_mask.contains(dropoffloc)
+                },
+            )?,
+        };
+        let (pickup_z, dropoff_z) = if self.spatial_gen.config().dim == 3 {
+            (
+                Some(ground_elevation(pickuploc.x(), pickuploc.y())),
+                Some(ground_elevation(dropoffloc.x(), dropoffloc.y())),
+            )
+        } else {
+            (None, None)
+        };
 
         let fare_per_mile = self.fare_per_mile_random.next_value() as f64;
         let fare_value = (distance_value * fare_per_mile) / 100.0;
@@ -1184,7 +1781,7 @@ impl TripGeneratorIterator {
             dropoff_second,
         );
 
-        Trip {
+        Ok(Trip {
             t_tripkey: trip_key,
             t_custkey: customer_key,
             t_driverkey: driver_key,
@@ -1197,12 +1794,14 @@ impl TripGeneratorIterator {
             t_distance: distance,
             t_pickuploc: pickuploc,
             t_dropoffloc: dropoffloc,
-        }
+            t_pickup_z: pickup_z,
+            t_dropoff_z: dropoff_z,
+        })
     }
 }
 
-impl Iterator for TripGeneratorIterator {
-    type Item = Trip;
+impl Iterator for TryTripGeneratorIterator {
+    type Item = Result<Trip, SpatialBenchError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.index >= self.row_count {
@@ -1222,6 +1821,9 @@ impl Iterator for TripGeneratorIterator {
         self.trip_minutes_per_mile_random.row_finished();
 
         self.index += 1;
+        if trip.is_err() {
+            self.index = self.row_count;
+        }
 
         Some(trip)
     }
@@ -1234,17 +1836,45 @@ pub struct Building<'a> {
     pub b_buildingkey: i64,
     /// Name of the building
     pub b_name: StringSequenceInstance<'a>,
-    /// WKT representation of the building's polygon
+    /// Footprint of the building or, for a campus, the outline its parts were
+    /// shaped from
     pub b_boundary: geo::Polygon,
+    /// Parts of the footprint of a campus, which are written to the
+    /// `b_boundary` column instead, see [`BuildingGenerator::with_footprint`]
+    pub b_campus: Option<geo::MultiPolygon>,
+    /// Ground elevation in meters, the Z coordinate of `b_boundary` when buildings
+    /// are generated in 3D
+    pub b_elevation: Option<f64>,
+    /// Height in meters, when buildings are generated in 3D
+    pub b_height: Option<f64>,
+    /// Number of floors, when buildings are generated in 3D
+    pub b_floors: Option<i32>,
+}
+
+impl Building<'_> {
+    /// Returns the footprint written to the `b_boundary` column: the parts of a
+    /// campus, or the polygon of any other building
+    pub fn footprint(&self) -> Geometry {
+        match &self.b_campus {
+            Some(campus) => Geometry::MultiPolygon(campus.clone()),
+            None => Geometry::Polygon(self.b_boundary.clone()),
+        }
+    }
 }
 
 impl Display for Building<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "{}|{}|{:?}|",
-            self.b_buildingkey, self.b_name, self.b_boundary,
-        )
+            "{}|{}|{}|",
+            self.b_buildingkey,
+            self.b_name,
+            GeometryZ::new(&self.footprint(), self.b_elevation),
+        )?;
+        if let (Some(height), Some(floors)) = (self.b_height, self.b_floors) {
+            write!(f, "{height:.2}|{floors}|")?;
+        }
+        Ok(())
     }
 }
 
@@ -1258,6 +1888,12 @@ pub struct BuildingGenerator<'a> {
     text_pool: &'a TextPool,
     spatial_gen: SpatialGenerator,
     continent_cdf: Vec<WeightedTarget>,
+    land_mask: Option<&'static LandMask>,
+    footprint: Option<FootprintOptions>,
+    non_overlapping: bool,
+    /// Placement without overlaps, computed by the first call to [`Self::iter`]
+    /// and shared by its clones and iterators
+    placement: OnceLock<Arc<HardCorePlacement>>,
 }
 
 impl<'a> BuildingGenerator<'a> {
@@ -1270,45 +1906,183 @@ impl<'a> BuildingGenerator<'a> {
     ///
     /// Note the generator's lifetime is `&'static`. See [`BuildingGenerator`] for
     /// more details.
+    ///
+    /// Panics if the building distribution cannot be prepared for the validity
+    /// override; use [`Self::try_new`] to handle the error.
     pub fn new(scale_factor: f64, part: i32, part_count: i32) -> BuildingGenerator<'static> {
-        Self::new_with_distributions_and_text_pool(
+        Self::try_new(scale_factor, part, part_count)
+            .unwrap_or_else(|e| panic!("Failed to prepare the building distribution: {e}"))
+    }
+
+    /// Creates a new BuildingGenerator with the given scale factor, or the error
+    /// preparing the building distribution for the validity override
+    pub fn try_new(
+        scale_factor: f64,
+        part: i32,
+        part_count: i32,
+    ) -> Result<BuildingGenerator<'static>, SpatialBenchError> {
+        Self::try_new_with_spatial_gen(
             scale_factor,
             part,
             part_count,
-            Distributions::static_default(),
-            TextPool::get_or_init_default(),
             spatial_overrides::building_or_default(SpatialDefaults::building_default),
         )
     }
 
-    /// Creates a BuildingGenerator with specified distributions and text pool
-    pub fn new_with_distributions_and_text_pool<'b>(
+    /// Creates a new BuildingGenerator whose footprints are sampled from a custom
+    /// distribution, or the error preparing it
+    ///
+    /// The other overrides apply as in [`Self::new`], and the configured
+    /// building distribution still sets the dimension of the geometries.
+    pub fn new_with_spatial_distribution(
         scale_factor: f64,
         part: i32,
         part_count: i32,
-        distributions: &'b Distributions,
-        text_pool: &'b TextPool,
-        spatial_gen: SpatialGenerator,
-    ) -> BuildingGenerator<'b> {
-        let continent_cdf = {
-            let affines = ContinentAffines::default();
-            build_continent_cdf(&affines)
-                .into_iter()
-                .map(|(_name, m, cdf)| WeightedTarget { m, cdf })
-                .collect()
-        };
+        distribution: Box<dyn SpatialDistribution>,
+    ) -> Result<BuildingGenerator<'static>, SpatialBenchError> {
+        let spatial_gen = spatial_overrides::building_or_default(SpatialDefaults::building_default)
+            .with_distribution(distribution)?;
+        Self::try_new_with_spatial_gen(scale_factor, part, part_count, spatial_gen)
+    }
 
-        BuildingGenerator {
-            scale_factor,
-            part,
+    /// Creates a new BuildingGenerator with the given spatial generator and the
+    /// overrides
+    fn try_new_with_spatial_gen(
+        scale_factor: f64,
+        part: i32,
+        part_count: i32,
+        spatial_gen: SpatialGenerator,
+    ) -> Result<BuildingGenerator<'static>, SpatialBenchError> {
+        if !matches!(
+            spatial_gen.config().geom_type,
+            GeomType::Box | GeomType::Polygon
+        ) {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "The building geometry type must be box or polygon, not {:?}",
+                spatial_gen.config().geom_type
+            )));
+        }
+        let generator = BuildingGenerator::new_with_distributions_and_text_pool(
+            scale_factor,
+            part,
+            part_count,
+            Distributions::static_default(),
+            TextPool::get_or_init_default(),
+            spatial_gen,
+        );
+        let generator = match spatial_overrides::regions() {
+            Some(regions) => generator.with_regions(&regions)?,
+            None => generator,
+        };
+        let generator = if spatial_overrides::land_mask() {
+            generator.with_land_mask()
+        } else {
+            generator
+        };
+        let generator = match spatial_overrides::footprint() {
+            Some(options) => generator.with_footprint(options),
+            None => generator,
+        };
+        let generator = if spatial_overrides::non_overlapping() {
+            generator.with_non_overlapping()
+        } else {
+            generator
+        };
+        match spatial_overrides::validity() {
+            Some(validity) => generator.with_validity(validity),
+            None => Ok(generator),
+        }
+    }
+
+    /// Creates a BuildingGenerator with specified distributions and text pool
+    pub fn new_with_distributions_and_text_pool<'b>(
+        scale_factor: f64,
+        part: i32,
+        part_count: i32,
+        distributions: &'b Distributions,
+        text_pool: &'b TextPool,
+        spatial_gen: SpatialGenerator,
+    ) -> BuildingGenerator<'b> {
+        let continent_cdf = {
+            let affines = ContinentAffines::default();
+            build_continent_cdf(&affines)
+                .into_iter()
+                .map(|(_name, m, cdf)| WeightedTarget { m, cdf })
+                .collect()
+        };
+
+        // the parcel distribution splits the unit square into one parcel per building
+        let cardinality = Self::calculate_row_count(scale_factor, 1, 1) as u64;
+        BuildingGenerator {
+            scale_factor,
+            part,
             part_count,
             distributions,
             text_pool,
-            spatial_gen,
+            spatial_gen: spatial_gen.with_cardinality(cardinality),
             continent_cdf,
+            land_mask: None,
+            footprint: None,
+            non_overlapping: false,
+            placement: OnceLock::new(),
         }
     }
 
+    /// Place buildings in the given regions instead of the default continents,
+    /// or the error if the regions are invalid, see [`validate_regions`]
+    pub fn with_regions(mut self, regions: &[Region]) -> Result<Self, SpatialBenchError> {
+        validate_regions(regions)?;
+        self.continent_cdf = build_region_cdf(regions);
+        self.placement = OnceLock::new();
+        Ok(self)
+    }
+
+    /// Redraw buildings whose center falls in water, using the bundled land mask
+    pub fn with_land_mask(mut self) -> Self {
+        self.land_mask = Some(LandMask::bundled());
+        self.placement = OnceLock::new();
+        self
+    }
+
+    /// Give footprints courtyards and turn them into campuses of several
+    /// polygons, with the probabilities of `options`
+    pub fn with_footprint(mut self, options: FootprintOptions) -> Self {
+        self.footprint = Some(options);
+        self.placement = OnceLock::new();
+        self
+    }
+
+    /// Place buildings so that no two footprints intersect
+    ///
+    /// A building whose footprint intersects one of a lower key is drawn again at
+    /// other locations until one is free, so the table keeps its row count and
+    /// keys; generation fails if none of the
+    /// [`MAX_HARDCORE_ATTEMPTS`](crate::spatial::hardcore::MAX_HARDCORE_ATTEMPTS)
+    /// locations is. The buildings of the whole table are placed once per process
+    /// to resolve these conflicts, and the placement is shared by every part.
+    ///
+    /// Parcels never overlap and are not drawn again, since another location
+    /// would be the parcel of another building: the parcel distribution is
+    /// placed as is.
+    pub fn with_non_overlapping(mut self) -> Self {
+        self.non_overlapping = true;
+        self
+    }
+
+    /// Whether buildings are generated in 3D, with a Z coordinate and the
+    /// `b_height` and `b_floors` columns
+    pub fn is_3d(&self) -> bool {
+        self.spatial_gen.config().dim == 3
+    }
+
+    /// Set the validity guarantees of the footprints, see [`Validity`], or
+    /// returns the error preparing the building distribution again
+    pub fn with_validity(mut self, validity: Validity) -> Result<Self, SpatialBenchError> {
+        self.spatial_gen = self.spatial_gen.with_validity(validity)?;
+        self.placement = OnceLock::new();
+        Ok(self)
+    }
+
     /// Return the row count for the given scale factor and generator part count
     pub fn calculate_row_count(scale_factor: f64, part: i32, part_count: i32) -> i64 {
         GenerateUtils::calculate_logarithmic_row_count(
@@ -1319,9 +2093,75 @@ impl<'a> BuildingGenerator<'a> {
         )
     }
 
+    /// Returns the placement of every building of the table without overlaps,
+    /// or the error generating a footprint or finding a free location
+    ///
+    /// The placement of a built-in distribution is shared by the generators with
+    /// the same settings in the process; a custom one is placed for this
+    /// generator and its clones only, since its settings are unknown.
+    fn hard_core_placement(&self) -> Result<Arc<HardCorePlacement>, SpatialBenchError> {
+        let count = Self::calculate_row_count(self.scale_factor, 1, 1) as u64;
+        let footprint = |key, candidate_key| {
+            let (boundary, campus) = building_boundary(
+                &self.spatial_gen,
+                &self.continent_cdf,
+                self.land_mask,
+                self.footprint.as_ref(),
+                key,
+                candidate_key,
+            )?;
+            Ok(campus.map_or(Geometry::Polygon(boundary), Geometry::MultiPolygon))
+        };
+        let placement = if self.spatial_gen.is_custom() {
+            Arc::new(HardCorePlacement::new(count, footprint)?)
+        } else {
+            let targets: Vec<_> = self.continent_cdf.iter().map(|t| (t.m, t.cdf)).collect();
+            let settings = format!(
+                "{}|{:?}|{}|{targets:?}|{}|{:?}",
+                self.scale_factor,
+                self.spatial_gen.config(),
+                self.spatial_gen.cardinality(),
+                self.land_mask.is_some(),
+                self.footprint
+            );
+            HardCorePlacement::shared(settings, count, footprint)?
+        };
+        debug!(
+            "Placed {count} buildings without overlaps, moved {}",
+            placement.moved()
+        );
+        Ok(placement)
+    }
+
+    /// Returns the shared placement of the buildings without overlaps, placing
+    /// them on first use
+    fn shared_placement(&self) -> Result<Arc<HardCorePlacement>, SpatialBenchError> {
+        if let Some(placement) = self.placement.get() {
+            return Ok(Arc::clone(placement));
+        }
+        let placement = self.hard_core_placement()?;
+        Ok(Arc::clone(self.placement.get_or_init(|| placement)))
+    }
+
     /// Returns an iterator over the part rows
+    ///
+    /// The iterator panics if a building cannot be generated; see
+    /// [`Self::try_iter`].
     pub fn iter(&self) -> BuildingGeneratorIterator<'a> {
-        BuildingGeneratorIterator::new(
+        BuildingGeneratorIterator(self.try_iter())
+    }
+
+    /// Returns an iterator over the part rows that yields the error generating a
+    /// building, e.g. from a custom distribution that does not generate
+    /// polygons, after which it ends
+    pub fn try_iter(&self) -> TryBuildingGeneratorIterator<'a> {
+        let non_overlapping = self.non_overlapping && !self.spatial_gen.is_parcel();
+        let (placement, error) = match non_overlapping.then(|| self.shared_placement()) {
+            Some(Ok(placement)) => (Some(placement), None),
+            Some(Err(e)) => (None, Some(e)),
+            None => (None, None),
+        };
+        TryBuildingGeneratorIterator::new(
             self.distributions,
             self.text_pool,
             GenerateUtils::calculate_start_index(
@@ -1333,6 +2173,10 @@ impl<'a> BuildingGenerator<'a> {
             Self::calculate_row_count(self.scale_factor, self.part, self.part_count),
             self.spatial_gen.clone(),
             self.continent_cdf.clone(),
+            self.land_mask,
+            self.footprint,
+            placement,
+            error,
         )
     }
 }
@@ -1346,19 +2190,84 @@ impl<'a> IntoIterator for &'a BuildingGenerator<'a> {
     }
 }
 
+/// Salt of the draws shaping the footprint of a building
+const FOOTPRINT_SALT: u64 = 0xF007_9121;
+
+/// Generates the footprint of a building at the location of `candidate_key`,
+/// the building key unless the building was moved to avoid an overlap, as its
+/// boundary and, for a campus, the parts shaped from the boundary
+///
+/// The shape of the footprint is drawn from the building key, so a moved
+/// building keeps its courtyards and campus parts.
+fn building_boundary(
+    spatial_gen: &SpatialGenerator,
+    continent_cdf: &[WeightedTarget],
+    land_mask: Option<&LandMask>,
+    footprint: Option<&FootprintOptions>,
+    building_key: u64,
+    candidate_key: u64,
+) -> Result<(geo::Polygon, Option<geo::MultiPolygon>), SpatialBenchError> {
+    // Generate the polygon in a continent selected based on the key
+    let geom = generate_in_continent(spatial_gen, continent_cdf, land_mask, candidate_key)?;
+    let polygon = geo::Polygon::try_from(geom).map_err(|e| {
+        SpatialBenchError::InvalidConfig(format!(
+            "The building distribution must generate polygons: {e}"
+        ))
+    })?;
+    let Some(options) = footprint else {
+        return Ok((polygon, None));
+    };
+    let seed = spider_seed_for_index(building_key, FOOTPRINT_SALT);
+    let rng = &mut StdRng::seed_from_u64(seed);
+    let shaped = match spatial_gen.config().validity {
+        Validity::Raw => shape_footprint(polygon.clone(), options, rng),
+        Validity::Strict => strict_footprint(
+            shape_footprint(polygon.clone(), options, rng),
+            polygon.clone(),
+        ),
+    };
+    Ok(match shaped {
+        Geometry::MultiPolygon(campus) => (polygon, Some(campus)),
+        Geometry::Polygon(shaped) => (shaped, None),
+        _ => unreachable!("footprints are polygons or multipolygons"),
+    })
+}
+
 /// Iterator that generates Building rows
+///
+/// Panics if a building cannot be generated; see [`BuildingGenerator::try_iter`].
+#[derive(Debug)]
+pub struct BuildingGeneratorIterator<'a>(TryBuildingGeneratorIterator<'a>);
+
+impl<'a> Iterator for BuildingGeneratorIterator<'a> {
+    type Item = Building<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|building| {
+            building.unwrap_or_else(|e| panic!("Failed to generate buildings: {e}"))
+        })
+    }
+}
+
+/// Iterator that generates Building rows, or the error generating a building
+/// after which it ends
 #[derive(Debug)]
-pub struct BuildingGeneratorIterator<'a> {
+pub struct TryBuildingGeneratorIterator<'a> {
     name_random: RandomStringSequence<'a>,
     spatial_gen: SpatialGenerator,
     continent_cdf: Vec<WeightedTarget>,
+    land_mask: Option<&'static LandMask>,
+    footprint: Option<FootprintOptions>,
+    placement: Option<Arc<HardCorePlacement>>,
+    error: Option<SpatialBenchError>,
 
     start_index: i64,
     row_count: i64,
     index: i64,
 }
 
-impl<'a> BuildingGeneratorIterator<'a> {
+impl<'a> TryBuildingGeneratorIterator<'a> {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         distributions: &'a Distributions,
         text_pool: &'a TextPool,
@@ -1366,6 +2275,10 @@ impl<'a> BuildingGeneratorIterator<'a> {
         row_count: i64,
         spatial_gen: SpatialGenerator,
         continent_cdf: Vec<WeightedTarget>,
+        land_mask: Option<&'static LandMask>,
+        footprint: Option<FootprintOptions>,
+        placement: Option<Arc<HardCorePlacement>>,
+        error: Option<SpatialBenchError>,
     ) -> Self {
         let mut name_random = RandomStringSequence::new(
             709314158,
@@ -1382,10 +2295,14 @@ impl<'a> BuildingGeneratorIterator<'a> {
         name_random.advance_rows(start_index);
         wkt_random.advance_rows(start_index);
 
-        BuildingGeneratorIterator {
+        TryBuildingGeneratorIterator {
             name_random,
             spatial_gen,
             continent_cdf,
+            land_mask,
+            footprint,
+            placement,
+            error,
 
             start_index,
             row_count,
@@ -1394,37 +2311,58 @@ impl<'a> BuildingGeneratorIterator<'a> {
         }
     }
 
-    /// Creates a part with the given key
-    fn make_building(&mut self, building_key: i64) -> Building<'a> {
+    /// Creates a part with the given key, or the error if the footprint is not a
+    /// polygon
+    fn make_building(&mut self, building_key: i64) -> Result<Building<'a>, SpatialBenchError> {
         let name = self.name_random.next_value();
+        let candidate_key = match &self.placement {
+            Some(placement) => placement.placed_key(building_key),
+            None => building_key as u64,
+        };
+        let (boundary, campus) = building_boundary(
+            &self.spatial_gen,
+            &self.continent_cdf,
+            self.land_mask,
+            self.footprint.as_ref(),
+            building_key as u64,
+            candidate_key,
+        )?;
 
-        // Select continent based on building_key
-        let u = hash_to_unit_u64(building_key as u64, 0xC0DEC0DE);
-        let idx = self
-            .continent_cdf
-            .iter()
-            .position(|t| u <= t.cdf)
-            .unwrap_or(self.continent_cdf.len() - 1);
-        let continent_affine = &self.continent_cdf[idx].m;
-
-        // Generate point in unit space [0,1]
-        let geom = self
-            .spatial_gen
-            .generate(building_key as u64, continent_affine);
-        let polygon: geo::Polygon = geom.try_into().expect("Failed to convert to polygon");
+        let (elevation, height, floors) = if self.spatial_gen.config().dim == 3 {
+            let center = boundary
+                .bounding_rect()
+                .map(|bbox| bbox.center())
+                .unwrap_or_default();
+            let floors = building_floors(building_key as u64);
+            (
+                Some(ground_elevation(center.x, center.y)),
+                Some(building_height(building_key as u64, floors)),
+                Some(floors),
+            )
+        } else {
+            (None, None, None)
+        };
 
-        Building {
+        Ok(Building {
             b_buildingkey: building_key,
             b_name: name,
-            b_boundary: polygon,
-        }
+            b_boundary: boundary,
+            b_campus: campus,
+            b_elevation: elevation,
+            b_height: height,
+            b_floors: floors,
+        })
     }
 }
 
-impl<'a> Iterator for BuildingGeneratorIterator<'a> {
-    type Item = Building<'a>;
+impl<'a> Iterator for TryBuildingGeneratorIterator<'a> {
+    type Item = Result<Building<'a>, SpatialBenchError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            self.index = self.row_count;
+            return Some(Err(e));
+        }
         if self.index >= self.row_count {
             return None;
         }
@@ -1434,6 +2372,9 @@ impl<'a> Iterator for BuildingGeneratorIterator<'a> {
         self.name_random.row_finished();
 
         self.index += 1;
+        if building.is_err() {
+            self.index = self.row_count;
+        }
 
         Some(building)
     }
@@ -1454,104 +2395,397 @@ pub struct Zone {
     pub z_name: String,
     /// Subtype of the zone
     pub z_subtype: String,
+    /// Administrative level of the subtype, from 0 (country) to 8 (microhood)
+    pub z_level: i32,
+    /// Key of the zone of the nearest coarser level that contains this zone
+    pub z_parentkey: Option<i64>,
     /// Boundary geometry in WKT format
     pub z_boundary: Geometry,
+    /// Bounding box of the boundary, if enabled in the zone geometry options
+    pub z_bbox: Option<Rect>,
 }
 
 impl Display for Zone {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "{}|{}|{}|{}|{}|{}|{:?}|",
+            "{}|{}|{}|{}|{}|{}|{}|{}|{:?}|",
             self.z_zonekey,
             self.z_gersid,
             self.z_country,
             self.z_region,
             self.z_name,
             self.z_subtype,
-            self.z_boundary
-        )
+            self.z_level,
+            OptionalKey(self.z_parentkey),
+            self.z_boundary,
+        )?;
+        // the bounding box column is only written when it is enabled
+        if self.z_bbox.is_some() {
+            write!(f, "{}|", OptionalRect(self.z_bbox))?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats an optional key, with nothing for `None`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OptionalKey(pub Option<i64>);
+
+impl Display for OptionalKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(key) => write!(f, "{}", key),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Formats an optional bounding box as a WKT polygon, with nothing for `None`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OptionalRect(pub Option<Rect>);
+
+impl Display for OptionalRect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(rect) => write!(f, "{:?}", rect.to_polygon()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A geometry with an optional Z coordinate shared by all its coordinates
+///
+/// Without Z it formats like the geometry itself; with Z it formats as WKT with
+/// `Z` (e.g. `POINT Z(1.0 2.0 3.0)`) and encodes as WKB with Z coordinates.
+/// Points, line strings, polygons and multipolygons get Z coordinates, other
+/// geometries are written in 2D.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeometryZ<'a> {
+    pub geometry: &'a Geometry,
+    pub z: Option<f64>,
+}
+
+impl<'a> GeometryZ<'a> {
+    pub fn new(geometry: &'a Geometry, z: Option<f64>) -> Self {
+        Self { geometry, z }
+    }
+
+    /// Encodes the geometry as WKB, with Z coordinates if set
+    pub fn to_wkb(&self) -> Result<Vec<u8>, GeozeroError> {
+        ToWkb::to_wkb(self, self.dims())
+    }
+
+    /// The Z coordinate, if set and the geometry is written in 3D
+    fn written_z(&self) -> Option<f64> {
+        match self.geometry {
+            Geometry::Point(_)
+            | Geometry::LineString(_)
+            | Geometry::Polygon(_)
+            | Geometry::MultiPolygon(_) => self.z,
+            _ => None,
+        }
+    }
+}
+
+impl Display for GeometryZ<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(z) = self.written_z() else {
+            return write!(f, "{:?}", self.geometry);
+        };
+        let ring = |f: &mut fmt::Formatter, ring: &geo::LineString| {
+            write!(f, "(")?;
+            for (i, c) in ring.0.iter().enumerate() {
+                let sep = if i == 0 { "" } else { "," };
+                write!(f, "{sep}{:?} {:?} {z:?}", c.x, c.y)?;
+            }
+            write!(f, ")")
+        };
+        let polygon = |f: &mut fmt::Formatter, polygon: &geo::Polygon| {
+            write!(f, "(")?;
+            ring(f, polygon.exterior())?;
+            for interior in polygon.interiors() {
+                write!(f, ",")?;
+                ring(f, interior)?;
+            }
+            write!(f, ")")
+        };
+        match self.geometry {
+            Geometry::Point(p) => write!(f, "POINT Z({:?} {:?} {z:?})", p.x(), p.y()),
+            Geometry::LineString(line) => {
+                write!(f, "LINESTRING Z")?;
+                ring(f, line)
+            }
+            Geometry::Polygon(p) => {
+                write!(f, "POLYGON Z")?;
+                polygon(f, p)
+            }
+            Geometry::MultiPolygon(mp) => {
+                write!(f, "MULTIPOLYGON Z(")?;
+                for (i, p) in mp.0.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    polygon(f, p)?;
+                }
+                write!(f, ")")
+            }
+            _ => unreachable!("written in 2D above"),
+        }
+    }
+}
+
+impl GeozeroGeometry for GeometryZ<'_> {
+    fn process_geom<P: GeomProcessor>(&self, processor: &mut P) -> geozero::error::Result<()> {
+        let Some(z) = self.written_z() else {
+            return self.geometry.process_geom(processor);
+        };
+        let ring = |processor: &mut P, ring: &geo::LineString, tagged, idx| {
+            processor.linestring_begin(tagged, ring.0.len(), idx)?;
+            for (i, c) in ring.0.iter().enumerate() {
+                processor.coordinate(c.x, c.y, Some(z), None, None, None, i)?;
+            }
+            processor.linestring_end(tagged, idx)
+        };
+        let polygon = |processor: &mut P, polygon: &geo::Polygon, tagged, idx| {
+            processor.polygon_begin(tagged, polygon.interiors().len() + 1, idx)?;
+            ring(processor, polygon.exterior(), false, 0)?;
+            for (i, interior) in polygon.interiors().iter().enumerate() {
+                ring(processor, interior, false, i + 1)?;
+            }
+            processor.polygon_end(tagged, idx)
+        };
+        match self.geometry {
+            Geometry::Point(p) => {
+                processor.point_begin(0)?;
+                processor.coordinate(p.x(), p.y(), Some(z), None, None, None, 0)?;
+                processor.point_end(0)
+            }
+            Geometry::LineString(line) => ring(processor, line, true, 0),
+            Geometry::Polygon(p) => polygon(processor, p, true, 0),
+            Geometry::MultiPolygon(mp) => {
+                processor.multipolygon_begin(mp.0.len(), 0)?;
+                for (i, p) in mp.0.iter().enumerate() {
+                    polygon(processor, p, false, i)?;
+                }
+                processor.multipolygon_end(0)
+            }
+            _ => unreachable!("checked above"),
+        }
+    }
+
+    fn dims(&self) -> CoordDimensions {
+        if self.written_z().is_some() {
+            CoordDimensions::xyz()
+        } else {
+            CoordDimensions::xy()
+        }
+    }
+}
+
+/// A [`Zone`] with its boundary encoded as (2D) WKB
+///
+/// This is the form in which zones are stored in the zone cache, so zones can be
+/// written to columnar formats without decoding and re-encoding their boundaries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneWkb {
+    /// Primary key
+    pub z_zonekey: i64,
+    /// GERS ID of the zone
+    pub z_gersid: String,
+    /// Country of the zone
+    pub z_country: String,
+    /// Region of the zone
+    pub z_region: String,
+    /// Name of the zone
+    pub z_name: String,
+    /// Subtype of the zone
+    pub z_subtype: String,
+    /// Administrative level of the subtype, from 0 (country) to 8 (microhood)
+    pub z_level: i32,
+    /// Key of the zone of the nearest coarser level that contains this zone
+    pub z_parentkey: Option<i64>,
+    /// Boundary geometry in WKB format
+    pub z_boundary: Vec<u8>,
+    /// Bounding box of the boundary, if enabled in the zone geometry options
+    pub z_bbox: Option<Rect>,
+}
+
+impl ZoneWkb {
+    /// Encodes the boundary of a zone as WKB
+    pub fn from_zone(zone: Zone) -> Result<Self, GeozeroError> {
+        Ok(Self {
+            z_boundary: zone.z_boundary.to_wkb(CoordDimensions::xy())?,
+            z_zonekey: zone.z_zonekey,
+            z_gersid: zone.z_gersid,
+            z_country: zone.z_country,
+            z_region: zone.z_region,
+            z_name: zone.z_name,
+            z_subtype: zone.z_subtype,
+            z_level: zone.z_level,
+            z_parentkey: zone.z_parentkey,
+            z_bbox: zone.z_bbox,
+        })
+    }
+
+    /// Decodes the boundary of the zone
+    pub fn into_zone(self) -> Result<Zone, GeozeroError> {
+        Ok(Zone {
+            z_boundary: Wkb(&self.z_boundary).to_geo()?,
+            z_zonekey: self.z_zonekey,
+            z_gersid: self.z_gersid,
+            z_country: self.z_country,
+            z_region: self.z_region,
+            z_name: self.z_name,
+            z_subtype: self.z_subtype,
+            z_level: self.z_level,
+            z_parentkey: self.z_parentkey,
+            z_bbox: self.z_bbox,
+        })
     }
 }
 
-/// Generator for [`Zone`]s that loads from a parquet file in S3
+/// Generator for [`Zone`]s that loads Overture division areas from a [`ZoneSource`]
+///
+/// Zones are ordered by administrative level (coarsest first) and then by GERS id,
+/// and keys are assigned in that order. Like the other generators, the table does
+/// not depend on the number of parts: concatenating parts `1..=N` yields the same
+/// rows for any `N`.
+///
+/// Without a cache, each part reads the rows up to the end of its key range from
+/// the source. With a cache directory, the source is scanned once and the sorted
+/// table is stored in a local [`cache`](crate::zone::cache), from which every part
+/// reads its rows. The subtypes included at each scale factor come from the
+/// [`ZoneConfig`], and the number of zones is counted in the source.
 #[derive(Debug, Clone)]
 pub struct ZoneGenerator {
     scale_factor: f64,
     part: i32,
     part_count: i32,
+    config: ZoneConfig,
 }
 
 impl ZoneGenerator {
-    /// S3 URL for the zones parquet file
-    const OVERTURE_RELEASE_DATE: &'static str = "2025-08-20.1";
-    const OVERTURE_S3_BUCKET: &'static str = "overturemaps-us-west-2";
-    const OVERTURE_S3_PREFIX: &'static str = "release";
-
-    /// Gets the S3 URL for the zones parquet file
-    fn get_zones_parquet_url() -> String {
-        format!(
-            "s3://{}/{}/{}/theme=divisions/type=division_area/*",
-            Self::OVERTURE_S3_BUCKET,
-            Self::OVERTURE_S3_PREFIX,
-            Self::OVERTURE_RELEASE_DATE
-        )
-    }
-
     /// Get zone subtypes based on scale factor
-    fn get_zone_subtypes_for_scale_factor(scale_factor: f64) -> Vec<&'static str> {
-        let mut subtypes = vec!["microhood", "macrohood", "county"];
+    fn get_zone_subtypes(&self) -> Vec<&str> {
+        self.config.subtypes_for_scale_factor(self.scale_factor)
+    }
 
-        if scale_factor >= 10.0 {
-            subtypes.extend_from_slice(&["neighborhood"]);
+    /// Calculate total zones for the scale factor
+    ///
+    /// Zones read from a source are counted in the source, which loads (and
+    /// caches) the zone table if caching is enabled. Below scale factor 1 the
+    /// count is scaled down, rounding up.
+    fn calculate_total_zones(&self) -> Result<i64, SpatialBenchError> {
+        if let ZoneSource::Synthetic { .. } = self.config.source {
+            return Ok(self
+                .calculate_synthetic_zone_counts()
+                .iter()
+                .map(|(_, count)| count)
+                .sum());
         }
 
-        if scale_factor >= 100.0 {
-            subtypes.extend_from_slice(&["localadmin", "locality", "region", "dependency"]);
-        }
+        Ok(self.scale_zone_count(self.count_source_zones()?))
+    }
 
-        if scale_factor >= 1000.0 {
-            subtypes.push("country");
+    /// Scales the number of zones in the source down to the scale factor
+    fn scale_zone_count(&self, count: i64) -> i64 {
+        if self.scale_factor < 1.0 {
+            (count as f64 * self.scale_factor).ceil() as i64
+        } else {
+            count
         }
-
-        subtypes
     }
 
-    /// Calculate total zones for a given scale factor based on subtype counts
-    fn calculate_total_zones_for_scale_factor(scale_factor: f64) -> i64 {
-        let subtypes = Self::get_zone_subtypes_for_scale_factor(scale_factor);
-        let mut total = 0i64;
-
-        for subtype in subtypes {
-            let count = match subtype {
-                "microhood" => 74797,
-                "macrohood" => 42619,
-                "neighborhood" => 298615,
-                "county" => 39680,
-                "localadmin" => 19007,
-                "locality" => 555834,
-                "region" => 4714,
-                "dependency" => 105,
-                "country" => 378,
-                _ => 0,
-            };
-            total += count;
-        }
+    /// Calculate the number of synthetic zones of each subtype for the scale factor
+    ///
+    /// Synthetic zones have the reference number of zones of each subtype (see
+    /// [`ZONE_SUBTYPES`]). Below scale factor 1 the total is scaled down (rounding
+    /// up) and split between the subtypes in proportion to their counts. Every
+    /// level has at least one zone per continent, so that it covers them all.
+    fn calculate_synthetic_zone_counts(&self) -> Vec<(&str, i64)> {
+        let scale_factor = self.scale_factor;
+        let mut counts: Vec<(&str, i64)> = self
+            .get_zone_subtypes()
+            .into_iter()
+            .map(|subtype| (subtype, subtype_zone_count(subtype)))
+            .collect();
 
         // Scale down for testing purposes
         if scale_factor < 1.0 {
-            total = (total as f64 * scale_factor).ceil() as i64;
+            let total: i64 = counts.iter().map(|(_, count)| count).sum();
+            let scaled_total = (total as f64 * scale_factor).ceil() as i64;
+
+            // largest remainder apportionment, so the counts add up to the scaled total
+            let exact: Vec<f64> = counts
+                .iter()
+                .map(|(_, count)| *count as f64 * scale_factor)
+                .collect();
+            for ((_, count), e) in counts.iter_mut().zip(&exact) {
+                *count = e.floor() as i64;
+            }
+            let mut remaining = scaled_total - counts.iter().map(|(_, count)| count).sum::<i64>();
+            let mut by_remainder: Vec<usize> = (0..counts.len()).collect();
+            by_remainder.sort_by(|&a, &b| {
+                (exact[b] - exact[b].floor())
+                    .partial_cmp(&(exact[a] - exact[a].floor()))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            for i in by_remainder {
+                if remaining <= 0 {
+                    break;
+                }
+                counts[i].1 += 1;
+                remaining -= 1;
+            }
         }
 
-        total
+        for (_, count) in &mut counts {
+            *count = (*count).max(synthetic::min_level_count());
+        }
+        counts
     }
 
     /// Create a new zone generator with streaming approach
+    ///
+    /// Zones are configured by the zone configuration override, or read from the
+    /// default Overture release on S3 if none is set.
     pub fn new(scale_factor: f64, part: i32, part_count: i32) -> Self {
+        Self::new_with_config(
+            scale_factor,
+            part,
+            part_count,
+            spatial_overrides::zone_config_or_default(),
+        )
+    }
+
+    /// Create a new zone generator that reads zones from the given [`ZoneSource`]
+    ///
+    /// The other settings come from the zone configuration override.
+    pub fn new_with_source(
+        scale_factor: f64,
+        part: i32,
+        part_count: i32,
+        source: ZoneSource,
+    ) -> Self {
+        let config = ZoneConfig {
+            source,
+            ..spatial_overrides::zone_config_or_default()
+        };
+        Self::new_with_config(scale_factor, part, part_count, config)
+    }
+
+    /// Create a new zone generator with the given [`ZoneConfig`]
+    pub fn new_with_config(
+        scale_factor: f64,
+        part: i32,
+        part_count: i32,
+        config: ZoneConfig,
+    ) -> Self {
         let start = Instant::now();
         info!(
-            "Creating ZoneGenerator with scale_factor={}, part={}, part_count={}",
-            scale_factor, part, part_count
+            "Creating ZoneGenerator with scale_factor={}, part={}, part_count={}, source={}",
+            scale_factor, part, part_count, config.source
         );
         let elapsed = start.elapsed();
         info!("ZoneGenerator created in {:?}", elapsed);
@@ -1560,104 +2794,283 @@ impl ZoneGenerator {
             scale_factor,
             part,
             part_count,
+            config,
         }
     }
 
-    /// Calculate zones per partition
-    fn calculate_zones_per_part(&self) -> i64 {
-        let total_zones = Self::calculate_total_zones_for_scale_factor(self.scale_factor);
-        (total_zones as f64 / self.part_count as f64).ceil() as i64
+    /// Whether the zones have the `z_bbox` column, filled when the
+    /// [`ZoneGeometryOptions::bbox`](crate::zone::ZoneGeometryOptions) option is set
+    pub fn has_bbox(&self) -> bool {
+        self.config.geometry.bbox
     }
 
-    /// Calculate offset for this partition
-    fn calculate_offset(&self) -> i64 {
-        let zones_per_part = self.calculate_zones_per_part();
-        (self.part - 1) as i64 * zones_per_part
+    /// Set the directory of the zone cache, or read the source without a cache
+    /// (once per part) if `None`
+    pub fn with_cache_dir(mut self, cache_dir: Option<PathBuf>) -> Self {
+        self.config.cache_dir = cache_dir;
+        self
     }
 
-    /// Load zones for this specific partition using LIMIT and OFFSET
-    fn load_partition_zones(&self) -> Result<Vec<Zone>, Box<dyn std::error::Error>> {
-        info!(
-            "Loading zones for partition {} of {}",
-            self.part, self.part_count
-        );
-        let start_total = Instant::now();
+    /// Calculate the offset and the row count of this partition
+    fn calculate_part_range(&self) -> Result<(i64, i64), SpatialBenchError> {
+        Ok(self.part_range(self.calculate_total_zones()?))
+    }
 
-        // Create a connection to DuckDB
-        let t0 = Instant::now();
-        let conn = Connection::open_in_memory()?;
-        debug!("Opened DuckDB connection in {:?}", t0.elapsed());
+    /// Returns the offset and the row count of this partition of `total_zones`
+    fn part_range(&self, total_zones: i64) -> (i64, i64) {
+        let zones_per_part = (total_zones as f64 / self.part_count as f64).ceil() as i64;
+        let offset = (self.part - 1) as i64 * zones_per_part;
 
-        // Install and load required extensions
-        let t1 = Instant::now();
-        conn.execute_batch(
-            r#"
-            INSTALL httpfs;
-            LOAD httpfs;
-            INSTALL spatial;
-            LOAD spatial;
-
-            -- Public bucket: force unsigned requests
-            SET s3_access_key_id = '';
-            SET s3_secret_access_key = '';
-            SET s3_session_token = '';
-
-            -- Region + endpoint for the Overture bucket
-            SET s3_region = 'us-west-2';
-            SET s3_endpoint = 's3.us-west-2.amazonaws.com';
-            "#,
-        )?;
-        debug!(
-            "Installed and loaded DuckDB extensions in {:?}",
-            t1.elapsed()
-        );
+        // Don't exceed total available zones
+        let count = std::cmp::min(zones_per_part, total_zones - offset).max(0);
+        (offset, count)
+    }
 
-        // Calculate partition parameters
-        let zones_per_part = self.calculate_zones_per_part();
-        let offset = self.calculate_offset();
-        let zones_url = Self::get_zones_parquet_url();
-        let subtypes = Self::get_zone_subtypes_for_scale_factor(self.scale_factor);
+    /// Load the zones with keys `offset + 1..=offset + count`, with the configured
+    /// geometry processing
+    fn load_zones(
+        &self,
+        part_rows: Option<&mut PartRows>,
+        offset: i64,
+        count: i64,
+    ) -> Result<Vec<Zone>, SpatialBenchError> {
+        let mut zones = self.load_source_zones(part_rows, offset, count)?;
+        let options = &self.config.geometry;
+        if !options.is_identity() {
+            let start = Instant::now();
+            zones.iter_mut().for_each(|zone| options.apply(zone));
+            debug!(
+                "Processed {} zone boundaries in {:?}",
+                zones.len(),
+                start.elapsed()
+            );
+        }
+        Ok(zones)
+    }
 
-        info!(
-            "Partition {}: LIMIT {} OFFSET {} from {} with subtypes: {:?}",
-            self.part, zones_per_part, offset, zones_url, subtypes
-        );
+    /// Load the zones with keys `offset + 1..=offset + count` with WKB boundaries
+    ///
+    /// Boundaries read from the cache are returned as stored when no geometry
+    /// processing is configured; other zones are encoded after loading.
+    fn load_zone_wkbs(
+        &self,
+        part_rows: Option<&mut PartRows>,
+        offset: i64,
+        count: i64,
+    ) -> Result<Vec<ZoneWkb>, SpatialBenchError> {
+        let synthetic = matches!(self.config.source, ZoneSource::Synthetic { .. });
+        if self.config.geometry.is_identity() && !synthetic {
+            if let Some(path) = self.cache_path() {
+                return cache::load_zone_wkbs(&path, offset, count, |sink| {
+                    self.query_zones_into(None, sink)
+                });
+            }
+        }
+        self.load_zones(part_rows, offset, count)?
+            .into_iter()
+            .map(|zone| Ok(ZoneWkb::from_zone(zone)?))
+            .collect()
+    }
 
-        // Build the subtype filter
-        let subtype_filter = if subtypes.is_empty() {
-            return Err(format!(
-                "No subtypes found for scale factor {} in partition {}. This indicates a logic error.",
-                self.scale_factor,
-                self.part
-            ).into());
-        } else {
-            format!(
-                "subtype IN ({})",
-                subtypes
-                    .iter()
-                    .map(|s| format!("'{}'", s))
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            )
+    /// Load the zones with keys `offset + 1..=offset + count` as generated or stored
+    /// in the source
+    ///
+    /// Zones of a source read without a cache are moved out of `part_rows`, the
+    /// rows of the partition loaded by [`Self::load_part_rows`].
+    fn load_source_zones(
+        &self,
+        part_rows: Option<&mut PartRows>,
+        offset: i64,
+        count: i64,
+    ) -> Result<Vec<Zone>, SpatialBenchError> {
+        if let ZoneSource::Synthetic { seed } = self.config.source {
+            let mut levels = self.calculate_synthetic_zone_counts();
+            levels.sort_by_key(|(subtype, _)| subtype_rank(subtype));
+            return Ok(synthetic::generate_zones(seed, &levels, offset, count));
+        }
+
+        if let Some(path) = self.cache_path() {
+            return cache::load_zones(&path, offset, count, |sink| {
+                self.query_zones_into(None, sink)
+            });
+        }
+
+        let part_rows = part_rows.ok_or_else(|| {
+            SpatialBenchError::ZoneSource("zones of the partition are not loaded".to_string())
+        })?;
+        Ok(part_rows.by_ref().take(count as usize).collect())
+    }
+
+    /// Count the zones of the subtypes of the scale factor in the source
+    ///
+    /// The zones of the default Overture release are counted in advance (see
+    /// [`ZONE_SUBTYPES`]), so the release is not scanned. Other sources are
+    /// counted in the cache if caching is enabled, or else with a query that only
+    /// reads the `subtype` and `is_land` columns.
+    fn count_source_zones(&self) -> Result<i64, SpatialBenchError> {
+        if self.config.source == ZoneSource::default() {
+            return Ok(self
+                .get_zone_subtypes()
+                .iter()
+                .map(|subtype| subtype_zone_count(subtype))
+                .sum());
+        }
+        match self.cache_path() {
+            Some(path) => cache::count_zones(&path, |sink| self.query_zones_into(None, sink)),
+            None => {
+                let (conn, from_where) = self.open_zone_query()?;
+                let count: i64 =
+                    conn.query_row(&format!("SELECT COUNT(*) {from_where}"), [], |row| {
+                        row.get(0)
+                    })?;
+                Ok(count)
+            }
+        }
+    }
+
+    /// Whether the zones are read from the source without the on-disk cache
+    fn reads_source_without_cache(&self) -> bool {
+        !matches!(self.config.source, ZoneSource::Synthetic { .. }) && self.cache_path().is_none()
+    }
+
+    /// Load the zone table up to the end of this partition from a source without a
+    /// cache, and return the offset and row count of this partition with its rows
+    ///
+    /// The parent of a zone is on a coarser level, which sorts before it, so the
+    /// rows up to the end of the partition hold the parents of all its zones;
+    /// only the rows of the partition are kept.
+    fn load_part_rows(&self) -> Result<(i64, i64, PartRows), SpatialBenchError> {
+        let (offset, count) = self.calculate_part_range()?;
+        let mut table = self.build_zone_table(Some(offset + count))?;
+        table.truncate((offset + count) as usize);
+        table.drain(..(offset as usize).min(table.len()));
+        table.shrink_to_fit();
+        Ok((offset, count, table.into_iter()))
+    }
+
+    /// Returns the path of the cache file of the zone table, if caching is enabled
+    /// and the files of the source can be fingerprinted
+    ///
+    /// The cache holds every zone of the subtypes, so it is shared by all scale
+    /// factors using them.
+    fn cache_path(&self) -> Option<PathBuf> {
+        let dir = self.config.cache_dir.as_ref()?;
+        cache::cache_path(dir, &self.config.source, &self.get_zone_subtypes())
+    }
+
+    /// Query the zones of the subtypes of the scale factor from the source, only
+    /// the first `limit` ones if given, and assign their parent keys
+    fn build_zone_table(&self, limit: Option<i64>) -> Result<Vec<Zone>, SpatialBenchError> {
+        let mut zones = self.query_zones(limit)?;
+        let t0 = Instant::now();
+        hierarchy::assign_parent_keys(&mut zones);
+        debug!("Assigned parent zones in {:?}", t0.elapsed());
+        Ok(zones)
+    }
+
+    /// Open a DuckDB connection to the source and return it with the `FROM` and
+    /// `WHERE` clauses selecting the zones of the subtypes of the scale factor
+    fn open_zone_query(&self) -> Result<(Connection, String), SpatialBenchError> {
+        let source = &self.config.source;
+
+        // Create a connection to DuckDB
+        let t0 = Instant::now();
+        let conn = Connection::open_in_memory()?;
+        debug!("Opened DuckDB connection in {:?}", t0.elapsed());
+
+        // Install and load required extensions
+        let t1 = Instant::now();
+        conn.execute_batch(source.setup_sql())?;
+        debug!(
+            "Installed and loaded DuckDB extensions in {:?}",
+            t1.elapsed()
+        );
+
+        let subtypes = self.get_zone_subtypes();
+        let relation = source.relation_sql().ok_or_else(|| {
+            SpatialBenchError::ZoneSource(format!("{} is not read through DuckDB", source))
+        })?;
+
+        info!("Zones from {} with subtypes: {:?}", source, subtypes);
+
+        // Build the subtype filter
+        let subtype_filter = if subtypes.is_empty() {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "No zone subtypes configured for scale factor {}",
+                self.scale_factor
+            )));
+        } else {
+            format!(
+                "subtype IN ({})",
+                subtypes
+                    .iter()
+                    .map(|s| format!("'{}'", s))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
         };
 
         // Combine subtype filter with is_land filter
         let combined_filter = format!("{} AND is_land = true", subtype_filter);
 
+        Ok((
+            conn,
+            format!("FROM ({}) WHERE {}", relation, combined_filter),
+        ))
+    }
+
+    /// Query the zones of the subtypes of the scale factor from the source using
+    /// DuckDB, only the first `limit` ones in key order if given
+    fn query_zones(&self, limit: Option<i64>) -> Result<Vec<Zone>, SpatialBenchError> {
+        let mut zones = Vec::new();
+        self.query_zones_into(limit, &mut |zone| {
+            zones.push(zone);
+            Ok(())
+        })?;
+        Ok(zones)
+    }
+
+    /// Query the zones like [`Self::query_zones`], passing each zone to `sink` in
+    /// key order as it is read, without parent keys
+    fn query_zones_into(
+        &self,
+        limit: Option<i64>,
+        sink: &mut cache::ZoneSink,
+    ) -> Result<(), SpatialBenchError> {
+        let source = &self.config.source;
+        info!("Loading zones from {}", source);
+        let start_total = Instant::now();
+
+        let (conn, from_where) = self.open_zone_query()?;
+
+        // Order by administrative level, then GERS id (the remaining keys only break
+        // ties between rows with missing ids), so keys and partitions do not depend
+        // on the scan order
+        let subtype_order = format!(
+            "CASE z_subtype {} END",
+            ZONE_SUBTYPES
+                .iter()
+                .enumerate()
+                .map(|(rank, (subtype, _))| format!("WHEN '{}' THEN {}", subtype, rank))
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+
         let query = format!(
             "SELECT
                 COALESCE(id, '') as z_gersid,
                 COALESCE(country, '') as z_country,
                 COALESCE(region, '') as z_region,
-                COALESCE(names.primary, '') as z_name,
+                COALESCE(name, '') as z_name,
                 COALESCE(subtype, '') as z_subtype,
-                ST_AsWKB(geometry) as z_boundary
-             FROM read_parquet('{}', hive_partitioning=1)
-             WHERE {}
-             LIMIT {} OFFSET {};",
-            zones_url, combined_filter, zones_per_part, offset
+                geometry as z_boundary
+             {}
+             ORDER BY {}, z_gersid, z_name, z_country, z_region, z_boundary{};",
+            from_where,
+            subtype_order,
+            limit.map_or(String::new(), |limit| format!(" LIMIT {limit}"))
         );
-        debug!("Generated partition query: {}", query);
+        debug!("Generated zone query: {}", query);
 
         // Prepare + execute query
         let t2 = Instant::now();
@@ -1669,59 +3082,95 @@ impl ZoneGenerator {
         debug!("Executed query and got row iterator in {:?}", t3.elapsed());
 
         // Iterate rows and parse geometries
-        let mut zones = Vec::new();
-        let mut zone_id = offset + 1;
+        let mut zone_id = 1;
 
+        let encoding = source.geometry_encoding();
         let t4 = Instant::now();
-        while let Ok(Some(row)) = rows.next() {
+        while let Some(row) = rows.next()? {
             let z_gersid: String = row.get(0)?;
             let z_country: String = row.get(1)?;
             let z_region: String = row.get(2)?;
             let z_name: String = row.get(3)?;
             let z_subtype: String = row.get(4)?;
-            let wkb_bytes: Vec<u8> = row.get(5)?;
-            let geometry: Geometry = Wkb(&wkb_bytes).to_geo()?;
+            let geometry: Geometry = match encoding {
+                GeometryEncoding::Wkb => {
+                    let wkb_bytes: Vec<u8> = row.get(5)?;
+                    Wkb(&wkb_bytes).to_geo()?
+                }
+                GeometryEncoding::GeoJson => {
+                    let geojson: String = row.get(5)?;
+                    GeoJson(&geojson).to_geo()?
+                }
+            };
 
-            zones.push(Zone {
+            sink(Zone {
                 z_zonekey: zone_id,
                 z_gersid,
                 z_country,
                 z_region,
                 z_name,
+                z_level: subtype_rank(&z_subtype) as i32,
                 z_subtype,
+                z_parentkey: None,
                 z_boundary: geometry,
-            });
+                z_bbox: None,
+            })?;
 
-            if zones.len() % 1000 == 0 {
-                debug!("Loaded {} zones for partition {}", zones.len(), self.part);
+            if zone_id % 1000 == 0 {
+                debug!("Loaded {} zones", zone_id);
             }
             zone_id += 1;
         }
 
-        info!(
-            "Partition {} loaded: {} zones in {:?}",
-            self.part,
-            zones.len(),
-            t4.elapsed()
-        );
+        info!("Loaded {} zones in {:?}", zone_id - 1, t4.elapsed());
 
-        info!("Total partition load took {:?}", start_total.elapsed());
-        Ok(zones)
+        info!("Total zone load took {:?}", start_total.elapsed());
+        Ok(())
     }
 
     /// Return the row count for the given part
+    ///
+    /// # Panics
+    ///
+    /// Panics if the zone source cannot be read; see
+    /// [`Self::try_calculate_row_count`].
     pub fn calculate_row_count(&self) -> i64 {
-        let total_zones = Self::calculate_total_zones_for_scale_factor(self.scale_factor);
-        let zones_per_part = self.calculate_zones_per_part();
-        let offset = self.calculate_offset();
+        self.try_calculate_row_count()
+            .unwrap_or_else(|e| panic!("Failed to count zones: {e}"))
+    }
 
-        // Don't exceed total available zones
-        std::cmp::min(zones_per_part, total_zones - offset).max(0)
+    /// Return the row count for the given part, or the error reading the zone source
+    pub fn try_calculate_row_count(&self) -> Result<i64, SpatialBenchError> {
+        Ok(self.calculate_part_range()?.1)
     }
 
     /// Returns an iterator over the zone rows
+    ///
+    /// The iterator panics if the zone source cannot be read; see
+    /// [`Self::try_iter`].
     pub fn iter(&self) -> ZoneGeneratorIterator {
-        ZoneGeneratorIterator::new(self.clone())
+        ZoneGeneratorIterator(self.try_iter())
+    }
+
+    /// Returns an iterator over the zone rows with their boundaries as WKB
+    ///
+    /// When no geometry processing is configured, boundaries read from the zone
+    /// cache are passed through without being decoded. The iterator panics if
+    /// the zone source cannot be read; see [`Self::try_iter_wkb`].
+    pub fn iter_wkb(&self) -> ZoneGeneratorIterator<ZoneWkb> {
+        ZoneGeneratorIterator(self.try_iter_wkb())
+    }
+
+    /// Returns an iterator over the zone rows that yields the error reading the
+    /// zone source, after which it ends
+    pub fn try_iter(&self) -> TryZoneGeneratorIterator {
+        TryZoneGeneratorIterator::new(self.clone(), Self::load_zones)
+    }
+
+    /// Returns an iterator over the zone rows with their boundaries as WKB that
+    /// yields the error reading the zone source, after which it ends
+    pub fn try_iter_wkb(&self) -> TryZoneGeneratorIterator<ZoneWkb> {
+        TryZoneGeneratorIterator::new(self.clone(), Self::load_zone_wkbs)
     }
 }
 
@@ -1734,45 +3183,133 @@ impl IntoIterator for ZoneGenerator {
     }
 }
 
-/// Iterator that generates Zone rows by loading partition data on-demand
+/// Number of zone keys loaded at a time by [`ZoneGeneratorIterator`]
+pub const ZONE_BATCH_SIZE: i64 = 1024;
+
+/// Zone rows of a partition read from a source without a cache
+type PartRows = std::vec::IntoIter<Zone>;
+
+/// Loads the rows with keys `offset + 1..=offset + count`
+type ZoneBatchLoader<T> =
+    fn(&ZoneGenerator, Option<&mut PartRows>, i64, i64) -> Result<Vec<T>, SpatialBenchError>;
+
+/// Iterator over the Zone rows of a partition
+///
+/// Panics if the zone source cannot be read; use [`TryZoneGeneratorIterator`]
+/// to handle the error.
 #[derive(Debug)]
-pub struct ZoneGeneratorIterator {
-    zones: Vec<Zone>,
-    index: usize,
-}
-
-impl ZoneGeneratorIterator {
-    fn new(generator: ZoneGenerator) -> Self {
-        // Load zones for this partition only
-        let zones = generator.load_partition_zones().unwrap_or_else(|e| {
-            error!(
-                "Failed to load zones for partition {}: {}",
-                generator.part, e
-            );
-            Vec::new()
-        });
+pub struct ZoneGeneratorIterator<T = Zone>(TryZoneGeneratorIterator<T>);
 
-        ZoneGeneratorIterator { zones, index: 0 }
+impl<T> Iterator for ZoneGeneratorIterator<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .next()
+            .map(|zone| zone.unwrap_or_else(|e| panic!("Failed to load zones: {e}")))
     }
 }
 
-impl Iterator for ZoneGeneratorIterator {
-    type Item = Zone;
+/// Iterator over the Zone rows of a partition that yields the errors reading
+/// the zone source
+///
+/// Rows are loaded in batches of [`ZONE_BATCH_SIZE`] keys and moved out of the
+/// batch, so only one batch of boundaries is held in memory at a time. The
+/// partition is only counted (and the zone source read) by the first call to
+/// `next`. A source read without a cache is loaded then, and only the rows of
+/// the partition are kept, to be moved out batch by batch. After an error the
+/// iterator ends.
+#[derive(Debug)]
+pub struct TryZoneGeneratorIterator<T = Zone> {
+    generator: ZoneGenerator,
+    load: ZoneBatchLoader<T>,
+    /// Offset of the next batch and offset after the last row of the partition,
+    /// once counted
+    range: Option<(i64, i64)>,
+    /// Rows of the partition not yet loaded, for a source read without a cache
+    part_rows: Option<PartRows>,
+    batch: std::vec::IntoIter<T>,
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.zones.len() {
-            return None;
+impl<T> TryZoneGeneratorIterator<T> {
+    fn new(generator: ZoneGenerator, load: ZoneBatchLoader<T>) -> Self {
+        TryZoneGeneratorIterator {
+            generator,
+            load,
+            range: None,
+            part_rows: None,
+            batch: Vec::new().into_iter(),
         }
+    }
 
-        let zone = self.zones[self.index].clone();
-        self.index += 1;
-        Some(zone)
+    /// Counts the partition on the first call
+    fn range(&mut self) -> Result<(i64, i64), SpatialBenchError> {
+        if let Some(range) = self.range {
+            return Ok(range);
+        }
+        let (offset, count) = if self.generator.reads_source_without_cache() {
+            let (offset, count, rows) = self.generator.load_part_rows()?;
+            self.part_rows = Some(rows);
+            (offset, count)
+        } else {
+            self.generator.calculate_part_range()?
+        };
+        info!(
+            "Partition {} of {}: zones {} to {}",
+            self.generator.part,
+            self.generator.part_count,
+            offset + 1,
+            offset + count
+        );
+        self.range = Some((offset, offset + count));
+        Ok((offset, offset + count))
+    }
+}
+
+impl<T> Iterator for TryZoneGeneratorIterator<T> {
+    type Item = Result<T, SpatialBenchError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(zone) = self.batch.next() {
+                return Some(Ok(zone));
+            }
+            let (offset, end) = match self.range() {
+                Ok(range) => range,
+                Err(e) => {
+                    self.range = Some((0, 0));
+                    return Some(Err(e));
+                }
+            };
+            if offset >= end {
+                return None;
+            }
+
+            let count = ZONE_BATCH_SIZE.min(end - offset);
+            match (self.load)(&self.generator, self.part_rows.as_mut(), offset, count) {
+                Ok(zones) => {
+                    self.batch = zones.into_iter();
+                    self.range = Some((offset + count, end));
+                }
+                Err(e) => {
+                    self.range = Some((end, end));
+                    return Some(Err(e));
+                }
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::spatial::land::LandMask;
+    use crate::spatial::{
+        DistributionParams, DistributionType, GeomType, PolygonStyle, SizeUnit, SpatialConfig,
+    };
+    use crate::zone::{SubtypeTier, ZoneGeometryOptions, ZoneWeight};
+    use geo::{BoundingRect, Contains};
+    use std::collections::HashMap;
     #[test]
     fn test_vehicle_generation() {
         // Create a generator with a small scale factor
@@ -1883,6 +3420,91 @@ mod tests {
         assert_eq!(first.to_string(), "2|172|1|1|1997-12-24 08:47:14|1997-12-24 09:28:57|0.03|0.00|0.04|0.01|POINT(94.423867952 29.887250009)|POINT(94.43760277 29.88940658)|");
     }
 
+    #[test]
+    fn test_trip_zone_placement() {
+        let config = ZoneConfig {
+            source: ZoneSource::Synthetic { seed: 42 },
+            ..ZoneConfig::default()
+        };
+        let placement = ZonePlacement {
+            subtype_weights: vec![("county".to_string(), 1.0)],
+            weight: ZoneWeight::Uniform,
+        };
+        let counties: Vec<_> = ZoneGenerator::new_with_config(0.0001, 1, 1, config.clone())
+            .into_iter()
+            .filter(|zone| zone.z_subtype == "county")
+            .collect();
+
+        let trips: Vec<_> = TripGenerator::new(0.0001, 1, 1)
+            .with_zone_placement(config.clone(), &placement)
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(trips.len(), 600);
+        for trip in &trips {
+            assert!(
+                counties
+                    .iter()
+                    .any(|zone| zone.z_boundary.contains(&trip.t_pickuploc)),
+                "{:?} is in no county",
+                trip.t_pickuploc
+            );
+        }
+        let again: Vec<_> = TripGenerator::new(0.0001, 2, 3)
+            .with_zone_placement(config, &placement)
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(again, trips[200..400]);
+    }
+
+    #[test]
+    fn test_zone_sampler() {
+        let config = ZoneConfig {
+            source: ZoneSource::Synthetic { seed: 42 },
+            ..ZoneConfig::default()
+        };
+        let zones: Vec<_> = ZoneGenerator::new_with_config(0.01, 1, 1, config.clone())
+            .into_iter()
+            .collect();
+
+        // pickups lie inside the zones, which the microhoods tile, the same way each time
+        let placement = ZonePlacement {
+            subtype_weights: vec![("county".to_string(), 1.0), ("microhood".to_string(), 3.0)],
+            weight: ZoneWeight::Area,
+        };
+        let sampler = ZoneSampler::new(0.01, config.clone(), &placement).unwrap();
+        for key in 1..=500 {
+            let point = sampler.sample(key);
+            assert_eq!(sampler.sample(key), point);
+            assert!(zones
+                .iter()
+                .any(|zone| zone.z_subtype == "microhood" && zone.z_boundary.contains(&point)));
+
+            // dropoffs too, in the pickup zone when no other zone is within the distance
+            let dropoff = sampler.sample_dropoff(key, point, 5.0);
+            assert_eq!(sampler.sample_dropoff(key, point, 5.0), dropoff);
+            assert!(zones
+                .iter()
+                .any(|zone| zone.z_subtype == "microhood" && zone.z_boundary.contains(&dropoff)));
+            let dropoff = sampler.sample_dropoff(key, point, 0.0);
+            assert!(
+                zones
+                    .iter()
+                    .any(|zone| zone.z_boundary.contains(&point)
+                        && zone.z_boundary.contains(&dropoff))
+            );
+        }
+
+        // subtypes must be in the Zone table at the scale factor
+        let placement = ZonePlacement {
+            subtype_weights: vec![("country".to_string(), 1.0)],
+            ..ZonePlacement::default()
+        };
+        assert!(ZoneSampler::new(0.01, config, &placement).is_err());
+        assert!("volume".parse::<ZoneWeight>().is_err());
+    }
+
     #[test]
     fn test_building_generation() {
         // Create a generator with a small scale factor
@@ -1910,76 +3532,2199 @@ mod tests {
     }
 
     #[test]
-    fn test_zone_generation() {
-        // Create a generator with a small scale factor
-        let generator = ZoneGenerator::new(0.001, 1, 1);
-        let zones: Vec<_> = generator.into_iter().collect();
+    fn test_building_regions() {
+        let west = Region::from_bbox("west", [-125.0, 32.0, -114.0, 49.0], None);
+        let east = Region::from_bbox("east", [-84.0, 25.0, -67.0, 47.0], Some(0.0));
+        // a default weight (an area) cannot be mixed with a relative one
+        assert!(BuildingGenerator::new(0.51, 1, 1)
+            .with_regions(&[west.clone(), east.clone()])
+            .is_err());
+        assert!(TripGenerator::new(0.001, 1, 1)
+            .with_regions(&[west.clone(), east.clone()])
+            .is_err());
+
+        let west = Region {
+            weight: Some(1.0),
+            ..west
+        };
+        let buildings = BuildingGenerator::new(0.51, 1, 1)
+            .with_regions(&[west, east])
+            .unwrap();
+
+        // every building is in the western region, the eastern one has no weight
+        for building in buildings.iter() {
+            let bbox = building.b_boundary.bounding_rect().unwrap();
+            assert!(
+                bbox.min().x >= -126.0 && bbox.max().x <= -113.0,
+                "{building}"
+            );
+            assert!(bbox.min().y >= 31.0 && bbox.max().y <= 50.0, "{building}");
+        }
 
-        assert_eq!(zones.len(), 158);
+        // the default regions are the continents
+        let default: Vec<_> = BuildingGenerator::new(0.51, 1, 1).iter().take(10).collect();
+        let continents: Vec<_> = BuildingGenerator::new(0.51, 1, 1)
+            .with_regions(&ContinentAffines::default().regions())
+            .unwrap()
+            .iter()
+            .take(10)
+            .collect();
+        assert_eq!(default, continents);
+    }
 
-        // Check first zone
-        let first = &zones[0];
-        assert_eq!(first.z_zonekey, 1);
-        // The first zone is now a county due to the is_land filter and county being in base subtypes
-        assert_eq!(first.z_subtype, "county");
-        // Verify the string format matches the expected pattern (but don't check exact content since it's dynamic)
-        let expected_pattern = format!(
-            "{}|{}|{}|{}|{}|{}|{:?}|",
-            first.z_zonekey,
-            first.z_gersid,
-            first.z_country,
-            first.z_region,
-            first.z_name,
-            first.z_subtype,
-            first.z_boundary
-        );
-        assert_eq!(first.to_string(), expected_pattern);
+    #[test]
+    fn test_land_mask() {
+        let mask = LandMask::bundled();
+        let land = [
+            (2.35, 48.85),  // Paris
+            (37.6, 55.75),  // Moscow
+            (31.2, 30.0),   // Cairo
+            (36.8, -1.3),   // Nairobi
+            (77.2, 28.6),   // Delhi
+            (116.4, 39.9),  // Beijing
+            (149.1, -35.3), // Canberra
+            (-87.6, 41.9),  // Chicago
+            (-99.1, 19.4),  // Mexico City
+            (-46.6, -23.5), // Sao Paulo
+            (-21.9, 64.1),  // Reykjavik
+        ];
+        let water = [
+            (-40.0, 30.0), // Atlantic
+            (-140.0, 0.0), // Pacific
+            (80.0, -20.0), // Indian Ocean
+            (18.0, 35.0),  // Mediterranean
+            (34.0, 43.0),  // Black Sea
+            (51.0, 42.0),  // Caspian Sea
+            (19.5, 57.5),  // Baltic Sea
+            (-85.0, 60.0), // Hudson Bay
+            (88.0, 15.0),  // Bay of Bengal
+            (-90.0, 25.0), // Gulf of Mexico
+        ];
+        for (x, y) in land {
+            assert!(mask.contains(&Point::new(x, y)), "({x}, {y}) is land");
+        }
+        for (x, y) in water {
+            assert!(!mask.contains(&Point::new(x, y)), "({x}, {y}) is water");
+        }
+
+        // buildings are redrawn onto land, keeping those on land already
+        let default: Vec<_> = BuildingGenerator::new(0.51, 1, 1).iter().collect();
+        let masked: Vec<_> = BuildingGenerator::new(0.51, 1, 1)
+            .with_land_mask()
+            .iter()
+            .collect();
+        assert_eq!(masked.len(), default.len());
+        let mut kept = 0;
+        for (building, original) in masked.iter().zip(&default) {
+            let center = building.b_boundary.bounding_rect().unwrap().center();
+            assert!(mask.contains(&center.into()), "{building}");
+            let original_center = original.b_boundary.bounding_rect().unwrap().center();
+            if mask.contains(&original_center.into()) {
+                assert_eq!(building, original);
+                kept += 1;
+            }
+        }
+        assert!(kept > 0 && kept < default.len());
+
+        // and every part draws the same candidates
+        let all: Vec<_> = BuildingGenerator::new(1.0, 1, 1)
+            .with_land_mask()
+            .iter()
+            .collect();
+        let part: Vec<_> = BuildingGenerator::new(1.0, 2, 3)
+            .with_land_mask()
+            .iter()
+            .collect();
+        let start = (part[0].b_buildingkey - 1) as usize;
+        assert_eq!(part, all[start..start + part.len()]);
+
+        // trips are drawn again until both the pickup and the dropoff are on land
+        let default: Vec<_> = TripGenerator::new(0.001, 1, 1).iter().collect();
+        let masked: Vec<_> = TripGenerator::new(0.001, 1, 1)
+            .with_land_mask()
+            .iter()
+            .collect();
+        assert_eq!(masked.len(), default.len());
+        for (trip, original) in masked.iter().zip(&default) {
+            assert!(mask.contains(&trip.t_pickuploc), "{trip}");
+            assert!(mask.contains(&trip.t_dropoffloc), "{trip}");
+            assert_eq!(trip.t_distance, original.t_distance);
+            if mask.contains(&original.t_pickuploc) && mask.contains(&original.t_dropoffloc) {
+                assert_eq!(trip, original);
+            }
+        }
     }
 
     #[test]
-    fn test_zone_subtype_filters() {
-        // Test scale factor 0-10: should include microhood, macrohood, and county
-        let subtypes_0_10 = ZoneGenerator::get_zone_subtypes_for_scale_factor(5.0);
-        assert_eq!(subtypes_0_10, vec!["microhood", "macrohood", "county"]);
+    fn test_linestring_geometry() {
+        let identity = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let config = SpatialConfig {
+            dist_type: DistributionType::Uniform,
+            geom_type: GeomType::LineString,
+            dim: 2,
+            seed: 11,
+            width: 0.0,
+            height: 0.0,
+            maxseg: 5,
+            polysize: 0.01,
+            polygon_style: PolygonStyle::Star,
+            params: DistributionParams::None,
+            validity: Validity::Raw,
+            units: SizeUnit::Unit,
+        };
+        let generator = SpatialGenerator::try_new(config.clone()).unwrap();
+        for i in 0..200 {
+            let Geometry::LineString(line) = generator.generate(i, &identity) else {
+                panic!("expected a line string");
+            };
+            assert!((2..=6).contains(&line.0.len()), "{line:?}");
+            for segment in line.lines() {
+                let length = segment.dx().hypot(segment.dy());
+                assert!(length <= 0.01 + 1e-8, "{line:?}");
+            }
+            assert!(line
+                .coords()
+                .all(|c| (0.0..=1.0).contains(&c.x) && (0.0..=1.0).contains(&c.y)));
+            assert_eq!(generator.generate(i, &identity), Geometry::LineString(line));
+        }
 
-        // Test scale factor 10-100: should include microhood, macrohood, county, and neighborhood
-        let subtypes_10_100 = ZoneGenerator::get_zone_subtypes_for_scale_factor(50.0);
-        assert_eq!(
-            subtypes_10_100,
-            vec!["microhood", "macrohood", "county", "neighborhood"]
-        );
+        // segments have a length
+        let flat = SpatialConfig {
+            polysize: 0.0,
+            ..config.clone()
+        };
+        assert!(SpatialGenerator::try_new(flat).is_err());
+
+        // parcels are boxes or polygons
+        let parcel = SpatialConfig {
+            dist_type: DistributionType::Parcel,
+            params: DistributionParams::Parcel {
+                srange: 0.1,
+                dither: 0.0,
+            },
+            ..config
+        };
+        assert!(SpatialGenerator::try_new_with_cardinality(parcel, 100).is_err());
+    }
 
-        // Test scale factor 100-1000: should include all except country
-        let subtypes_100_1000 = ZoneGenerator::get_zone_subtypes_for_scale_factor(500.0);
+    #[test]
+    fn test_footprint_polygon_style() {
+        use crate::spatial::utils::METERS_PER_DEGREE;
+        use geo::{Area, Validation};
+
+        let config = SpatialConfig {
+            dist_type: DistributionType::Uniform,
+            geom_type: GeomType::Polygon,
+            dim: 2,
+            seed: 3,
+            width: 0.0,
+            height: 0.0,
+            maxseg: 7,
+            polysize: 0.0,
+            polygon_style: PolygonStyle::Footprint {
+                median_size: 20.0,
+                size_sigma: 0.5,
+            },
+            params: DistributionParams::None,
+            validity: Validity::Raw,
+            units: SizeUnit::Unit,
+        };
+        let generator = SpatialGenerator::try_new(config.clone()).unwrap();
+        // a one degree square on the equator, and a wider one further north
+        let equator = [1.0, 0.0, 10.0, 0.0, 1.0, 0.0];
+        let north = [3.0, 0.0, 10.0, 0.0, 1.0, 60.0];
+        let area_m2 = |polygon: &geo::Polygon| {
+            let lat = polygon.exterior().0[0].y.to_radians();
+            polygon.unsigned_area() * METERS_PER_DEGREE * METERS_PER_DEGREE * lat.cos()
+        };
+
+        let mut vertex_counts: HashMap<usize, usize> = HashMap::new();
+        let mut rectangle_diagonals = Vec::new();
+        for i in 0..2000 {
+            let Geometry::Polygon(polygon) = generator.generate(i, &equator) else {
+                panic!("expected a polygon");
+            };
+            assert!(polygon.is_valid(), "{polygon:?}");
+            let vertices = polygon.exterior().0.len() - 1;
+            *vertex_counts.entry(vertices).or_default() += 1;
+            if vertices == 4 {
+                let c = &polygon.exterior().0;
+                let diagonal = (c[2].x - c[0].x).hypot(c[2].y - c[0].y) * METERS_PER_DEGREE;
+                rectangle_diagonals.push(diagonal);
+            }
+
+            // sizes are in meters, whatever the region and latitude
+            let Geometry::Polygon(stretched) = generator.generate(i, &north) else {
+                panic!("expected a polygon");
+            };
+            let (a, b) = (area_m2(&polygon), area_m2(&stretched));
+            assert!((a - b).abs() < a * 0.01, "{a} m² and {b} m²");
+        }
+
+        // mostly rectangles, then L, T and U shapes
+        assert!(vertex_counts[&4] > 1000, "{vertex_counts:?}");
+        assert!(vertex_counts[&6] > 250, "{vertex_counts:?}");
+        assert!(vertex_counts[&8] > 300, "{vertex_counts:?}");
+
+        // the diagonal of a rectangle is 1 to 1.42 times its longer side
+        rectangle_diagonals.sort_by(f64::total_cmp);
+        let median = rectangle_diagonals[rectangle_diagonals.len() / 2];
+        assert!((20.0..28.4).contains(&median), "median diagonal {median} m");
+
+        // parcels are sized by the parcel
+        let parcel = SpatialConfig {
+            dist_type: DistributionType::Parcel,
+            params: DistributionParams::Parcel {
+                srange: 0.1,
+                dither: 0.0,
+            },
+            ..config
+        };
+        assert!(SpatialGenerator::try_new_with_cardinality(parcel, 100).is_err());
+    }
+
+    #[test]
+    fn test_3d_generation() {
+        let trip_spatial = SpatialGenerator::new(SpatialConfig {
+            dim: 3,
+            ..SpatialDefaults::trip_default().config().clone()
+        });
+        let trips: Vec<_> = TripGenerator::new_with_distributions_and_text_pool(
+            0.01,
+            1,
+            1,
+            Distributions::static_default(),
+            TextPool::get_or_init_default(),
+            crate::kde::default_distance_kde(),
+            trip_spatial,
+        )
+        .iter()
+        .take(100)
+        .collect();
+        for trip in &trips {
+            let z = trip.t_pickup_z.unwrap();
+            assert_eq!(
+                z,
+                ground_elevation(trip.t_pickuploc.x(), trip.t_pickuploc.y())
+            );
+            assert!((0.0..2000.0).contains(&z), "{z}");
+            assert!(trip.t_dropoff_z.is_some());
+            assert!(trip.to_string().contains("|POINT Z("), "{trip}");
+        }
+
+        // a point with Z is 29 bytes of ISO WKB, with type 1001
+        let trip = &trips[0];
+        let pickup = Geometry::Point(trip.t_pickuploc);
+        let wkb = GeometryZ::new(&pickup, trip.t_pickup_z).to_wkb().unwrap();
+        assert_eq!(wkb.len(), 29);
+        assert_eq!(u32::from_le_bytes(wkb[1..5].try_into().unwrap()), 1001);
         assert_eq!(
-            subtypes_100_1000,
-            vec![
-                "microhood",
-                "macrohood",
-                "county",
-                "neighborhood",
-                "localadmin",
-                "locality",
-                "region",
-                "dependency"
-            ]
+            f64::from_le_bytes(wkb[21..29].try_into().unwrap()),
+            trip.t_pickup_z.unwrap()
         );
+        let wkb_2d = GeometryZ::new(&pickup, None).to_wkb().unwrap();
+        assert_eq!(wkb_2d, pickup.to_wkb(CoordDimensions::xy()).unwrap());
 
-        // Test scale factor 1000+: should include all subtypes
-        let subtypes_1000_plus = ZoneGenerator::get_zone_subtypes_for_scale_factor(2000.0);
-        assert_eq!(
-            subtypes_1000_plus,
-            vec![
-                "microhood",
-                "macrohood",
-                "county",
-                "neighborhood",
-                "localadmin",
-                "locality",
-                "region",
-                "dependency",
-                "country"
-            ]
+        let generator = BuildingGenerator::new(1.0, 1, 1);
+        let building_spatial = SpatialGenerator::new(SpatialConfig {
+            dim: 3,
+            ..generator.spatial_gen.config().clone()
+        });
+        let generator_3d = BuildingGenerator::new_with_distributions_and_text_pool(
+            1.0,
+            1,
+            1,
+            Distributions::static_default(),
+            TextPool::get_or_init_default(),
+            building_spatial,
         );
+        assert!(!generator.is_3d());
+        assert!(generator_3d.is_3d());
+
+        let mut towers = 0;
+        for (flat, building) in generator.iter().zip(generator_3d.iter()).take(2000) {
+            // the footprint is the same, with the heights in separate columns
+            assert_eq!(flat.b_boundary, building.b_boundary);
+            assert_eq!(flat.b_height, None);
+            // heights are derived from the key, so every part generates the same rows
+            let floors = building_floors(building.b_buildingkey as u64);
+            assert_eq!(building.b_floors, Some(floors));
+            let height = building.b_height.unwrap();
+            assert!((1..=120).contains(&floors), "{floors}");
+            assert!(
+                (floors as f64 * 3.2..=floors as f64 * 3.2 + 3.0).contains(&height),
+                "{height} m for {floors} floors"
+            );
+            towers += (floors >= 10) as usize;
+
+            let line = building.to_string();
+            assert!(
+                line.starts_with(&format!(
+                    "{}|{}|POLYGON Z((",
+                    building.b_buildingkey, building.b_name
+                )),
+                "{line}"
+            );
+            assert!(line.ends_with(&format!("|{height:.2}|{floors}|")), "{line}");
+            let wkb = GeometryZ::new(&building.footprint(), building.b_elevation)
+                .to_wkb()
+                .unwrap();
+            assert_eq!(u32::from_le_bytes(wkb[1..5].try_into().unwrap()), 1003);
+        }
+        // most buildings are low, a few are towers
+        assert!((10..400).contains(&towers), "{towers} towers");
+
+        let mut config = generator.spatial_gen.config().clone();
+        config.dim = 4;
+        assert!(SpatialGenerator::try_new(config).is_err());
+    }
+
+    #[test]
+    fn test_non_overlapping_buildings() {
+        use geo::{Intersects, Rect};
+
+        // pairs of intersecting footprints, swept by the west edge of their bbox
+        let overlaps = |buildings: &[Building]| {
+            let mut boxes: Vec<_> = buildings
+                .iter()
+                .map(|b| (b.b_boundary.bounding_rect().unwrap(), &b.b_boundary))
+                .collect();
+            boxes.sort_by(|a, b| a.0.min().x.total_cmp(&b.0.min().x));
+            let mut count = 0;
+            for (i, (bbox, footprint)) in boxes.iter().enumerate() {
+                for (other_bbox, other) in &boxes[i + 1..] {
+                    if other_bbox.min().x > bbox.max().x {
+                        break;
+                    }
+                    if bbox.intersects(other_bbox) && footprint.intersects(*other) {
+                        count += 1;
+                    }
+                }
+            }
+            count
+        };
+
+        let overlapping: Vec<_> = BuildingGenerator::new(1.0, 1, 1).iter().collect();
+        assert!(overlaps(&overlapping) > 0);
+
+        let generator = BuildingGenerator::new(1.0, 1, 1).with_non_overlapping();
+        let buildings: Vec<_> = generator.iter().collect();
+        assert_eq!(overlaps(&buildings), 0);
+        // conflicts are rare, and buildings without one stay in place
+        let moved = overlapping
+            .iter()
+            .zip(&buildings)
+            .take_while(|(a, b)| a.b_buildingkey == b.b_buildingkey)
+            .filter(|(a, b)| a.b_boundary != b.b_boundary)
+            .count();
+        assert!(moved < overlapping.len() / 10, "{moved} buildings moved");
+        // and none is dropped, so the keys have no gaps
+        assert_eq!(buildings.len(), overlapping.len());
+        assert!(buildings
+            .iter()
+            .zip(1..)
+            .all(|(building, key)| building.b_buildingkey == key));
+
+        // every part resolves the conflicts of the whole table
+        let parts: Vec<_> = (1..=3)
+            .flat_map(|part| {
+                BuildingGenerator::new(1.0, part, 3)
+                    .with_non_overlapping()
+                    .iter()
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(parts, buildings);
+
+        // the placement is computed once and shared by the clones of the generator
+        // and the generators of the other parts
+        assert_eq!(generator.clone().iter().collect::<Vec<_>>(), buildings);
+        assert!(Arc::ptr_eq(
+            generator.placement.get().unwrap(),
+            generator.clone().placement.get().unwrap()
+        ));
+        let part = BuildingGenerator::new(1.0, 2, 3).with_non_overlapping();
+        assert!(Arc::ptr_eq(
+            generator.placement.get().unwrap(),
+            &part.shared_placement().unwrap()
+        ));
+
+        // placing fails if a building has no free location
+        let square = || Ok(Rect::new((0.0, 0.0), (1.0, 1.0)).to_polygon().into());
+        assert!(HardCorePlacement::new(1, |_, _| square()).is_ok());
+        assert!(HardCorePlacement::new(2, |_, _| square()).is_err());
+    }
+
+    #[test]
+    fn test_building_footprints() {
+        use geo::{Area, Contains, Validation};
+
+        let default: Vec<_> = BuildingGenerator::new(0.51, 1, 1).iter().collect();
+        let footprints = |courtyard_probability, multipolygon_probability| {
+            BuildingGenerator::new(0.51, 1, 1)
+                .with_footprint(FootprintOptions {
+                    courtyard_probability,
+                    multipolygon_probability,
+                    max_parts: 3,
+                })
+                .iter()
+                .collect::<Vec<_>>()
+        };
+
+        // every footprint gets a courtyard inside its exterior
+        for (building, original) in footprints(1.0, 0.0).iter().zip(&default) {
+            assert!(building.b_campus.is_none(), "{building}");
+            let polygon = &building.b_boundary;
+            let exterior = geo::Polygon::new(polygon.exterior().clone(), vec![]);
+            assert_eq!(exterior, original.b_boundary);
+            // but polygons clamped flat onto the edge of a region
+            let courtyards = if original.b_boundary.unsigned_area() > 0.0 {
+                1
+            } else {
+                0
+            };
+            assert_eq!(polygon.interiors().len(), courtyards, "{building}");
+            assert!(polygon.is_valid(), "{building}");
+        }
+
+        // campuses stay in the bounding box of the original footprint
+        for (building, original) in footprints(0.0, 1.0).iter().zip(&default) {
+            let Some(campus) = &building.b_campus else {
+                panic!("expected a campus, got {building}");
+            };
+            // the boundary is the outline the parts were shaped from
+            assert_eq!(building.b_boundary, original.b_boundary);
+            assert!((2..=3).contains(&campus.0.len()));
+            assert!(campus.is_valid(), "{building}");
+            let bbox = original.b_boundary.bounding_rect().unwrap();
+            let bbox = geo::Rect::new(
+                bbox.min() - geo::coord! { x: 1e-8, y: 1e-8 },
+                bbox.max() + geo::coord! { x: 1e-8, y: 1e-8 },
+            );
+            assert!(bbox.contains(campus), "{building}");
+        }
+
+        // without probabilities, footprints are unchanged
+        assert_eq!(footprints(0.0, 0.0), default);
+    }
+
+    #[test]
+    fn test_parcel_distribution() {
+        let identity = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let config = |geom_type, dither| SpatialConfig {
+            dist_type: DistributionType::Parcel,
+            geom_type,
+            dim: 2,
+            seed: 42,
+            width: 0.0,
+            height: 0.0,
+            maxseg: 6,
+            polysize: 0.0,
+            polygon_style: PolygonStyle::Star,
+            params: DistributionParams::Parcel {
+                srange: 0.2,
+                dither,
+            },
+            validity: Validity::Raw,
+            units: SizeUnit::Unit,
+        };
+
+        // without dither the boxes tile the unit square
+        let generator =
+            SpatialGenerator::try_new_with_cardinality(config(GeomType::Box, 0.0), 100).unwrap();
+        let boxes: Vec<Rect> = (1..=100)
+            .map(|i| generator.generate(i, &identity).bounding_rect().unwrap())
+            .collect();
+        let area: f64 = boxes.iter().map(|r| r.width() * r.height()).sum();
+        assert!((area - 1.0).abs() < 1e-6, "total area {area}");
+        for (i, a) in boxes.iter().enumerate() {
+            for b in &boxes[i + 1..] {
+                let overlap_x = a.max().x.min(b.max().x) - a.min().x.max(b.min().x);
+                let overlap_y = a.max().y.min(b.max().y) - a.min().y.max(b.min().y);
+                assert!(
+                    overlap_x <= 1e-9 || overlap_y <= 1e-9,
+                    "{a:?} overlaps {b:?}"
+                );
+            }
+        }
+
+        // polygons stay inside their parcel
+        let generator =
+            SpatialGenerator::try_new_with_cardinality(config(GeomType::Polygon, 0.3), 100)
+                .unwrap();
+        for (i, parcel) in (1..).zip(&boxes) {
+            let polygon = generator.generate(i, &identity);
+            let bbox = polygon.bounding_rect().unwrap();
+            assert!(bbox.min().x >= parcel.min().x - 1e-9 && bbox.max().x <= parcel.max().x + 1e-9);
+            assert!(bbox.min().y >= parcel.min().y - 1e-9 && bbox.max().y <= parcel.max().y + 1e-9);
+        }
+
+        // parcels in water are drawn again in another region by the land mask,
+        // keeping their parcel of the unit square, which is owned by their key
+        let atlantic = Region::from_bbox("atlantic", [-45.0, 25.0, -35.0, 35.0], None);
+        let france = Region::from_bbox("france", [0.0, 44.0, 5.0, 48.0], None);
+        let targets = [
+            WeightedTarget {
+                m: atlantic.affine,
+                cdf: 0.5,
+            },
+            WeightedTarget {
+                m: france.affine,
+                cdf: 1.0,
+            },
+        ];
+        for i in 1..=100 {
+            let parcel =
+                generate_in_continent(&generator, &targets, Some(LandMask::bundled()), i).unwrap();
+            assert_eq!(parcel, generator.generate(i, &france.affine));
+        }
+        // but a region without land has no room for them
+        assert!(
+            generate_in_continent(&generator, &targets[..1], Some(LandMask::bundled()), 1).is_err()
+        );
+
+        // and are not redrawn to avoid overlaps, which they never have
+        let buildings = |non_overlapping| {
+            let generator = BuildingGenerator::new_with_distributions_and_text_pool(
+                0.51,
+                1,
+                1,
+                Distributions::static_default(),
+                TextPool::get_or_init_default(),
+                SpatialGenerator::try_new_with_cardinality(config(GeomType::Box, 0.0), 1).unwrap(),
+            );
+            let generator = if non_overlapping {
+                generator.with_non_overlapping()
+            } else {
+                generator
+            };
+            generator.iter().collect::<Vec<_>>()
+        };
+        assert_eq!(buildings(true), buildings(false));
+
+        // parcels have no point geometry
+        assert!(
+            SpatialGenerator::try_new_with_cardinality(config(GeomType::Point, 0.0), 100).is_err()
+        );
+
+        // and need the number of records to split the unit square into
+        assert!(SpatialGenerator::try_new(config(GeomType::Box, 0.0)).is_err());
+        let single =
+            SpatialGenerator::try_new_with_cardinality(config(GeomType::Box, 0.0), 1).unwrap();
+        assert_eq!(
+            single.generate(1, &identity).bounding_rect(),
+            Some(Rect::new((0.0, 0.0), (1.0, 1.0)))
+        );
+    }
+
+    #[test]
+    fn test_hotspot_distribution() {
+        use crate::spatial::utils::METERS_PER_DEGREE;
+        use crate::spatial::{HotspotBackground, HotspotComponent};
+
+        let sedona = HotspotComponent {
+            lon: -111.76,
+            lat: 34.87,
+            sigma_km: 5.0,
+            weight: 3.0,
+        };
+        let berlin = HotspotComponent {
+            lon: 13.4,
+            lat: 52.52,
+            sigma_km: 20.0,
+            weight: 1.0,
+        };
+        let config = |background| SpatialConfig {
+            dist_type: DistributionType::Hotspot,
+            geom_type: GeomType::Point,
+            dim: 2,
+            seed: 11,
+            width: 0.0,
+            height: 0.0,
+            maxseg: 0,
+            polysize: 0.0,
+            polygon_style: PolygonStyle::Star,
+            params: DistributionParams::Hotspot {
+                components: vec![sedona.clone(), berlin.clone()],
+                background,
+            },
+            validity: Validity::Raw,
+            units: SizeUnit::Unit,
+        };
+        let km_to = |p: &Point, c: &HotspotComponent| {
+            let dx = (p.x() - c.lon) * c.lat.to_radians().cos();
+            dx.hypot(p.y() - c.lat) * METERS_PER_DEGREE / 1000.0
+        };
+
+        // a uniform background in a region of lon and lat 0 to 10
+        let generator = SpatialGenerator::try_new(config(Some(Box::new(HotspotBackground {
+            weight: 1.0,
+            dist_type: DistributionType::Uniform,
+            params: DistributionParams::None,
+        }))))
+        .unwrap();
+        let region = [10.0, 0.0, 0.0, 0.0, 10.0, 0.0];
+        let points: Vec<Point> = (0..5000)
+            .map(|i| generator.generate(i, &region).try_into().unwrap())
+            .collect();
+        let mut sedona_km: Vec<f64> = points
+            .iter()
+            .map(|p| km_to(p, &sedona))
+            .filter(|km| *km < 50.0)
+            .collect();
+        let berlin_count = points.iter().filter(|p| km_to(p, &berlin) < 200.0).count();
+        let background = points
+            .iter()
+            .filter(|p| (0.0..=10.0).contains(&p.x()) && (0.0..=10.0).contains(&p.y()))
+            .count();
+        assert!(
+            (2800..3200).contains(&sedona_km.len()),
+            "{}",
+            sedona_km.len()
+        );
+        assert!((850..1150).contains(&berlin_count), "{berlin_count}");
+        assert_eq!(sedona_km.len() + berlin_count + background, 5000);
+
+        // the median distance of a 2D Gaussian is 1.18 sigma
+        sedona_km.sort_by(f64::total_cmp);
+        let median = sedona_km[sedona_km.len() / 2];
+        assert!((5.2..6.6).contains(&median), "median {median} km");
+        // the first keys are reserved for the components in turn
+        assert!(km_to(&points[1], &sedona) < 50.0);
+        assert!(km_to(&points[2], &berlin) < 200.0);
+
+        // polygons are generated around the point
+        let mut polygon_config = config(None);
+        polygon_config.geom_type = GeomType::Polygon;
+        polygon_config.maxseg = 6;
+        polygon_config.polysize = 0.01;
+        let polygons = SpatialGenerator::try_new(polygon_config).unwrap();
+        for i in 0..100 {
+            let bbox = polygons.generate(i, &region).bounding_rect().unwrap();
+            let center = Point::from(bbox.center());
+            assert!(km_to(&center, &sedona) < 50.0 || km_to(&center, &berlin) < 200.0);
+            assert!(bbox.width() < 0.1, "{bbox:?}");
+        }
+
+        let invalid = |components: Vec<HotspotComponent>, background| {
+            SpatialGenerator::try_new(SpatialConfig {
+                params: DistributionParams::Hotspot {
+                    components,
+                    background,
+                },
+                ..config(None)
+            })
+            .is_err()
+        };
+        let zero_sigma = HotspotComponent {
+            sigma_km: 0.0,
+            ..sedona.clone()
+        };
+        let no_weight = HotspotComponent {
+            weight: 0.0,
+            ..sedona.clone()
+        };
+        assert!(invalid(vec![zero_sigma], None));
+        assert!(invalid(vec![no_weight], None));
+        assert!(invalid(vec![], None));
+        for (dist_type, params) in [
+            (DistributionType::Normal, DistributionParams::None),
+            (
+                DistributionType::Parcel,
+                DistributionParams::Parcel {
+                    srange: 0.1,
+                    dither: 0.0,
+                },
+            ),
+        ] {
+            let background = HotspotBackground {
+                weight: 1.0,
+                dist_type,
+                params,
+            };
+            assert!(invalid(vec![sedona.clone()], Some(Box::new(background))));
+        }
+
+        // every component has trips at SF 0.001, even with a tiny share of them
+        let components: Vec<HotspotComponent> = (0..12)
+            .map(|i| HotspotComponent {
+                lon: -120.0 + 20.0 * i as f64,
+                lat: 40.0,
+                sigma_km: 10.0,
+                weight: if i == 0 { 1000.0 } else { 0.01 },
+            })
+            .collect();
+        let spatial_gen = SpatialGenerator::try_new(SpatialConfig {
+            params: DistributionParams::Hotspot {
+                components: components.clone(),
+                background: Some(Box::new(HotspotBackground {
+                    weight: 1000.0,
+                    dist_type: DistributionType::Uniform,
+                    params: DistributionParams::None,
+                })),
+            },
+            ..config(None)
+        })
+        .unwrap();
+        let pickups: Vec<Point> = TripGenerator::new_with_distributions_and_text_pool(
+            0.001,
+            1,
+            1,
+            Distributions::static_default(),
+            TextPool::get_or_init_default(),
+            crate::kde::default_distance_kde(),
+            spatial_gen,
+        )
+        .iter()
+        .map(|trip| trip.t_pickuploc)
+        .collect();
+        for component in &components {
+            assert!(
+                pickups.iter().any(|p| km_to(p, component) < 100.0),
+                "no trip around {component:?}"
+            );
+        }
+
+        // points near the antimeridian wrap to the other side
+        let fiji = HotspotComponent {
+            lon: 179.9,
+            lat: -17.0,
+            sigma_km: 50.0,
+            weight: 1.0,
+        };
+        let generator = SpatialGenerator::try_new(SpatialConfig {
+            params: DistributionParams::Hotspot {
+                components: vec![fiji],
+                background: None,
+            },
+            ..config(None)
+        })
+        .unwrap();
+        let lons: Vec<f64> = (0..200)
+            .map(|i| Point::try_from(generator.generate(i, &region)).unwrap().x())
+            .collect();
+        assert!(lons.iter().all(|lon| (-180.0..180.0).contains(lon)));
+        assert!(lons.iter().any(|lon| *lon > 179.9));
+        assert!(lons.iter().any(|lon| *lon < -179.9));
+    }
+
+    #[test]
+    fn test_custom_spatial_distribution() {
+        use crate::spatial::SpatialDistribution;
+        use geo::Rect;
+
+        /// Points or squares on a 100 x 100 grid of 0.1 degrees, ignoring the
+        /// continent of the record
+        #[derive(Debug)]
+        struct GridDistribution {
+            squares: bool,
+        }
+
+        impl GridDistribution {
+            fn center(index: u64) -> Point {
+                let cell = index % 10_000;
+                Point::new((cell % 100) as f64 / 10.0, (cell / 100) as f64 / 10.0)
+            }
+        }
+
+        impl SpatialDistribution for GridDistribution {
+            fn sample(&self, index: u64, _affine: &[f64; 6]) -> Geometry {
+                let center = Self::center(index);
+                if self.squares {
+                    let half = Point::new(0.001, 0.001);
+                    Rect::new(center - half, center + half).to_polygon().into()
+                } else {
+                    center.into()
+                }
+            }
+        }
+
+        /// Fails to load its data
+        #[derive(Debug)]
+        struct MissingDistribution;
+
+        impl SpatialDistribution for MissingDistribution {
+            fn prepare(&self) -> crate::error::Result<()> {
+                Err(SpatialBenchError::InvalidConfig("no data".to_string()))
+            }
+
+            fn sample(&self, _index: u64, _affine: &[f64; 6]) -> Geometry {
+                unreachable!()
+            }
+        }
+
+        let trips = TripGenerator::new_with_spatial_distribution(
+            0.01,
+            1,
+            1,
+            Box::new(GridDistribution { squares: false }),
+        )
+        .unwrap();
+        for trip in trips.iter().take(1000) {
+            assert_eq!(
+                trip.t_pickuploc,
+                GridDistribution::center(trip.t_tripkey as u64)
+            );
+        }
+
+        let buildings = BuildingGenerator::new_with_spatial_distribution(
+            1.0,
+            1,
+            1,
+            Box::new(GridDistribution { squares: true }),
+        )
+        .unwrap();
+        let mut count = 0;
+        for building in buildings.iter() {
+            let bbox = building.b_boundary.bounding_rect().unwrap();
+            let center = GridDistribution::center(building.b_buildingkey as u64);
+            assert!((bbox.center().x - center.x()).abs() < 1e-9, "{bbox:?}");
+            assert!((bbox.center().y - center.y()).abs() < 1e-9, "{bbox:?}");
+            count += 1;
+        }
+        assert_eq!(count, BuildingGenerator::calculate_row_count(1.0, 1, 1));
+
+        // clones of a generator sample the same distribution
+        let default = SpatialDefaults::building_default();
+        let custom = SpatialDefaults::building_default()
+            .with_distribution(Box::new(GridDistribution { squares: true }))
+            .unwrap();
+        let affine = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        assert_ne!(default.generate(7, &affine), custom.generate(7, &affine));
+        assert_eq!(
+            default.generate(7, &affine),
+            default.clone().generate(7, &affine)
+        );
+
+        // errors preparing the distribution are reported by the constructors
+        assert!(TripGenerator::new_with_spatial_distribution(
+            0.01,
+            1,
+            1,
+            Box::new(MissingDistribution)
+        )
+        .is_err());
+        assert!(BuildingGenerator::new_with_spatial_distribution(
+            1.0,
+            1,
+            1,
+            Box::new(MissingDistribution)
+        )
+        .is_err());
+
+        // geometries of the wrong type end the fallible iterators with an error
+        let trips = TripGenerator::new_with_spatial_distribution(
+            0.01,
+            1,
+            1,
+            Box::new(GridDistribution { squares: true }),
+        )
+        .unwrap();
+        let mut rows = trips.try_iter();
+        assert!(matches!(
+            rows.next(),
+            Some(Err(SpatialBenchError::InvalidConfig(_)))
+        ));
+        assert!(rows.next().is_none());
+        let buildings = BuildingGenerator::new_with_spatial_distribution(
+            1.0,
+            1,
+            1,
+            Box::new(GridDistribution { squares: false }),
+        )
+        .unwrap();
+        assert!(buildings.try_iter().any(|building| building.is_err()));
+        assert!(buildings
+            .with_non_overlapping()
+            .try_iter()
+            .next()
+            .unwrap()
+            .is_err());
+
+        // locations that are not points, or not in the nation, end the
+        // fallible customer and driver iterators with an error
+        let points = SpatialDefaults::customer_default();
+        let customers = CustomerGenerator::new(0.01, 1, 1)
+            .with_home_locations(
+                points
+                    .clone()
+                    .with_distribution(Box::new(GridDistribution { squares: true }))
+                    .unwrap(),
+            )
+            .unwrap();
+        let mut rows = customers.try_iter();
+        assert!(matches!(
+            rows.next(),
+            Some(Err(SpatialBenchError::InvalidConfig(_)))
+        ));
+        assert!(rows.next().is_none());
+        let drivers = DriverGenerator::new(0.01, 1, 1)
+            .with_base_locations(
+                points
+                    .with_distribution(Box::new(GridDistribution { squares: false }))
+                    .unwrap(),
+            )
+            .unwrap();
+        assert!(drivers.try_iter().next().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_metric_sizes() {
+        use crate::spatial::utils::METERS_PER_DEGREE;
+
+        let config = |geom_type| SpatialConfig {
+            dist_type: DistributionType::Thomas,
+            geom_type,
+            dim: 2,
+            seed: 23,
+            width: 30.0,
+            height: 10.0,
+            maxseg: 6,
+            polysize: 20.0,
+            polygon_style: PolygonStyle::Star,
+            params: DistributionParams::Thomas {
+                parents: 1,
+                mean_offspring: 1.0,
+                sigma: 1000.0,
+                pareto_alpha: 1.0,
+                pareto_xm: 1.0,
+            },
+            validity: Validity::Raw,
+            units: SizeUnit::Meters,
+        };
+        // a 10 degree square on the equator and a 100 by 10 degree band at 60N
+        let regions = [
+            [10.0, 0.0, 0.0, 0.0, 10.0, -5.0],
+            [100.0, 0.0, -50.0, 0.0, 10.0, 60.0],
+        ];
+
+        for (geom_type, max_width, max_height) in
+            [(GeomType::Box, 30.0, 10.0), (GeomType::Polygon, 40.0, 40.0)]
+        {
+            let generator = SpatialGenerator::try_new(config(geom_type)).unwrap();
+            let mut mean_widths = vec![];
+            for region in &regions {
+                let bboxes: Vec<_> = (0..2000)
+                    .map(|i| generator.generate(i, region).bounding_rect().unwrap())
+                    .collect();
+                let lat = bboxes.iter().map(|b| b.center().y).sum::<f64>() / 2000.0;
+                let lon = bboxes.iter().map(|b| b.center().x).sum::<f64>() / 2000.0;
+                let to_meters = |dlon: f64, dlat: f64| {
+                    (
+                        dlon * METERS_PER_DEGREE * lat.to_radians().cos(),
+                        dlat * METERS_PER_DEGREE,
+                    )
+                };
+
+                // sizes are in meters at the latitude of the geometry
+                let mut width_sum = 0.0;
+                for bbox in &bboxes {
+                    let (width, height) = to_meters(bbox.width(), bbox.height());
+                    assert!(width <= max_width * 1.01, "{width} m wide");
+                    assert!(height <= max_height * 1.01, "{height} m high");
+                    width_sum += width;
+                }
+                mean_widths.push(width_sum / 2000.0);
+
+                // the cluster spread is the same in both directions and regions
+                let (var_x, var_y) = bboxes.iter().fold((0.0, 0.0), |(vx, vy), b| {
+                    let (dx, dy) = to_meters(b.center().x - lon, b.center().y - lat);
+                    (vx + dx * dx / 2000.0, vy + dy * dy / 2000.0)
+                });
+                for sd in [var_x.sqrt(), var_y.sqrt()] {
+                    assert!((900.0..1100.0).contains(&sd), "{sd} m spread in {region:?}");
+                }
+            }
+            let ratio = mean_widths[0] / mean_widths[1];
+            assert!((0.9..1.1).contains(&ratio), "{mean_widths:?}");
+        }
+
+        let parcel = SpatialConfig {
+            dist_type: DistributionType::Parcel,
+            params: DistributionParams::Parcel {
+                srange: 0.1,
+                dither: 0.0,
+            },
+            ..config(GeomType::Box)
+        };
+        assert!(SpatialGenerator::try_new(parcel).is_err());
+        assert_eq!("meters".parse::<SizeUnit>(), Ok(SizeUnit::Meters));
+        assert!("feet".parse::<SizeUnit>().is_err());
+    }
+
+    #[test]
+    fn test_shared_city_model() {
+        use crate::spatial::CityModel;
+
+        let model = CityModel {
+            seed: 7,
+            cities: 50,
+            sub_mean: 3.0,
+            sub_sd: 1.0,
+            sub_min: 1,
+            sub_max: 5,
+            sigma_city: 0.05,
+            pareto_alpha_city: 1.2,
+            pareto_xm_city: 1.0,
+            pareto_alpha_sub: 1.0,
+            pareto_xm_sub: 1.0,
+        };
+        let config = |seed, geom_type, model| SpatialConfig {
+            dist_type: DistributionType::CityModel,
+            geom_type,
+            dim: 2,
+            seed,
+            width: 0.0,
+            height: 0.0,
+            maxseg: 5,
+            polysize: 0.0001,
+            polygon_style: PolygonStyle::Star,
+            params: DistributionParams::CityModel {
+                model,
+                sigma_sub: 0.002,
+            },
+            validity: Validity::Raw,
+            units: SizeUnit::Unit,
+        };
+        let region = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let trips = SpatialGenerator::try_new(config(56789, GeomType::Point, model)).unwrap();
+        let buildings = SpatialGenerator::try_new(config(12345, GeomType::Polygon, model)).unwrap();
+        let other_model = CityModel { seed: 8, ..model };
+        let others =
+            SpatialGenerator::try_new(config(12345, GeomType::Polygon, other_model)).unwrap();
+
+        // number of trips within 0.01 of the center of a building
+        let pickups: Vec<Point> = (0..2000)
+            .map(|i| trips.generate(i, &region).try_into().unwrap())
+            .collect();
+        let near = |generator: &SpatialGenerator| {
+            let centers: Vec<_> = (0..2000)
+                .map(|i| {
+                    generator
+                        .generate(i, &region)
+                        .bounding_rect()
+                        .unwrap()
+                        .center()
+                })
+                .collect();
+            pickups
+                .iter()
+                .filter(|p| {
+                    centers
+                        .iter()
+                        .any(|c| (c.x - p.x()).hypot(c.y - p.y()) < 0.01)
+                })
+                .count()
+        };
+        let (shared, independent) = (near(&buildings), near(&others));
+        assert!(shared > 1800, "{shared} trips near buildings");
+        assert!(
+            independent < shared / 2,
+            "{independent} trips near other buildings"
+        );
+
+        // a hierarchical Thomas distribution is a city model of its own
+        let hierarchical = SpatialConfig {
+            dist_type: DistributionType::HierarchicalThomas,
+            params: DistributionParams::HierarchicalThomas {
+                cities: 50,
+                sub_mean: 3.0,
+                sub_sd: 1.0,
+                sub_min: 1,
+                sub_max: 5,
+                sigma_city: 0.05,
+                sigma_sub: 0.002,
+                pareto_alpha_city: 1.2,
+                pareto_xm_city: 1.0,
+                pareto_alpha_sub: 1.0,
+                pareto_xm_sub: 1.0,
+            },
+            ..config(7, GeomType::Point, model)
+        };
+        let hierarchical = SpatialGenerator::try_new(hierarchical).unwrap();
+        let own_model = SpatialGenerator::try_new(config(7, GeomType::Point, model)).unwrap();
+        for i in 0..100 {
+            assert_eq!(
+                hierarchical.generate(i, &region),
+                own_model.generate(i, &region)
+            );
+        }
+
+        let no_cities = CityModel { cities: 0, ..model };
+        assert!(SpatialGenerator::try_new(config(7, GeomType::Point, no_cities)).is_err());
+    }
+
+    #[test]
+    fn test_density_grid_distribution() {
+        let dir = std::env::temp_dir();
+        let asc = dir.join(format!("spatialbench-density-{}.asc", std::process::id()));
+        let csv = dir.join(format!("spatialbench-density-{}.csv", std::process::id()));
+        std::fs::write(
+            &asc,
+            "ncols 2\nnrows 2\nxllcorner 10\nyllcorner 40\ncellsize 0.5\nNODATA_value -9999\n0 3\n-9999 1\n",
+        )
+        .unwrap();
+        std::fs::write(&csv, "0,3\n0,1\n").unwrap();
+        let config = |path: &std::path::Path, bbox| SpatialConfig {
+            dist_type: DistributionType::DensityGrid,
+            geom_type: GeomType::Point,
+            dim: 2,
+            seed: 7,
+            width: 0.0,
+            height: 0.0,
+            maxseg: 0,
+            polysize: 0.0,
+            polygon_style: PolygonStyle::Star,
+            params: DistributionParams::DensityGrid {
+                path: path.to_str().unwrap().to_string(),
+                bbox,
+            },
+            validity: Validity::Raw,
+            units: SizeUnit::Unit,
+        };
+
+        // only the two eastern cells have a positive weight
+        let generator = SpatialGenerator::try_new(config(&asc, None)).unwrap();
+        let points: Vec<Point> = (0..400)
+            .map(|i| generator.generate(i, &[0.0; 6]).try_into().unwrap())
+            .collect();
+        assert!(points
+            .iter()
+            .all(|p| (10.5..=11.0).contains(&p.x()) && (40.0..=41.0).contains(&p.y())));
+        let north = points.iter().filter(|p| p.y() > 40.5).count();
+        assert!(
+            (250..350).contains(&north),
+            "{north} points in the north cell"
+        );
+
+        // a CSV raster with the same extent places the same points
+        let from_csv =
+            SpatialGenerator::try_new(config(&csv, Some([10.0, 40.0, 11.0, 41.0]))).unwrap();
+        assert_eq!(
+            from_csv.generate(5, &[0.0; 6]),
+            generator.generate(5, &[0.0; 6])
+        );
+        // but has no extent of its own
+        assert!(SpatialGenerator::try_new(config(&csv, None)).is_err());
+
+        std::fs::remove_file(&asc).unwrap();
+        std::fs::remove_file(&csv).unwrap();
+        assert!(SpatialGenerator::try_new(config(&asc, None)).is_err());
+
+        // a generator rebuilt for another validity reports the missing raster, and
+        // one left unchanged keeps its prepared raster
+        assert!(generator.clone().with_validity(Validity::Strict).is_err());
+        let unchanged = generator.clone().with_validity(Validity::Raw).unwrap();
+        assert_eq!(
+            unchanged.generate(5, &[0.0; 6]),
+            generator.generate(5, &[0.0; 6])
+        );
+        // the raster is loaded when the generator is built, not when sampled
+        let missing = std::panic::catch_unwind(|| SpatialGenerator::new(config(&asc, None)));
+        assert!(missing.is_err());
+
+        // the raster size is a whole number
+        std::fs::write(
+            &asc,
+            "ncols 2.5\nnrows 2\nxllcorner 10\nyllcorner 40\ncellsize 0.5\n0 3 0\n0 1 0\n",
+        )
+        .unwrap();
+        assert!(SpatialGenerator::try_new(config(&asc, None)).is_err());
+        std::fs::remove_file(&asc).unwrap();
+
+        // GeoTIFF rasters of the same weights and extent place the same points
+        let tif = dir.join(format!("spatialbench-density-{}.tif", std::process::id()));
+        let f32_rows = |rows: &[[f32; 2]]| -> Vec<Vec<u8>> {
+            rows.iter()
+                .map(|row| row.iter().flat_map(|v| v.to_le_bytes()).collect())
+                .collect()
+        };
+        let weights = [[0.0, 3.0], [-9999.0, 1.0]];
+        let nodata = (42113, 2, b"-9999\0".to_vec());
+        let scale = (33550, 12, [0.5f64, 0.5, 0.0].map(f64::to_le_bytes).concat());
+        let tiepoint = |lon: f64, lat: f64| {
+            let point = [0.0, 0.0, 0.0, lon, lat, 0.0];
+            (33922, 12, point.map(f64::to_le_bytes).concat())
+        };
+
+        // uncompressed float strips of one row
+        let strips = f32_rows(&weights);
+        std::fs::write(
+            &tif,
+            geotiff(
+                (2, 2),
+                None,
+                &strips,
+                3,
+                1,
+                vec![nodata.clone(), scale.clone(), tiepoint(10.0, 41.0)],
+            ),
+        )
+        .unwrap();
+        let from_tif = SpatialGenerator::try_new(config(&tif, None)).unwrap();
+        assert_eq!(
+            from_tif.generate(5, &[0.0; 6]),
+            generator.generate(5, &[0.0; 6])
+        );
+
+        // an LZW compressed tile of bytes, padded to 16 x 16, with horizontal
+        // differencing and an affine transformation
+        let mut tile = vec![0u8; 256];
+        tile[1] = 3;
+        tile[17] = 1;
+        for row in tile.chunks_exact_mut(16) {
+            for i in (1..16).rev() {
+                row[i] = row[i].wrapping_sub(row[i - 1]);
+            }
+        }
+        let transformation = [0.5f64, 0.0, 0.0, 10.0, 0.0, -0.5, 0.0, 41.0]
+            .into_iter()
+            .chain([0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0])
+            .flat_map(f64::to_le_bytes)
+            .collect();
+        std::fs::write(
+            &tif,
+            geotiff(
+                (2, 2),
+                Some((16, 16)),
+                &[lzw(&tile)],
+                1,
+                5,
+                vec![
+                    (258, 3, 8u16.to_le_bytes().to_vec()),
+                    (317, 3, 2u16.to_le_bytes().to_vec()),
+                    (34264, 12, transformation),
+                ],
+            ),
+        )
+        .unwrap();
+        let from_tif = SpatialGenerator::try_new(config(&tif, None)).unwrap();
+        assert_eq!(
+            from_tif.generate(5, &[0.0; 6]),
+            generator.generate(5, &[0.0; 6])
+        );
+
+        // a Deflate compressed strip of floats with floating point differencing,
+        // tied at the center of the first cell
+        let mut strip = Vec::new();
+        for row in f32_rows(&weights) {
+            // the bytes of each sample from the most significant, one plane at a time
+            let planes: Vec<u8> = (0..4)
+                .flat_map(|b| row.chunks_exact(4).map(move |v| v[3 - b]))
+                .collect();
+            strip.extend(
+                (0..planes.len())
+                    .map(|i| planes[i].wrapping_sub(if i == 0 { 0 } else { planes[i - 1] })),
+            );
+        }
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &strip).unwrap();
+        let pixel_is_point = [1u16, 1, 0, 1, 1025, 0, 1, 2]
+            .map(u16::to_le_bytes)
+            .concat();
+        std::fs::write(
+            &tif,
+            geotiff(
+                (2, 2),
+                None,
+                &[encoder.finish().unwrap()],
+                3,
+                8,
+                vec![
+                    (317, 3, 3u16.to_le_bytes().to_vec()),
+                    nodata,
+                    scale.clone(),
+                    tiepoint(10.25, 40.75),
+                    (34735, 3, pixel_is_point),
+                ],
+            ),
+        )
+        .unwrap();
+        let from_tif = SpatialGenerator::try_new(config(&tif, None)).unwrap();
+        assert_eq!(
+            from_tif.generate(5, &[0.0; 6]),
+            generator.generate(5, &[0.0; 6])
+        );
+
+        // a raster in projected coordinates needs a bbox
+        let projected = [1u16, 1, 0, 1, 1024, 0, 1, 1]
+            .map(u16::to_le_bytes)
+            .concat();
+        std::fs::write(
+            &tif,
+            geotiff(
+                (2, 2),
+                None,
+                &strips,
+                3,
+                1,
+                vec![scale, tiepoint(10.0, 41.0), (34735, 3, projected)],
+            ),
+        )
+        .unwrap();
+        assert!(SpatialGenerator::try_new(config(&tif, None)).is_err());
+        assert!(SpatialGenerator::try_new(config(&tif, Some([10.0, 40.0, 11.0, 41.0]))).is_ok());
+        std::fs::remove_file(&tif).unwrap();
+    }
+
+    /// Returns a little-endian, single band GeoTIFF of `size` (columns, rows) in
+    /// strips, or in tiles of the given size, of samples of a `SampleFormat`
+    /// (1 for bytes, 3 for floats) with the extra `(tag, type, value)` entries
+    fn geotiff(
+        size: (u32, u32),
+        tile: Option<(u32, u32)>,
+        blocks: &[Vec<u8>],
+        sample_format: u16,
+        compression: u16,
+        extra: Vec<(u16, u16, Vec<u8>)>,
+    ) -> Vec<u8> {
+        let mut bytes = b"II\x2a\0\0\0\0\0".to_vec();
+        let mut offsets = Vec::new();
+        for block in blocks {
+            offsets.extend((bytes.len() as u32).to_le_bytes());
+            bytes.extend(block);
+        }
+        let counts = blocks
+            .iter()
+            .flat_map(|b| (b.len() as u32).to_le_bytes())
+            .collect();
+        let short = |v: u32| (3, (v as u16).to_le_bytes().to_vec());
+        let long = |v: u32| (4, v.to_le_bytes().to_vec());
+        let mut entries = vec![
+            (256, long(size.0)),
+            (257, long(size.1)),
+            (258, short(32)),
+            (259, short(compression as u32)),
+            (339, short(sample_format as u32)),
+        ];
+        match tile {
+            Some((width, height)) => entries.extend([
+                (322, long(width)),
+                (323, long(height)),
+                (324, (4, offsets)),
+                (325, (4, counts)),
+            ]),
+            None => entries.extend([
+                (273, (4, offsets)),
+                (278, long(size.1 / blocks.len() as u32)),
+                (279, (4, counts)),
+            ]),
+        }
+        for (tag, field_type, value) in extra {
+            entries.retain(|(t, _)| *t != tag);
+            entries.push((tag, (field_type, value)));
+        }
+        entries.sort_by_key(|(tag, _)| *tag);
+
+        // values that do not fit in an entry go before the directory
+        let mut directory = Vec::new();
+        for (tag, (field_type, mut value)) in entries {
+            let size = match field_type {
+                2 => 1,
+                3 => 2,
+                12 => 8,
+                _ => 4,
+            };
+            directory.extend(tag.to_le_bytes());
+            directory.extend(field_type.to_le_bytes());
+            directory.extend((value.len() as u32 / size).to_le_bytes());
+            if value.len() > 4 {
+                directory.extend((bytes.len() as u32).to_le_bytes());
+                bytes.append(&mut value);
+            } else {
+                value.resize(4, 0);
+                directory.extend(value);
+            }
+        }
+        let offset = bytes.len() as u32;
+        bytes[4..8].copy_from_slice(&offset.to_le_bytes());
+        bytes.extend((directory.len() as u16 / 12).to_le_bytes());
+        bytes.extend(directory);
+        bytes
+    }
+
+    /// Compresses with TIFF LZW, without adding strings: each byte is a 9 bit
+    /// code, and the table is cleared before it needs wider codes
+    fn lzw(data: &[u8]) -> Vec<u8> {
+        let mut codes = Vec::new();
+        for chunk in data.chunks(200) {
+            codes.push(256);
+            codes.extend(chunk.iter().map(|&b| b as u16));
+        }
+        codes.push(257);
+        let mut bytes = vec![0u8; (codes.len() * 9).div_ceil(8)];
+        for (i, code) in codes.into_iter().enumerate() {
+            for bit in 0..9 {
+                if code >> (8 - bit) & 1 == 1 {
+                    let b = i * 9 + bit;
+                    bytes[b / 8] |= 0x80 >> (b % 8);
+                }
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_zone_generation() {
+        // Create a generator with a small scale factor
+        let generator = ZoneGenerator::new(0.001, 1, 1);
+        let zones: Vec<_> = generator.into_iter().collect();
+
+        assert_eq!(zones.len(), 158);
+
+        // Check first zone
+        let first = &zones[0];
+        assert_eq!(first.z_zonekey, 1);
+        // The first zone is now a county due to the is_land filter and county being in base subtypes
+        assert_eq!(first.z_subtype, "county");
+        // Verify the string format matches the expected pattern (but don't check exact content since it's dynamic)
+        let expected_pattern = format!(
+            "{}|{}|{}|{}|{}|{}|{}|{}|{:?}|",
+            first.z_zonekey,
+            first.z_gersid,
+            first.z_country,
+            first.z_region,
+            first.z_name,
+            first.z_subtype,
+            first.z_level,
+            OptionalKey(first.z_parentkey),
+            first.z_boundary
+        );
+        assert_eq!(first.to_string(), expected_pattern);
+    }
+
+    #[test]
+    fn test_zone_generation_from_local_ndjson() {
+        let features = [
+            r#"{"type":"Feature","id":"a","properties":{"id":"a","country":"US","region":"US-CA","names":{"primary":"Alameda County"},"subtype":"county","is_land":true},"geometry":{"type":"Polygon","coordinates":[[[0,0],[1,0],[1,1],[0,1],[0,0]]]}}"#,
+            r#"{"type":"Feature","id":"b","properties":{"id":"b","country":"US","region":"US-CA","names":{"primary":"Bay"},"subtype":"county","is_land":false},"geometry":{"type":"Polygon","coordinates":[[[1,1],[2,1],[2,2],[1,1]]]}}"#,
+            r#"{"type":"Feature","id":"c","properties":{"id":"c","country":"US","region":null,"names":{"primary":"United States"},"subtype":"country","is_land":true},"geometry":{"type":"Polygon","coordinates":[[[0,0],[5,0],[5,5],[0,0]]]}}"#,
+            r#"{"type":"Feature","id":"d","properties":{"id":"d","country":"FR","region":"FR-IDF","names":{"primary":"Le Marais"},"subtype":"microhood","is_land":true},"geometry":{"type":"Polygon","coordinates":[[[2,48],[3,48],[3,49],[2,48]]]}}"#,
+        ];
+        let path =
+            std::env::temp_dir().join(format!("spatialbench-zones-{}.ndjson", std::process::id()));
+        std::fs::write(&path, features.join("\n")).unwrap();
+
+        let source = ZoneSource::NdJson(path.to_str().unwrap().to_string());
+        let zones: Vec<_> = ZoneGenerator::new_with_source(1.0, 1, 1, source)
+            .into_iter()
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+
+        // the water zone and the country (not included at this scale factor) are skipped
+        assert_eq!(zones.len(), 2);
+        assert_eq!(
+            zones[0].to_string(),
+            "1|a|US|US-CA|Alameda County|county|3||POLYGON((0.0 0.0,1.0 0.0,1.0 1.0,0.0 1.0,0.0 0.0))||"
+        );
+        assert_eq!(
+            zones[1].to_string(),
+            "2|d|FR|FR-IDF|Le Marais|microhood|8||POLYGON((2.0 48.0,3.0 48.0,3.0 49.0,2.0 48.0))||"
+        );
+    }
+
+    #[test]
+    fn test_zone_source_error() {
+        let path = std::env::temp_dir().join(format!(
+            "spatialbench-missing-{}.ndjson",
+            std::process::id()
+        ));
+        let source = ZoneSource::NdJson(path.to_str().unwrap().to_string());
+        let generator = ZoneGenerator::new_with_source(1.0, 1, 1, source).with_cache_dir(None);
+
+        assert!(generator.try_calculate_row_count().is_err());
+        let mut zones = generator.try_iter();
+        assert!(matches!(zones.next(), Some(Err(_))));
+        assert!(zones.next().is_none());
+    }
+
+    /// Returns the zones of all parts when generated in `part_count` parts
+    fn zones_in_parts(scale_factor: f64, part_count: i32, source: &ZoneSource) -> Vec<Zone> {
+        (1..=part_count)
+            .flat_map(|part| {
+                ZoneGenerator::new_with_source(scale_factor, part, part_count, source.clone())
+            })
+            .collect()
+    }
+
+    /// Writes 300 point features of four subtypes, in no particular order, to a
+    /// newline-delimited GeoJSON file in the temporary directory
+    fn write_ndjson_zones(name: &str) -> std::path::PathBuf {
+        let subtypes = ["microhood", "county", "macrohood", "country"];
+        let features: Vec<String> = (0..300u64)
+            .map(|i| {
+                let id = format!("{:016x}", spider_seed_for_index(i, 7));
+                let subtype = subtypes[(i % 4) as usize];
+                format!(
+                    r#"{{"type":"Feature","properties":{{"id":"{id}","country":"US","region":"US-CA","names":{{"primary":"Zone {i}"}},"subtype":"{subtype}","is_land":true}},"geometry":{{"type":"Point","coordinates":[{i},0]}}}}"#
+                )
+            })
+            .collect();
+        let path = std::env::temp_dir().join(format!(
+            "spatialbench-{}-{}.ndjson",
+            name,
+            std::process::id()
+        ));
+        std::fs::write(&path, features.join("\n")).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_zone_order_and_parts_from_local_ndjson() {
+        let path = write_ndjson_zones("zone-parts");
+        let source = ZoneSource::NdJson(path.to_str().unwrap().to_string());
+
+        // the country features are not included at this scale factor
+        let zones = zones_in_parts(1.0, 1, &source);
+        assert_eq!(zones.len(), 225);
+
+        // ordered by level, then GERS id
+        let mut sorted = zones.clone();
+        sorted.sort_by(|a, b| {
+            (subtype_rank(&a.z_subtype), &a.z_gersid)
+                .cmp(&(subtype_rank(&b.z_subtype), &b.z_gersid))
+        });
+        assert_eq!(zones, sorted);
+        assert_eq!(zones[0].z_subtype, "county");
+
+        for part_count in [2, 3, 7] {
+            assert_eq!(zones_in_parts(1.0, part_count, &source), zones);
+        }
+
+        // smaller scale factors keep the first zones, rounding up
+        let scaled = zones_in_parts(0.1, 3, &source);
+        assert_eq!(scaled, zones[..23]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_zone_cache_from_local_ndjson() {
+        let path = write_ndjson_zones("zone-cache");
+        let source = ZoneSource::NdJson(path.to_str().unwrap().to_string());
+        let cache_dir =
+            std::env::temp_dir().join(format!("spatialbench-zone-cache-{}", std::process::id()));
+        let generate = |scale_factor, part, part_count, cache_dir: Option<std::path::PathBuf>| {
+            ZoneGenerator::new_with_source(scale_factor, part, part_count, source.clone())
+                .with_cache_dir(cache_dir)
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+
+        // scale factors with the same subtypes read slices of the same cache file,
+        // and parts read without the cache keep only their own rows
+        for scale_factor in [0.1, 1.0] {
+            let expected = generate(scale_factor, 1, 1, None);
+            assert!(!expected.is_empty());
+            for part_count in [1, 3] {
+                for cache_dir in [Some(cache_dir.clone()), None] {
+                    let zones: Vec<_> = (1..=part_count)
+                        .flat_map(|part| {
+                            generate(scale_factor, part, part_count, cache_dir.clone())
+                        })
+                        .collect();
+                    assert_eq!(zones, expected);
+                }
+            }
+        }
+        assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_zone_cache_key_of_directories() {
+        let dir =
+            std::env::temp_dir().join(format!("spatialbench-zone-dir-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("country=FR")).unwrap();
+        let file = dir.join("country=FR").join("part-0.parquet");
+        std::fs::write(&file, b"zones").unwrap();
+
+        let cache_dir = std::path::Path::new("/tmp/cache");
+        let source = ZoneSource::Parquet(dir.to_str().unwrap().to_string());
+        let before = cache::cache_path(cache_dir, &source, &["country"]).unwrap();
+
+        // editing a file in the directory invalidates the cache
+        std::fs::write(&file, b"other zones").unwrap();
+        let after = cache::cache_path(cache_dir, &source, &["country"]).unwrap();
+        assert_ne!(before, after);
+
+        // the files of a glob are unknown, so it is not cached
+        let glob = ZoneSource::Parquet(format!("{}/*/*.parquet", dir.display()));
+        assert_eq!(cache::cache_path(cache_dir, &glob, &["country"]), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_zone_config_subtype_tiers() {
+        let path = write_ndjson_zones("zone-tiers");
+        let config = ZoneConfig {
+            source: ZoneSource::NdJson(path.to_str().unwrap().to_string()),
+            subtype_tiers: vec![
+                SubtypeTier::new(0.0, &["country"]),
+                SubtypeTier::new(10.0, &["county"]),
+            ],
+            cache_dir: None,
+            ..ZoneConfig::default()
+        };
+        config.validate().unwrap();
+        let generate = |scale_factor| {
+            ZoneGenerator::new_with_config(scale_factor, 1, 1, config.clone())
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+
+        let zones = generate(1.0);
+        assert_eq!(zones.len(), 75);
+        assert!(zones.iter().all(|z| z.z_subtype == "country"));
+
+        let zones = generate(10.0);
+        assert_eq!(zones.len(), 150);
+        assert_eq!(zones[0].z_subtype, "country");
+        assert_eq!(zones[149].z_subtype, "county");
+        std::fs::remove_file(&path).unwrap();
+
+        let config = ZoneConfig {
+            subtype_tiers: vec![SubtypeTier::new(0.0, &["postcode"])],
+            ..ZoneConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_synthetic_zone_parts() {
+        let source = ZoneSource::Synthetic { seed: 42 };
+        let zones = zones_in_parts(0.01, 1, &source);
+        assert_eq!(zones.len(), 1571);
+        for part_count in [2, 3, 7, 50, 2000] {
+            assert_eq!(
+                zones_in_parts(0.01, part_count, &source),
+                zones,
+                "part_count={part_count}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_zone_batches() {
+        let source = ZoneSource::Synthetic { seed: 42 };
+        let generator = ZoneGenerator::new_with_source(0.01, 1, 1, source);
+        let zones: Vec<_> = generator.iter().collect();
+        assert!(zones.len() as i64 > ZONE_BATCH_SIZE);
+        assert!(zones
+            .iter()
+            .enumerate()
+            .all(|(i, zone)| zone.z_zonekey == i as i64 + 1));
+
+        let decoded: Vec<_> = generator
+            .iter_wkb()
+            .map(|zone| zone.into_zone().unwrap())
+            .collect();
+        assert_eq!(decoded, zones);
+    }
+
+    #[test]
+    fn test_synthetic_zone_generation() {
+        let source = ZoneSource::Synthetic { seed: 42 };
+        let generator = ZoneGenerator::new_with_source(0.001, 1, 1, source.clone());
+        let zones: Vec<_> = generator.into_iter().collect();
+
+        // same cardinality as the Overture based table
+        assert_eq!(zones.len(), 158);
+        assert_eq!(
+            zones.iter().map(|z| z.z_zonekey).collect::<Vec<_>>(),
+            (1..=158).collect::<Vec<_>>()
+        );
+
+        // coarsest levels first
+        let subtypes: Vec<_> = zones.iter().map(|z| z.z_subtype.as_str()).collect();
+        let mut sorted = subtypes.clone();
+        sorted.sort_by_key(|s| subtype_rank(s));
+        assert_eq!(subtypes, sorted);
+        assert_eq!(subtypes[0], "county");
+
+        let first = &zones[0];
+        assert_eq!(first.z_country.len(), 2);
+        assert!(first.z_region.starts_with(&format!("{}-", first.z_country)));
+        assert!(first.z_name.ends_with(" County"));
+
+        // deterministic for a given seed
+        let again: Vec<_> = ZoneGenerator::new_with_source(0.001, 1, 1, source.clone())
+            .into_iter()
+            .collect();
+        assert_eq!(zones, again);
+
+        // but different for another one
+        let other: Vec<_> =
+            ZoneGenerator::new_with_source(0.001, 1, 1, ZoneSource::Synthetic { seed: 43 })
+                .into_iter()
+                .collect();
+        assert_ne!(zones[0].z_boundary, other[0].z_boundary);
+
+        // at tiny scale factors, every level still has a zone per continent
+        let tiny: Vec<_> = ZoneGenerator::new_with_source(0.00001, 1, 1, source)
+            .into_iter()
+            .collect();
+        let mut per_level: HashMap<&str, usize> = HashMap::new();
+        for zone in &tiny {
+            *per_level.entry(zone.z_subtype.as_str()).or_default() += 1;
+        }
+        assert!(per_level.values().all(|&count| count >= 8), "{per_level:?}");
+    }
+
+    #[test]
+    fn test_synthetic_zone_levels_tile_the_continents() {
+        use crate::zone::synthetic::ZoneTessellation;
+        use geo::{Area, Contains, InteriorPoint};
+
+        let tessellation = ZoneTessellation::get(7, 1000);
+        let continent_area: f64 = tessellation
+            .level(8)
+            .iter()
+            .map(|&id| {
+                tessellation
+                    .zone(0, "country", id)
+                    .z_boundary
+                    .unsigned_area()
+            })
+            .sum();
+
+        let coarse: Vec<_> = tessellation
+            .level(60)
+            .iter()
+            .map(|&id| tessellation.zone(0, "county", id).z_boundary)
+            .collect();
+        let fine: Vec<_> = tessellation
+            .level(1000)
+            .iter()
+            .map(|&id| tessellation.zone(0, "microhood", id).z_boundary)
+            .collect();
+        assert_eq!(coarse.len(), 60);
+        assert_eq!(fine.len(), 1000);
+        // levels smaller than the continents still cover them
+        assert_eq!(tessellation.level(3).len(), 8);
+
+        // each level covers the continents exactly once
+        for level in [&coarse, &fine] {
+            let area: f64 = level.iter().map(|g| g.unsigned_area()).sum();
+            assert!((area - continent_area).abs() < continent_area * 1e-9);
+        }
+
+        // and finer zones are nested in exactly one coarser zone
+        for zone in &fine {
+            let point = zone.interior_point().unwrap();
+            let parents = coarse.iter().filter(|c| c.contains(&point)).count();
+            assert_eq!(parents, 1);
+        }
+    }
+
+    #[test]
+    fn test_synthetic_zone_parents() {
+        use geo::{Contains, InteriorPoint};
+
+        // levels whose zone counts do not shrink towards the coarser levels:
+        // dependency < country and macrohood < locality
+        let config = ZoneConfig {
+            source: ZoneSource::Synthetic { seed: 42 },
+            subtype_tiers: vec![SubtypeTier::new(
+                0.0,
+                &["country", "dependency", "region", "locality", "macrohood"],
+            )],
+            ..ZoneConfig::default()
+        };
+        for generator in [
+            ZoneGenerator::new_with_source(0.001, 1, 1, ZoneSource::Synthetic { seed: 42 }),
+            ZoneGenerator::new_with_config(0.001, 1, 1, config),
+        ] {
+            let zones: Vec<_> = generator.iter().collect();
+            let mut counts: HashMap<i32, usize> = HashMap::new();
+            for zone in &zones {
+                *counts.entry(zone.z_level).or_default() += 1;
+            }
+
+            for zone in &zones {
+                // the nearest coarser level whose tiling the zone refines
+                let parent_level = counts
+                    .iter()
+                    .filter(|(l, n)| **l < zone.z_level && **n <= counts[&zone.z_level])
+                    .map(|(l, _)| *l)
+                    .max();
+                let Some(parent_key) = zone.z_parentkey else {
+                    assert_eq!(parent_level, None, "{}", zone.z_subtype);
+                    continue;
+                };
+                let parent = &zones[parent_key as usize - 1];
+                assert_eq!(Some(parent.z_level), parent_level);
+                let point = zone.z_boundary.interior_point().unwrap();
+                assert!(parent.z_boundary.contains(&point));
+            }
+        }
+    }
+
+    #[test]
+    fn test_synthetic_zone_counts() {
+        for scale_factor in [0.00001, 0.0003, 0.001, 0.01] {
+            let generator = ZoneGenerator::new_with_source(
+                scale_factor,
+                1,
+                1,
+                ZoneSource::Synthetic { seed: 1 },
+            );
+            let total = generator.calculate_total_zones().unwrap();
+            assert_eq!(generator.iter().count() as i64, total, "sf={scale_factor}");
+        }
+    }
+
+    #[test]
+    fn test_zone_parents_by_containment() {
+        use geo::InteriorPoint;
+
+        let square = |x0: f64, y0: f64, size: f64| -> Geometry {
+            geo::Rect::new((x0, y0), (x0 + size, y0 + size))
+                .to_polygon()
+                .into()
+        };
+        let zone = |key: i64, subtype: &str, boundary: Geometry| Zone {
+            z_zonekey: key,
+            z_gersid: format!("id{key}"),
+            z_country: "US".to_string(),
+            z_region: "US-CA".to_string(),
+            z_name: format!("Zone {key}"),
+            z_subtype: subtype.to_string(),
+            z_level: subtype_rank(subtype) as i32,
+            z_parentkey: None,
+            z_boundary: boundary,
+            z_bbox: None,
+        };
+        let mut zones = vec![
+            zone(1, "county", square(0.0, 0.0, 10.0)),
+            zone(2, "county", square(10.0, 0.0, 10.0)),
+            zone(3, "macrohood", square(1.0, 1.0, 2.0)),
+            zone(4, "macrohood", square(12.0, 1.0, 2.0)),
+            zone(5, "microhood", square(12.5, 1.5, 0.5)),
+            // not inside any macrohood, so the parent is the county
+            zone(6, "microhood", square(5.0, 5.0, 1.0)),
+            // outside all zones
+            zone(7, "microhood", square(50.0, 50.0, 1.0)),
+        ];
+        crate::zone::hierarchy::assign_parent_keys(&mut zones);
+
+        let parents: Vec<_> = zones.iter().map(|z| z.z_parentkey).collect();
+        assert_eq!(
+            parents,
+            vec![None, None, Some(1), Some(2), Some(4), Some(1), None]
+        );
+
+        // the zone cache assigns the same parents one level at a time
+        let points: Vec<_> = zones
+            .iter()
+            .map(|z| (z.z_zonekey, z.z_level, z.z_boundary.interior_point()))
+            .collect();
+        let by_level = crate::zone::hierarchy::parent_keys(&points, |level| {
+            Ok(zones
+                .iter()
+                .filter(|z| z.z_level == level)
+                .map(|z| (z.z_zonekey, z.z_boundary.clone()))
+                .collect())
+        })
+        .unwrap();
+        assert_eq!(by_level, parents);
+    }
+
+    #[test]
+    fn test_zone_geometry_options() {
+        use geo::{Area, CoordsIter};
+
+        // a wiggly circle with 4000 vertices
+        let ring: Vec<(f64, f64)> = (0..4000)
+            .map(|i| {
+                let angle = i as f64 / 4000.0 * std::f64::consts::TAU;
+                let radius = 1.0 + 0.001 * (i % 2) as f64;
+                (radius * angle.cos(), radius * angle.sin())
+            })
+            .collect();
+        let zone = Zone {
+            z_zonekey: 1,
+            z_gersid: "id1".to_string(),
+            z_country: "US".to_string(),
+            z_region: "US-CA".to_string(),
+            z_name: "Zone 1".to_string(),
+            z_subtype: "county".to_string(),
+            z_level: 3,
+            z_parentkey: None,
+            z_boundary: geo::Polygon::new(ring.into(), vec![]).into(),
+            z_bbox: None,
+        };
+        let area = zone.z_boundary.unsigned_area();
+
+        let mut simplified = zone.clone();
+        ZoneGeometryOptions {
+            simplify_tolerance: Some(0.01),
+            ..Default::default()
+        }
+        .apply(&mut simplified);
+        assert!(simplified.z_boundary.coords_count() < 1000);
+        assert!((simplified.z_boundary.unsigned_area() - area).abs() < 0.01 * area);
+        assert_eq!(simplified.z_bbox, None);
+
+        let mut subdivided = zone.clone();
+        ZoneGeometryOptions {
+            max_vertices: Some(256),
+            bbox: true,
+            ..Default::default()
+        }
+        .apply(&mut subdivided);
+        let Geometry::MultiPolygon(pieces) = &subdivided.z_boundary else {
+            panic!("expected a MultiPolygon, got {:?}", subdivided.z_boundary);
+        };
+        assert!(pieces.0.len() > 1);
+        assert!(pieces.iter().all(|p| p.coords_count() <= 256));
+        assert!((subdivided.z_boundary.unsigned_area() - area).abs() < 1e-9);
+        // the pieces are snapped to a fine grid when clipped
+        let (bbox, expected) = (
+            subdivided.z_bbox.unwrap(),
+            zone.z_boundary.bounding_rect().unwrap(),
+        );
+        assert!((bbox.min().x - expected.min().x).abs() < 1e-8);
+        assert!((bbox.max().y - expected.max().y).abs() < 1e-8);
+        let bbox_wkt = OptionalRect(subdivided.z_bbox).to_string();
+        assert!(bbox_wkt.starts_with("POLYGON(("));
+        assert!(subdivided.to_string().ends_with(&format!("|{bbox_wkt}|")));
+        assert!(crate::csv::ZoneCsv::new(subdivided)
+            .to_string()
+            .ends_with(&format!(",\"{bbox_wkt}\"")));
+        // without bounding boxes, the column is not written
+        assert!(simplified
+            .to_string()
+            .ends_with(&format!("|{:?}|", simplified.z_boundary)));
+
+        assert!(ZoneGeometryOptions {
+            max_vertices: Some(4),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_zone_subtype_filters() {
+        let config = ZoneConfig::default();
+
+        // Test scale factor 0-10: should include microhood, macrohood, and county
+        let subtypes_0_10 = config.subtypes_for_scale_factor(5.0);
+        assert_eq!(subtypes_0_10, vec!["microhood", "macrohood", "county"]);
+
+        // Test scale factor 10-100: should include microhood, macrohood, county, and neighborhood
+        let subtypes_10_100 = config.subtypes_for_scale_factor(50.0);
+        assert_eq!(
+            subtypes_10_100,
+            vec!["microhood", "macrohood", "county", "neighborhood"]
+        );
+
+        // Test scale factor 100-1000: should include all except country
+        let subtypes_100_1000 = config.subtypes_for_scale_factor(500.0);
+        assert_eq!(
+            subtypes_100_1000,
+            vec![
+                "microhood",
+                "macrohood",
+                "county",
+                "neighborhood",
+                "localadmin",
+                "locality",
+                "region",
+                "dependency"
+            ]
+        );
+
+        // Test scale factor 1000+: should include all subtypes
+        let subtypes_1000_plus = config.subtypes_for_scale_factor(2000.0);
+        assert_eq!(
+            subtypes_1000_plus,
+            vec![
+                "microhood",
+                "macrohood",
+                "county",
+                "neighborhood",
+                "localadmin",
+                "locality",
+                "region",
+                "dependency",
+                "country"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nation_outlines() {
+        use crate::spatial::nations::{nation_outline, NationArea};
+        use geo::{Area, BooleanOps, Validation};
+
+        let nations = Distributions::static_default().nations();
+        let outlines: Vec<_> = (0..nations.size())
+            .map(|key| {
+                let name = nations.get_value(key);
+                (name, nation_outline(name).unwrap())
+            })
+            .collect();
+        for (name, outline) in &outlines {
+            assert!(outline.is_valid(), "{name}");
+        }
+        // neighbouring nations only share their borders
+        for (i, (name, outline)) in outlines.iter().enumerate() {
+            for (other, other_outline) in &outlines[i + 1..] {
+                let overlap = outline.intersection(other_outline).unsigned_area();
+                assert!(overlap < 1e-9, "{name} and {other} overlap by {overlap}");
+            }
+        }
+
+        let nation_of = |lon: f64, lat: f64| {
+            let point = Point::new(lon, lat);
+            let found: Vec<_> = (0..nations.size())
+                .map(|key| nations.get_value(key))
+                .filter(|name| NationArea::of(name).contains(&point))
+                .collect();
+            assert!(found.len() <= 1, "{found:?}");
+            found.first().copied()
+        };
+        assert_eq!(nation_of(-87.63, 41.88), Some("UNITED STATES")); // Chicago
+        assert_eq!(nation_of(-122.33, 47.61), Some("UNITED STATES")); // Seattle
+        assert_eq!(nation_of(-83.05, 42.33), Some("UNITED STATES")); // Detroit
+        assert_eq!(nation_of(-79.38, 43.65), Some("CANADA")); // Toronto
+        assert_eq!(nation_of(-123.12, 49.28), Some("CANADA")); // Vancouver
+        assert_eq!(nation_of(-99.13, 19.43), None); // Mexico City
+        assert_eq!(nation_of(37.62, 55.75), Some("RUSSIA")); // Moscow
+        assert_eq!(nation_of(131.89, 43.12), Some("RUSSIA")); // Vladivostok
+        assert_eq!(nation_of(76.95, 43.24), None); // Almaty
+        assert_eq!(nation_of(71.45, 51.17), None); // Astana
+        assert_eq!(nation_of(106.91, 47.89), None); // Ulaanbaatar
+        assert_eq!(nation_of(116.40, 39.90), Some("CHINA")); // Beijing
+        assert_eq!(nation_of(77.21, 28.61), Some("INDIA")); // New Delhi
+        assert_eq!(nation_of(90.41, 23.81), None); // Dhaka
+        assert_eq!(nation_of(7.75, 48.58), Some("FRANCE")); // Strasbourg
+        assert_eq!(nation_of(7.85, 48.00), Some("GERMANY")); // Freiburg
+        assert_eq!(nation_of(4.35, 50.85), None); // Brussels
+        assert_eq!(nation_of(-6.26, 53.35), None); // Dublin
+        assert_eq!(nation_of(-5.93, 54.60), Some("UNITED KINGDOM")); // Belfast
+        assert_eq!(nation_of(139.69, 35.69), Some("JAPAN")); // Tokyo
+        assert_eq!(nation_of(106.85, -6.21), Some("INDONESIA")); // Jakarta
+        assert_eq!(nation_of(-58.38, -34.60), Some("ARGENTINA")); // Buenos Aires
+        assert_eq!(nation_of(-56.16, -34.90), None); // Montevideo
+    }
+
+    #[test]
+    fn test_customer_and_driver_locations() {
+        use crate::spatial::nations::NationArea;
+
+        // each location is in the outline of its nation, and of no other
+        let nations = Distributions::static_default().nations();
+        let areas: Vec<_> = (0..nations.size())
+            .map(|key| {
+                (
+                    nations.get_value(key),
+                    NationArea::of(nations.get_value(key)),
+                )
+            })
+            .collect();
+        let in_nation = |loc: Point, nation: &str| {
+            areas
+                .iter()
+                .all(|(name, area)| area.contains(&loc) == (*name == nation))
+        };
+
+        let customers: Vec<_> = CustomerGenerator::new(0.01, 1, 1)
+            .with_home_locations(SpatialDefaults::customer_default())
+            .unwrap()
+            .iter()
+            .collect();
+        assert_eq!(customers.len(), 300);
+        for customer in &customers {
+            let homeloc = customer.c_homeloc.unwrap();
+            assert!(in_nation(homeloc, customer.c_nation), "{customer}");
+        }
+        // the other columns are unchanged
+        let plain: Vec<_> = CustomerGenerator::new(0.01, 1, 1).iter().collect();
+        assert!(plain.iter().all(|c| c.c_homeloc.is_none()));
+        assert_eq!(
+            customers[0].to_string(),
+            format!(
+                "{}POINT({:?} {:?})|",
+                plain[0],
+                customers[0].c_homeloc.unwrap().x(),
+                customers[0].c_homeloc.unwrap().y()
+            )
+        );
+
+        // parts place drivers as the whole table does
+        let generator = DriverGenerator::new(0.1, 1, 1)
+            .with_base_locations(SpatialDefaults::driver_default())
+            .unwrap();
+        let drivers: Vec<_> = generator.iter().collect();
+        let parts: Vec<_> = (1..=2)
+            .flat_map(|part| {
+                DriverGenerator::new(0.1, part, 2)
+                    .with_base_locations(SpatialDefaults::driver_default())
+                    .unwrap()
+                    .iter()
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(drivers, parts);
+        for driver in &drivers {
+            let baseloc = driver.d_baseloc.unwrap();
+            assert!(in_nation(baseloc, &driver.d_nation), "{driver}");
+        }
+
+        // with the land mask, locations are on land
+        let mask = LandMask::bundled();
+        for customer in CustomerGenerator::new(0.01, 1, 1)
+            .with_home_locations(SpatialDefaults::customer_default())
+            .unwrap()
+            .with_land_mask()
+            .iter()
+        {
+            assert!(mask.contains(&customer.c_homeloc.unwrap()), "{customer}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_location_generators() {
+        use crate::spatial::HotspotComponent;
+
+        let customer_default = SpatialDefaults::customer_default();
+        let default_config = customer_default.config();
+        let polygons = SpatialGenerator::new(SpatialConfig {
+            geom_type: GeomType::Polygon,
+            ..default_config.clone()
+        });
+        let hotspot = SpatialGenerator::new(SpatialConfig {
+            dist_type: DistributionType::Hotspot,
+            params: DistributionParams::Hotspot {
+                components: vec![HotspotComponent {
+                    lon: -111.76,
+                    lat: 34.87,
+                    sigma_km: 5.0,
+                    weight: 1.0,
+                }],
+                background: None,
+            },
+            ..default_config.clone()
+        });
+        let meters = SpatialGenerator::new(SpatialConfig {
+            units: SizeUnit::Meters,
+            ..default_config.clone()
+        });
+        for spatial_gen in [polygons, hotspot, meters] {
+            assert!(matches!(
+                CustomerGenerator::new(0.01, 1, 1).with_home_locations(spatial_gen.clone()),
+                Err(SpatialBenchError::InvalidConfig(_))
+            ));
+            assert!(matches!(
+                DriverGenerator::new(0.01, 1, 1).with_base_locations(spatial_gen),
+                Err(SpatialBenchError::InvalidConfig(_))
+            ));
+        }
     }
 }
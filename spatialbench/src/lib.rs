@@ -27,6 +27,33 @@
 //!  );
 //! ```
 //!
+//! # Example: custom spatial distribution
+//!
+//! Trips and buildings can be placed by your own [`SpatialDistribution`], e.g.
+//! one sampling locations from real data, instead of the configured one.
+//! ```
+//! # use geo::{Geometry, Point};
+//! # use spatialbench::generators::TripGenerator;
+//! # use spatialbench::spatial::SpatialDistribution;
+//! /// Pickups on the diagonal of the continent of the trip
+//! #[derive(Debug)]
+//! struct DiagonalPickups;
+//!
+//! impl SpatialDistribution for DiagonalPickups {
+//!     fn sample(&self, index: u64, m: &[f64; 6]) -> Geometry {
+//!         let t = (index % 1000) as f64 / 1000.0;
+//!         Point::new(m[0] * t + m[1] * t + m[2], m[3] * t + m[4] * t + m[5]).into()
+//!     }
+//! }
+//!
+//! let pickups = Box::new(DiagonalPickups);
+//! let generator = TripGenerator::new_with_spatial_distribution(0.01, 1, 1, pickups)
+//!     .expect("the distribution has nothing to prepare");
+//! assert_eq!(generator.iter().count(), 60_000);
+//! ```
+//!
+//! [`SpatialDistribution`]: spatial::SpatialDistribution
+//!
 //! The TPC-H dataset is composed of several tables with foreign key relations
 //! between them. For each table we implement and expose a generator that uses
 //! the iterator API to produce structs e.g [`Trip`] that represent a single
@@ -55,9 +82,13 @@ pub mod csv;
 pub mod dates;
 pub mod decimal;
 pub mod distribution;
+pub mod error;
 pub mod generators;
 pub mod kde;
 pub mod q_and_a;
 pub mod random;
 pub mod spatial;
 pub mod text;
+pub mod zone;
+
+pub use error::SpatialBenchError;
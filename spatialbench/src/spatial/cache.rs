@@ -1,3 +1,5 @@
+use crate::spatial::SpatialGenerator;
+
 #[derive(Clone, Debug)]
 pub struct ThomasCache {
     pub cdf: Vec<f64>,
@@ -12,3 +14,24 @@ pub struct HierThomasCache {
     pub city_cdf: Vec<f64>,
     pub sub_cdfs: Vec<Vec<f64>>,
 }
+
+#[derive(Clone, Debug)]
+pub struct DensityGridCache {
+    pub ncols: usize,
+    pub nrows: usize,
+    /// Row-major indices of the cells with a positive weight, from the
+    /// north-west cell
+    pub cells: Vec<usize>,
+    pub cdf: Vec<f64>,
+    /// Maps unit coordinates of the raster (y running south) to lon/lat
+    pub affine: [f64; 6],
+}
+
+#[derive(Clone, Debug)]
+pub struct HotspotCache {
+    /// Cumulative weights of the components
+    pub cdf: Vec<f64>,
+    /// Share of the points drawn from the background
+    pub background_share: f64,
+    pub background: Option<SpatialGenerator>,
+}
@@ -0,0 +1,100 @@
+//! City models shared by several tables
+//!
+//! A city model places Pareto-weighted cities in the unit square, each with a
+//! variable number of Pareto-weighted subclusters around it, as the hierarchical
+//! Thomas distribution does. Tables drawing from the same model (the `CityModel`
+//! distribution) pick cities and subclusters with the same weights and spread
+//! their records around the same subcluster centers, each with its own spread,
+//! so e.g. trip pickups concentrate where the buildings are.
+//!
+//! The centers only depend on the model, and records pick their subcluster with
+//! the seed of their table, so tables sharing a model are still independent
+//! samples of it.
+
+use crate::error::{Result, SpatialBenchError};
+use crate::spatial::{DistributionParams, SpatialConfig};
+
+/// Cities and subclusters of a city model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CityModel {
+    /// Seed of the weights and subcluster centers
+    pub seed: u32,
+    /// Number of cities
+    pub cities: u32,
+    // subclusters per city (normal, clamped)
+    pub sub_mean: f64,
+    pub sub_sd: f64,
+    pub sub_min: u32,
+    pub sub_max: u32,
+    /// Spread of subcluster centers around their city, in the unit of the table
+    pub sigma_city: f64,
+    // Pareto weights
+    pub pareto_alpha_city: f64,
+    pub pareto_xm_city: f64,
+    pub pareto_alpha_sub: f64,
+    pub pareto_xm_sub: f64,
+}
+
+impl CityModel {
+    /// Returns the city model and the spread of records around their subcluster
+    /// of a hierarchical Thomas or city model distribution
+    ///
+    /// A hierarchical Thomas distribution is a city model of its own, seeded
+    /// with the seed of its table.
+    pub fn of(config: &SpatialConfig) -> Option<(CityModel, f64)> {
+        match config.params {
+            DistributionParams::HierarchicalThomas {
+                cities,
+                sub_mean,
+                sub_sd,
+                sub_min,
+                sub_max,
+                sigma_city,
+                sigma_sub,
+                pareto_alpha_city,
+                pareto_xm_city,
+                pareto_alpha_sub,
+                pareto_xm_sub,
+            } => Some((
+                CityModel {
+                    seed: config.seed,
+                    cities,
+                    sub_mean,
+                    sub_sd,
+                    sub_min,
+                    sub_max,
+                    sigma_city,
+                    pareto_alpha_city,
+                    pareto_xm_city,
+                    pareto_alpha_sub,
+                    pareto_xm_sub,
+                },
+                sigma_sub,
+            )),
+            DistributionParams::CityModel { model, sigma_sub } => Some((model, sigma_sub)),
+            _ => None,
+        }
+    }
+
+    /// Checks that the model has cities and a valid range of subclusters
+    pub fn validate(&self) -> Result<()> {
+        if self.cities == 0 {
+            return Err(SpatialBenchError::InvalidConfig(
+                "City model needs at least one city".to_string(),
+            ));
+        }
+        if self.sub_min > self.sub_max {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "City model sub_min ({}) is larger than sub_max ({})",
+                self.sub_min, self.sub_max
+            )));
+        }
+        if !(self.sigma_city.is_finite() && self.sigma_city >= 0.0) {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "City model sigma_city must be non-negative, got {}",
+                self.sigma_city
+            )));
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,8 @@
+use crate::error::{Result, SpatialBenchError};
+use crate::spatial::CityModel;
+use std::fmt;
+use std::str::FromStr;
+
 #[derive(Debug, Clone, Copy)]
 pub enum DistributionType {
     Uniform,
@@ -7,6 +12,10 @@ pub enum DistributionType {
     Bit,
     Thomas,
     HierarchicalThomas,
+    Parcel,
+    DensityGrid,
+    Hotspot,
+    CityModel,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -14,6 +23,95 @@ pub enum GeomType {
     Polygon,
     Box,
     Point,
+    LineString,
+}
+
+/// Shape of generated polygons
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PolygonStyle {
+    /// Vertices at random angles on the circle of radius `polysize`
+    #[default]
+    Star,
+    /// Building footprints: rotated rectangles, L, T and U shapes and occasional
+    /// irregular polygons, whose longer side is log-normally distributed in meters
+    /// (coordinates of the region are taken as lon/lat)
+    Footprint {
+        /// Median length of the longer side, in meters
+        median_size: f64,
+        /// Standard deviation of the natural log of the length
+        size_sigma: f64,
+    },
+}
+
+/// Validity guarantees of generated box and polygon geometries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Validity {
+    /// Geometries as generated: vertices clamped to the unit square may repeat
+    /// or form slivers, and rings may have either orientation
+    #[default]
+    Raw,
+    /// OGC-valid polygons without repeated points or self-intersections, with
+    /// counter-clockwise exteriors, clockwise holes and an area of at least
+    /// [`MIN_POLYGON_AREA`](crate::spatial::geometry::MIN_POLYGON_AREA)
+    Strict,
+}
+
+impl fmt::Display for Validity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Validity::Raw => write!(f, "raw"),
+            Validity::Strict => write!(f, "strict"),
+        }
+    }
+}
+
+impl FromStr for Validity {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "raw" => Ok(Validity::Raw),
+            "strict" => Ok(Validity::Strict),
+            _ => Err(format!(
+                "Unknown validity '{s}'. Expected one of: strict, raw"
+            )),
+        }
+    }
+}
+
+/// Unit of the geometry sizes (`width`, `height` and `polysize`) and of the
+/// spreads of the Thomas distributions (`sigma`, `sigma_city` and `sigma_sub`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SizeUnit {
+    /// Coordinates of the unit square, stretched by the affine of the region a
+    /// geometry is placed in
+    #[default]
+    Unit,
+    /// Meters, converted to degrees at the latitude of each geometry, so sizes
+    /// and spreads are the same in every region (coordinates of the region are
+    /// taken as lon/lat)
+    Meters,
+}
+
+impl fmt::Display for SizeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SizeUnit::Unit => write!(f, "unit"),
+            SizeUnit::Meters => write!(f, "meters"),
+        }
+    }
+}
+
+impl FromStr for SizeUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unit" => Ok(SizeUnit::Unit),
+            "meters" | "m" => Ok(SizeUnit::Meters),
+            _ => Err(format!("Unknown unit '{s}'. Expected one of: unit, meters")),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -35,6 +133,12 @@ pub enum DistributionParams {
         srange: f64,
         dither: f64,
     },
+    DensityGrid {
+        /// Local raster file (see [`density`](crate::spatial::density))
+        path: String,
+        /// `[min_lon, min_lat, max_lon, max_lat]` of the raster
+        bbox: Option<[f64; 4]>,
+    },
     Thomas {
         parents: u32,
         mean_offspring: f64,
@@ -57,6 +161,39 @@ pub enum DistributionParams {
         pareto_alpha_sub: f64,
         pareto_xm_sub: f64,
     },
+    Hotspot {
+        /// Gaussian components the points are drawn from
+        components: Vec<HotspotComponent>,
+        /// Distribution of the points outside the hotspots, if any
+        background: Option<Box<HotspotBackground>>,
+    },
+    CityModel {
+        /// Cities and subclusters, shared with the other tables of the model
+        model: CityModel,
+        /// Spread of the records of the table around their subcluster
+        sigma_sub: f64,
+    },
+}
+
+/// A Gaussian cluster of the hotspot distribution, centered on a lon/lat
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotspotComponent {
+    pub lon: f64,
+    pub lat: f64,
+    /// Standard deviation of the distance to the center, in kilometers
+    pub sigma_km: f64,
+    /// Relative share of the points drawn from the component
+    pub weight: f64,
+}
+
+/// Distribution of the points of the hotspot distribution that are not drawn
+/// from a component, placed in the regions like any other distribution
+#[derive(Debug, Clone)]
+pub struct HotspotBackground {
+    /// Relative share of the points, in the same unit as the component weights
+    pub weight: f64,
+    pub dist_type: DistributionType,
+    pub params: DistributionParams,
 }
 
 #[derive(Debug, Clone)]
@@ -70,12 +207,185 @@ pub struct SpatialConfig {
     pub width: f64,
     pub height: f64,
 
-    // Polygon-specific
+    // Polygon- and LineString-specific
     pub maxseg: i32,
     pub polysize: f64,
 
+    // Polygon-specific
+    pub polygon_style: PolygonStyle,
+
     // Distribution-specific
     pub params: DistributionParams,
+
+    // Box- and Polygon-specific
+    pub validity: Validity,
+
+    // Unit of the sizes and of the spreads of the Thomas distributions
+    pub units: SizeUnit,
+}
+
+impl SpatialConfig {
+    /// Checks that the parameters match the distribution type
+    ///
+    /// Uniform and Sierpinski distributions take no parameters and ignore any
+    /// that are given.
+    pub fn validate(&self) -> Result<()> {
+        let matches = match self.dist_type {
+            DistributionType::Uniform | DistributionType::Sierpinski => true,
+            DistributionType::Normal => matches!(self.params, DistributionParams::Normal { .. }),
+            DistributionType::Diagonal => {
+                matches!(self.params, DistributionParams::Diagonal { .. })
+            }
+            DistributionType::Bit => matches!(self.params, DistributionParams::Bit { .. }),
+            DistributionType::Thomas => matches!(self.params, DistributionParams::Thomas { .. }),
+            DistributionType::HierarchicalThomas => {
+                matches!(self.params, DistributionParams::HierarchicalThomas { .. })
+            }
+            DistributionType::Parcel => matches!(self.params, DistributionParams::Parcel { .. }),
+            DistributionType::DensityGrid => {
+                matches!(self.params, DistributionParams::DensityGrid { .. })
+            }
+            DistributionType::Hotspot => matches!(self.params, DistributionParams::Hotspot { .. }),
+            DistributionType::CityModel => {
+                matches!(self.params, DistributionParams::CityModel { .. })
+            }
+        };
+        if !matches {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "{:?} distribution does not take {:?} parameters",
+                self.dist_type, self.params
+            )));
+        }
+        if let DistributionParams::Hotspot {
+            components,
+            background,
+        } = &self.params
+        {
+            self.validate_hotspot(components, background.as_deref())?;
+        }
+        if let DistributionParams::CityModel { model, .. } = &self.params {
+            model.validate()?;
+        }
+        if self.dim != 2 && self.dim != 3 {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "Dimension must be 2 or 3, got {}",
+                self.dim
+            )));
+        }
+        if let PolygonStyle::Footprint {
+            median_size,
+            size_sigma,
+        } = self.polygon_style
+        {
+            if !(median_size.is_finite() && median_size > 0.0) {
+                return Err(SpatialBenchError::InvalidConfig(format!(
+                    "Footprint median size must be positive, got {median_size}"
+                )));
+            }
+            if !(size_sigma.is_finite() && size_sigma >= 0.0) {
+                return Err(SpatialBenchError::InvalidConfig(format!(
+                    "Footprint size sigma must be non-negative, got {size_sigma}"
+                )));
+            }
+            if let DistributionType::Parcel = self.dist_type {
+                return Err(SpatialBenchError::InvalidConfig(
+                    "Parcel distribution sizes polygons by their parcel, not by footprint style"
+                        .to_string(),
+                ));
+            }
+        }
+        if matches!(self.geom_type, GeomType::LineString)
+            && !(self.polysize.is_finite() && self.polysize > 0.0)
+        {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "Line string segment length (polysize) must be positive, got {}",
+                self.polysize
+            )));
+        }
+        if let DistributionType::Parcel = self.dist_type {
+            if self.units == SizeUnit::Meters {
+                return Err(SpatialBenchError::InvalidConfig(
+                    "Parcel distribution sizes geometries by their parcel, not in meters"
+                        .to_string(),
+                ));
+            }
+            if let GeomType::Point | GeomType::LineString = self.geom_type {
+                return Err(SpatialBenchError::InvalidConfig(format!(
+                    "Parcel distribution generates box or polygon geometries, not {:?}",
+                    self.geom_type
+                )));
+            }
+            if let DistributionParams::Parcel { srange, dither } = self.params {
+                if !(0.0..=0.5).contains(&srange) {
+                    return Err(SpatialBenchError::InvalidConfig(format!(
+                        "Parcel srange must be between 0 and 0.5, got {srange}"
+                    )));
+                }
+                if !(0.0..=1.0).contains(&dither) {
+                    return Err(SpatialBenchError::InvalidConfig(format!(
+                        "Parcel dither must be between 0 and 1, got {dither}"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the components of a hotspot distribution and that its background
+    /// is a valid distribution of its own
+    fn validate_hotspot(
+        &self,
+        components: &[HotspotComponent],
+        background: Option<&HotspotBackground>,
+    ) -> Result<()> {
+        for c in components {
+            if !((-180.0..=180.0).contains(&c.lon) && (-90.0..=90.0).contains(&c.lat)) {
+                return Err(SpatialBenchError::InvalidConfig(format!(
+                    "Hotspot center ({}, {}) is not a valid lon/lat",
+                    c.lon, c.lat
+                )));
+            }
+            if !(c.sigma_km.is_finite() && c.sigma_km > 0.0) {
+                return Err(SpatialBenchError::InvalidConfig(format!(
+                    "Hotspot sigma_km must be positive, got {}",
+                    c.sigma_km
+                )));
+            }
+            if !(c.weight.is_finite() && c.weight >= 0.0) {
+                return Err(SpatialBenchError::InvalidConfig(format!(
+                    "Hotspot weight must be non-negative, got {}",
+                    c.weight
+                )));
+            }
+        }
+        if components.iter().all(|c| c.weight == 0.0) {
+            return Err(SpatialBenchError::InvalidConfig(
+                "Hotspot distribution needs a component with a positive weight".to_string(),
+            ));
+        }
+
+        let Some(background) = background else {
+            return Ok(());
+        };
+        if !(background.weight.is_finite() && background.weight >= 0.0) {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "Hotspot background weight must be non-negative, got {}",
+                background.weight
+            )));
+        }
+        if let DistributionType::Hotspot | DistributionType::Parcel = background.dist_type {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "{:?} distribution cannot be a hotspot background",
+                background.dist_type
+            )));
+        }
+        SpatialConfig {
+            dist_type: background.dist_type,
+            params: background.params.clone(),
+            ..self.clone()
+        }
+        .validate()
+    }
 }
 
 #[derive(Debug, Clone, serde::Deserialize)]
@@ -89,3 +399,148 @@ pub struct ContinentAffines {
     pub south_north_america: [f64; 6],
     pub north_north_america: [f64; 6],
 }
+
+impl ContinentAffines {
+    /// Returns the continents as regions weighted by the area of their bounding box
+    pub fn regions(&self) -> Vec<Region> {
+        [
+            ("africa", self.africa),
+            ("europe", self.europe),
+            ("south_asia", self.south_asia),
+            ("north_asia", self.north_asia),
+            ("oceania", self.oceania),
+            ("south_america", self.south_america),
+            ("south_north_america", self.south_north_america),
+            ("north_north_america", self.north_north_america),
+        ]
+        .into_iter()
+        .map(|(name, affine)| Region {
+            name: name.to_string(),
+            affine,
+            weight: None,
+        })
+        .collect()
+    }
+}
+
+/// A named region trips and buildings are placed in
+///
+/// Distributions generate coordinates in the unit square, which the affine of
+/// the region maps to lon/lat.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub name: String,
+    /// `[a, b, c, d, e, f]` mapping `(x, y)` to `(a * x + b * y + c, d * x + e * y + f)`
+    pub affine: [f64; 6],
+    /// Relative share of the records placed in the region, or the spherical
+    /// area of its bounding box if `None`. Either every region of a list has a
+    /// weight or none has.
+    pub weight: Option<f64>,
+}
+
+impl Region {
+    /// Creates a region covering the bounding box `[min_lon, min_lat, max_lon, max_lat]`
+    pub fn from_bbox(name: impl Into<String>, bbox: [f64; 4], weight: Option<f64>) -> Self {
+        let [min_lon, min_lat, max_lon, max_lat] = bbox;
+        Self {
+            name: name.into(),
+            affine: [
+                max_lon - min_lon,
+                0.0,
+                min_lon,
+                0.0,
+                max_lat - min_lat,
+                min_lat,
+            ],
+            weight,
+        }
+    }
+}
+
+/// Checks that regions can be sampled: at least one region, finite affines and
+/// non-negative weights, set on every region or on none, that are not all zero
+pub fn validate_regions(regions: &[Region]) -> Result<()> {
+    if regions.is_empty() {
+        return Err(SpatialBenchError::InvalidConfig(
+            "At least one region is required".to_string(),
+        ));
+    }
+    for region in regions {
+        if region.affine.iter().any(|v| !v.is_finite()) {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "Invalid affine of region '{}'",
+                region.name
+            )));
+        }
+        if let Some(weight) = region.weight {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(SpatialBenchError::InvalidConfig(format!(
+                    "Invalid weight {weight} of region '{}'",
+                    region.name
+                )));
+            }
+        }
+    }
+    // a default weight (an area) and a relative share are in different units
+    if let Some(region) = regions.iter().find(|r| r.weight.is_none()) {
+        if regions.iter().any(|r| r.weight.is_some()) {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "Region '{}' has no weight; set the weight of every region or of none",
+                region.name
+            )));
+        }
+    }
+    if regions.iter().all(|r| r.weight == Some(0.0)) {
+        return Err(SpatialBenchError::InvalidConfig(
+            "Weights of the regions are all zero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Shapes of building footprints beyond a single ring
+///
+/// Probabilities of zero, the default, keep every footprint a simple polygon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FootprintOptions {
+    /// Probability that a polygon of a footprint has a courtyard (an interior ring)
+    pub courtyard_probability: f64,
+    /// Probability that a footprint is a campus of several polygons
+    pub multipolygon_probability: f64,
+    /// Maximum number of polygons of a campus, at least 2
+    pub max_parts: u32,
+}
+
+impl Default for FootprintOptions {
+    fn default() -> Self {
+        Self {
+            courtyard_probability: 0.0,
+            multipolygon_probability: 0.0,
+            max_parts: 4,
+        }
+    }
+}
+
+impl FootprintOptions {
+    /// Checks that the probabilities are between 0 and 1 and a campus can have
+    /// several polygons
+    pub fn validate(&self) -> Result<()> {
+        for (name, p) in [
+            ("courtyard", self.courtyard_probability),
+            ("multipolygon", self.multipolygon_probability),
+        ] {
+            if !(0.0..=1.0).contains(&p) {
+                return Err(SpatialBenchError::InvalidConfig(format!(
+                    "Footprint {name} probability must be between 0 and 1, got {p}"
+                )));
+            }
+        }
+        if self.max_parts < 2 {
+            return Err(SpatialBenchError::InvalidConfig(format!(
+                "Footprint max_parts must be at least 2, got {}",
+                self.max_parts
+            )));
+        }
+        Ok(())
+    }
+}
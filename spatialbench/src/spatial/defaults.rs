@@ -1,8 +1,7 @@
 use crate::spatial::{
-    ContinentAffines, DistributionParams, DistributionType, GeomType, SpatialConfig,
-    SpatialGenerator,
+    ContinentAffines, DistributionParams, DistributionType, GeomType, PolygonStyle, SizeUnit,
+    SpatialConfig, SpatialGenerator, Validity,
 };
-use std::sync::OnceLock;
 
 pub struct SpatialDefaults;
 
@@ -36,6 +35,7 @@ impl SpatialDefaults {
             // geometry = polygon
             maxseg: 0,
             polysize: 0.0,
+            polygon_style: PolygonStyle::Star,
 
             params: DistributionParams::HierarchicalThomas {
                 cities: 60000,
@@ -50,8 +50,10 @@ impl SpatialDefaults {
                 pareto_alpha_sub: 1.00,
                 pareto_xm_sub: 1.0,
             },
+            validity: Validity::Raw,
+            units: SizeUnit::Unit,
         };
-        SpatialGenerator::new(config, OnceLock::new(), OnceLock::new())
+        SpatialGenerator::new(config)
     }
 
     pub fn building_default() -> SpatialGenerator {
@@ -68,6 +70,7 @@ impl SpatialDefaults {
             // geometry = polygon
             maxseg: 7,
             polysize: 0.000039,
+            polygon_style: PolygonStyle::Star,
 
             params: DistributionParams::HierarchicalThomas {
                 cities: 10000,
@@ -82,7 +85,54 @@ impl SpatialDefaults {
                 pareto_alpha_sub: 1.00,
                 pareto_xm_sub: 1.0,
             },
+            validity: Validity::Raw,
+            units: SizeUnit::Unit,
         };
-        SpatialGenerator::new(config, OnceLock::new(), OnceLock::new())
+        SpatialGenerator::new(config)
+    }
+
+    /// Home locations of customers, clustered in the cities of their nation
+    pub fn customer_default() -> SpatialGenerator {
+        Self::nation_locations(24680, 0.01)
+    }
+
+    /// Base locations of drivers, spread wider around the cities of their nation
+    pub fn driver_default() -> SpatialGenerator {
+        Self::nation_locations(13579, 0.02)
+    }
+
+    fn nation_locations(seed: u32, sigma_sub: f64) -> SpatialGenerator {
+        let config = SpatialConfig {
+            dist_type: DistributionType::HierarchicalThomas,
+            geom_type: GeomType::Point,
+            dim: 2,
+            seed,
+
+            // geometry = box
+            width: 0.0,
+            height: 0.0,
+
+            // geometry = polygon
+            maxseg: 0,
+            polysize: 0.0,
+            polygon_style: PolygonStyle::Star,
+
+            params: DistributionParams::HierarchicalThomas {
+                cities: 100,
+                sub_mean: 8.0,
+                sub_sd: 5.0,
+                sub_min: 1,
+                sub_max: 30,
+                sigma_city: 0.05,
+                sigma_sub,
+                pareto_alpha_city: 1.00,
+                pareto_xm_city: 1.0,
+                pareto_alpha_sub: 1.00,
+                pareto_xm_sub: 1.0,
+            },
+            validity: Validity::Raw,
+            units: SizeUnit::Unit,
+        };
+        SpatialGenerator::new(config)
     }
 }
@@ -0,0 +1,726 @@
+//! Density rasters read by the density grid distribution
+//!
+//! A raster is a grid of non-negative weights covering a lon/lat bounding box,
+//! such as a population count per cell. Supported formats, chosen by the file
+//! extension:
+//!
+//! * `.asc`: ESRI ASCII grid, which carries its own extent in the header
+//! * `.tif`, `.tiff`: single band GeoTIFF, in strips or tiles, uncompressed or
+//!   compressed with LZW or Deflate, whose extent is read from its pixel scale
+//!   and tie point (or transformation) tags if it is in lon/lat
+//! * `.pgm`: plain (`P2`) or binary (`P5`) PGM image
+//! * `.csv`: one row of comma separated weights per raster row
+//!
+//! Rows run from north to south. PGM and CSV rasters have no georeference, so
+//! their extent must be given as a bounding box, as must the extent of a
+//! GeoTIFF in projected coordinates.
+
+use crate::error::{Result, SpatialBenchError};
+use crate::spatial::cache::DensityGridCache;
+use crate::spatial::utils::build_cdf_from_weights;
+use flate2::read::ZlibDecoder;
+use std::io::Read;
+use std::path::Path;
+
+/// Weights of a raster, row-major from the north-west cell
+struct Raster {
+    ncols: usize,
+    nrows: usize,
+    values: Vec<f64>,
+    /// `[min_lon, min_lat, max_lon, max_lat]`, if given by the file
+    bbox: Option<[f64; 4]>,
+}
+
+/// Reads the raster at `path` and builds the cumulative table of its cells
+///
+/// `bbox` (`[min_lon, min_lat, max_lon, max_lat]`) sets the extent of the
+/// raster, overriding the extent of an ASCII grid. Cells with a negative, NaN
+/// or no-data value get no points.
+pub fn load_density_grid(path: &str, bbox: Option<[f64; 4]>) -> Result<DensityGridCache> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    let raster = match extension.as_str() {
+        "asc" => parse_ascii_grid(&std::fs::read_to_string(path)?)?,
+        "tif" | "tiff" => parse_geotiff(&std::fs::read(path)?)?,
+        "pgm" => parse_pgm(&std::fs::read(path)?)?,
+        "csv" => parse_csv(&std::fs::read_to_string(path)?)?,
+        _ => {
+            return Err(invalid(format!(
+                "Unknown raster format of {path}. Expected .asc, .tif, .pgm or .csv"
+            )))
+        }
+    };
+
+    let [min_lon, min_lat, max_lon, max_lat] = bbox.or(raster.bbox).ok_or_else(|| {
+        invalid(format!(
+            "Raster {path} has no lon/lat georeference, set the bbox of the density grid"
+        ))
+    })?;
+    if !(min_lon < max_lon && min_lat < max_lat) {
+        return Err(invalid(format!(
+            "Invalid density grid bbox [{min_lon}, {min_lat}, {max_lon}, {max_lat}]"
+        )));
+    }
+
+    let (cells, weights): (Vec<usize>, Vec<f64>) = raster
+        .values
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| **v > 0.0)
+        .map(|(i, v)| (i, *v))
+        .unzip();
+    if cells.is_empty() {
+        return Err(invalid(format!(
+            "Raster {path} has no cell with a positive weight"
+        )));
+    }
+
+    Ok(DensityGridCache {
+        ncols: raster.ncols,
+        nrows: raster.nrows,
+        cells,
+        cdf: build_cdf_from_weights(weights),
+        // unit y runs from the north edge down
+        affine: [
+            max_lon - min_lon,
+            0.0,
+            min_lon,
+            0.0,
+            min_lat - max_lat,
+            max_lat,
+        ],
+    })
+}
+
+fn invalid(msg: String) -> SpatialBenchError {
+    SpatialBenchError::InvalidConfig(msg)
+}
+
+/// Parses an ESRI ASCII grid
+fn parse_ascii_grid(text: &str) -> Result<Raster> {
+    let mut tokens = text.split_whitespace().peekable();
+    let (mut ncols, mut nrows, mut cellsize, mut nodata) = (None, None, None, None);
+    let (mut xll, mut yll, mut center) = (None, None, false);
+    while let Some(key) = tokens.next_if(|t| t.starts_with(|c: char| c.is_ascii_alphabetic())) {
+        let value = tokens
+            .next()
+            .ok_or_else(|| invalid(format!("Missing value of ASCII grid header {key}")))?;
+        let number = || parse_number(value);
+        match key.to_lowercase().as_str() {
+            "ncols" => ncols = Some(parse_count(value)?),
+            "nrows" => nrows = Some(parse_count(value)?),
+            "xllcorner" => xll = Some(number()?),
+            "yllcorner" => yll = Some(number()?),
+            "xllcenter" => (xll, center) = (Some(number()?), true),
+            "yllcenter" => (yll, center) = (Some(number()?), true),
+            "cellsize" => cellsize = Some(number()?),
+            "nodata_value" => nodata = Some(number()?),
+            _ => return Err(invalid(format!("Unknown ASCII grid header {key}"))),
+        }
+    }
+    let (Some(ncols), Some(nrows), Some(xll), Some(yll), Some(cellsize)) =
+        (ncols, nrows, xll, yll, cellsize)
+    else {
+        return Err(invalid(
+            "ASCII grid header needs ncols, nrows, xllcorner, yllcorner and cellsize".to_string(),
+        ));
+    };
+
+    let values = tokens
+        .map(parse_number)
+        .map(|v| v.map(|v| if Some(v) == nodata { 0.0 } else { v }))
+        .collect::<Result<Vec<_>>>()?;
+    let (min_lon, min_lat) = if center {
+        (xll - cellsize / 2.0, yll - cellsize / 2.0)
+    } else {
+        (xll, yll)
+    };
+    let bbox = [
+        min_lon,
+        min_lat,
+        min_lon + ncols as f64 * cellsize,
+        min_lat + nrows as f64 * cellsize,
+    ];
+    raster(ncols, nrows, values, Some(bbox))
+}
+
+/// Parses a plain (`P2`) or binary (`P5`) PGM image
+fn parse_pgm(bytes: &[u8]) -> Result<Raster> {
+    // header: magic, width, height and maxval, separated by whitespace or comments
+    let mut pos = 0;
+    let mut header = Vec::new();
+    while header.len() < 4 {
+        while pos < bytes.len() && (bytes[pos].is_ascii_whitespace() || bytes[pos] == b'#') {
+            if bytes[pos] == b'#' {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    pos += 1;
+                }
+            } else {
+                pos += 1;
+            }
+        }
+        let start = pos;
+        while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        if start == pos {
+            return Err(invalid("Truncated PGM header".to_string()));
+        }
+        header.push(String::from_utf8_lossy(&bytes[start..pos]).into_owned());
+    }
+    let ncols = parse_count(&header[1])?;
+    let nrows = parse_count(&header[2])?;
+    let maxval = parse_count(&header[3])?;
+
+    let values = match header[0].as_str() {
+        "P2" => String::from_utf8_lossy(&bytes[pos..])
+            .split_whitespace()
+            .map(parse_number)
+            .collect::<Result<Vec<_>>>()?,
+        "P5" => {
+            // a single whitespace character separates the header from the pixels
+            let pixels = bytes.get(pos + 1..).unwrap_or_default();
+            if maxval < 256 {
+                pixels.iter().map(|&b| b as f64).collect()
+            } else {
+                pixels
+                    .chunks_exact(2)
+                    .map(|b| u16::from_be_bytes([b[0], b[1]]) as f64)
+                    .collect()
+            }
+        }
+        magic => {
+            return Err(invalid(format!(
+                "Unsupported PGM format {magic}. Expected P2 or P5"
+            )))
+        }
+    };
+    raster(ncols, nrows, values, None)
+}
+
+/// Parses rows of comma separated weights
+fn parse_csv(text: &str) -> Result<Raster> {
+    let rows = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.split(',')
+                .map(parse_number)
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let ncols = rows.first().map(Vec::len).unwrap_or_default();
+    if rows.iter().any(|row| row.len() != ncols) {
+        return Err(invalid(
+            "CSV raster rows have different numbers of cells".to_string(),
+        ));
+    }
+    raster(ncols, rows.len(), rows.concat(), None)
+}
+
+fn parse_number(token: &str) -> Result<f64> {
+    token
+        .trim()
+        .parse()
+        .map_err(|_| invalid(format!("Invalid raster value '{token}'")))
+}
+
+/// Parses a number of rows, columns or levels, which must be a whole number
+fn parse_count(token: &str) -> Result<usize> {
+    token.trim().parse().map_err(|_| {
+        invalid(format!(
+            "Invalid raster size '{token}', expected a whole number"
+        ))
+    })
+}
+
+/// Checks that the raster has `ncols` x `nrows` values
+fn raster(ncols: usize, nrows: usize, values: Vec<f64>, bbox: Option<[f64; 4]>) -> Result<Raster> {
+    if ncols == 0 || nrows == 0 || values.len() != ncols * nrows {
+        return Err(invalid(format!(
+            "Raster of {ncols} x {nrows} cells has {} values",
+            values.len()
+        )));
+    }
+    Ok(Raster {
+        ncols,
+        nrows,
+        values,
+        bbox,
+    })
+}
+
+/// TIFF tags read from a GeoTIFF
+mod tag {
+    pub const IMAGE_WIDTH: u16 = 256;
+    pub const IMAGE_LENGTH: u16 = 257;
+    pub const BITS_PER_SAMPLE: u16 = 258;
+    pub const COMPRESSION: u16 = 259;
+    pub const STRIP_OFFSETS: u16 = 273;
+    pub const SAMPLES_PER_PIXEL: u16 = 277;
+    pub const ROWS_PER_STRIP: u16 = 278;
+    pub const STRIP_BYTE_COUNTS: u16 = 279;
+    pub const PREDICTOR: u16 = 317;
+    pub const TILE_WIDTH: u16 = 322;
+    pub const TILE_LENGTH: u16 = 323;
+    pub const TILE_OFFSETS: u16 = 324;
+    pub const TILE_BYTE_COUNTS: u16 = 325;
+    pub const SAMPLE_FORMAT: u16 = 339;
+    pub const MODEL_PIXEL_SCALE: u16 = 33550;
+    pub const MODEL_TIEPOINT: u16 = 33922;
+    pub const MODEL_TRANSFORMATION: u16 = 34264;
+    pub const GEO_KEY_DIRECTORY: u16 = 34735;
+    pub const GDAL_NODATA: u16 = 42113;
+}
+
+/// Entry of the first image file directory of a TIFF
+#[derive(Clone, Copy)]
+struct TiffEntry {
+    field_type: u16,
+    count: usize,
+    /// Offset of the value, in the entry itself if it fits in 4 bytes
+    offset: usize,
+}
+
+/// Reads one value of a TIFF field from its bytes and the byte order
+type ReadValue = fn(&[u8], bool) -> f64;
+
+/// A classic (not BigTIFF) TIFF file
+struct Tiff<'a> {
+    bytes: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    fn slice(&self, offset: usize, len: usize) -> Result<&'a [u8]> {
+        offset
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(offset..end))
+            .ok_or_else(|| invalid("Truncated GeoTIFF".to_string()))
+    }
+
+    fn uint(&self, offset: usize, len: usize) -> Result<u64> {
+        Ok(read_uint(self.slice(offset, len)?, self.big_endian))
+    }
+
+    /// Reads the entries of the first image file directory
+    fn entries(&self) -> Result<Vec<(u16, TiffEntry)>> {
+        let ifd = self.uint(4, 4)? as usize;
+        let count = self.uint(ifd, 2)? as usize;
+        (0..count)
+            .map(|i| {
+                let pos = ifd + 2 + 12 * i;
+                let field_type = self.uint(pos + 2, 2)? as u16;
+                let count = self.uint(pos + 4, 4)? as usize;
+                let size = match field_type {
+                    1 | 2 | 6 | 7 => 1,
+                    3 | 8 => 2,
+                    4 | 9 | 11 => 4,
+                    5 | 10 | 12 => 8,
+                    _ => 0,
+                };
+                let offset = if size * count <= 4 {
+                    pos + 8
+                } else {
+                    self.uint(pos + 8, 4)? as usize
+                };
+                let entry = TiffEntry {
+                    field_type,
+                    count,
+                    offset,
+                };
+                Ok((self.uint(pos, 2)? as u16, entry))
+            })
+            .collect()
+    }
+
+    /// Reads the numeric values of an entry
+    fn values(&self, entry: TiffEntry) -> Result<Vec<f64>> {
+        let (size, read): (usize, ReadValue) = match entry.field_type {
+            1 | 7 => (1, |b, _| b[0] as f64),
+            6 => (1, |b, _| b[0] as i8 as f64),
+            3 => (2, |b, be| read_uint(b, be) as f64),
+            8 => (2, |b, be| read_uint(b, be) as u16 as i16 as f64),
+            4 => (4, |b, be| read_uint(b, be) as f64),
+            9 => (4, |b, be| read_uint(b, be) as u32 as i32 as f64),
+            11 => (4, |b, be| f32::from_bits(read_uint(b, be) as u32) as f64),
+            12 => (8, |b, be| f64::from_bits(read_uint(b, be))),
+            5 => (8, |b, be| {
+                read_uint(&b[..4], be) as f64 / read_uint(&b[4..], be) as f64
+            }),
+            10 => (8, |b, be| {
+                read_uint(&b[..4], be) as u32 as i32 as f64
+                    / read_uint(&b[4..], be) as u32 as i32 as f64
+            }),
+            t => return Err(invalid(format!("Unsupported GeoTIFF field type {t}"))),
+        };
+        let bytes = self.slice(entry.offset, size * entry.count)?;
+        Ok(bytes
+            .chunks_exact(size)
+            .map(|b| read(b, self.big_endian))
+            .collect())
+    }
+
+    /// Reads the text of an ASCII entry
+    fn ascii(&self, entry: TiffEntry) -> Result<String> {
+        let bytes = self.slice(entry.offset, entry.count)?;
+        Ok(String::from_utf8_lossy(bytes)
+            .trim_end_matches('\0')
+            .to_string())
+    }
+}
+
+/// Reads an unsigned integer of up to 8 bytes
+fn read_uint(bytes: &[u8], big_endian: bool) -> u64 {
+    let fold = |v: u64, b: &u8| (v << 8) | *b as u64;
+    if big_endian {
+        bytes.iter().fold(0, fold)
+    } else {
+        bytes.iter().rev().fold(0, fold)
+    }
+}
+
+/// Writes an unsigned integer of `bytes.len()` bytes
+fn write_uint(bytes: &mut [u8], value: u64, big_endian: bool) {
+    let len = bytes.len();
+    for (i, b) in bytes.iter_mut().enumerate() {
+        let shift = if big_endian { len - 1 - i } else { i };
+        *b = (value >> (8 * shift)) as u8;
+    }
+}
+
+/// Type of the samples of a GeoTIFF
+#[derive(Clone, Copy)]
+enum SampleType {
+    Unsigned(usize),
+    Signed(usize),
+    Float(usize),
+}
+
+impl SampleType {
+    fn new(bits: u64, format: u64) -> Result<Self> {
+        let size = match bits {
+            8 | 16 | 32 | 64 => bits as usize / 8,
+            _ => {
+                return Err(invalid(format!(
+                    "Unsupported GeoTIFF sample size of {bits} bits"
+                )))
+            }
+        };
+        match (format, size) {
+            (1, _) => Ok(SampleType::Unsigned(size)),
+            (2, _) => Ok(SampleType::Signed(size)),
+            (3, 4 | 8) => Ok(SampleType::Float(size)),
+            _ => Err(invalid(format!(
+                "Unsupported GeoTIFF sample format {format} of {bits} bits"
+            ))),
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            SampleType::Unsigned(size) | SampleType::Signed(size) | SampleType::Float(size) => size,
+        }
+    }
+
+    fn read(self, bytes: &[u8], big_endian: bool) -> f64 {
+        let value = read_uint(bytes, big_endian);
+        match self {
+            SampleType::Unsigned(_) => value as f64,
+            SampleType::Signed(size) => {
+                let shift = 64 - 8 * size as u32;
+                ((value << shift) as i64 >> shift) as f64
+            }
+            SampleType::Float(4) => f32::from_bits(value as u32) as f64,
+            SampleType::Float(_) => f64::from_bits(value),
+        }
+    }
+}
+
+/// Parses a single band GeoTIFF
+fn parse_geotiff(bytes: &[u8]) -> Result<Raster> {
+    let big_endian = match bytes.get(..2) {
+        Some(b"II") => false,
+        Some(b"MM") => true,
+        _ => return Err(invalid("Not a TIFF file".to_string())),
+    };
+    let tiff = Tiff { bytes, big_endian };
+    match tiff.uint(2, 2)? {
+        42 => {}
+        43 => return Err(invalid("BigTIFF rasters are not supported".to_string())),
+        magic => return Err(invalid(format!("Invalid TIFF version {magic}"))),
+    }
+    let entries = tiff.entries()?;
+    let find = |tag| entries.iter().find(|(t, _)| *t == tag).map(|(_, e)| *e);
+    let values = |tag| find(tag).map(|e| tiff.values(e)).transpose();
+    let first = |tag, default: Option<f64>| -> Result<f64> {
+        values(tag)?
+            .and_then(|v| v.first().copied())
+            .or(default)
+            .ok_or_else(|| invalid(format!("GeoTIFF is missing tag {tag}")))
+    };
+
+    let ncols = first(tag::IMAGE_WIDTH, None)? as usize;
+    let nrows = first(tag::IMAGE_LENGTH, None)? as usize;
+    let bands = first(tag::SAMPLES_PER_PIXEL, Some(1.0))?;
+    if bands != 1.0 {
+        return Err(invalid(format!("GeoTIFF has {bands} bands, expected one")));
+    }
+    let sample = SampleType::new(
+        first(tag::BITS_PER_SAMPLE, Some(1.0))? as u64,
+        first(tag::SAMPLE_FORMAT, Some(1.0))? as u64,
+    )?;
+    let compression = first(tag::COMPRESSION, Some(1.0))? as u64;
+    let predictor = first(tag::PREDICTOR, Some(1.0))? as u64;
+
+    // strips are blocks of whole rows
+    let (block_width, block_height, offsets, counts) = match find(tag::TILE_WIDTH) {
+        Some(_) => (
+            first(tag::TILE_WIDTH, None)? as usize,
+            first(tag::TILE_LENGTH, None)? as usize,
+            values(tag::TILE_OFFSETS)?,
+            values(tag::TILE_BYTE_COUNTS)?,
+        ),
+        None => (
+            ncols,
+            (first(tag::ROWS_PER_STRIP, Some(u32::MAX as f64))? as usize).min(nrows),
+            values(tag::STRIP_OFFSETS)?,
+            values(tag::STRIP_BYTE_COUNTS)?,
+        ),
+    };
+    let (Some(offsets), Some(counts)) = (offsets, counts) else {
+        return Err(invalid("GeoTIFF has no strip or tile offsets".to_string()));
+    };
+    if block_width == 0 || block_height == 0 {
+        return Err(invalid("GeoTIFF has empty strips or tiles".to_string()));
+    }
+    let blocks_across = ncols.div_ceil(block_width);
+    if offsets.len() != counts.len() || offsets.len() < blocks_across * nrows.div_ceil(block_height)
+    {
+        return Err(invalid("GeoTIFF is missing strips or tiles".to_string()));
+    }
+
+    let size = sample.size();
+    let mut values = vec![0.0; ncols * nrows];
+    for (block, (&offset, &count)) in offsets.iter().zip(&counts).enumerate() {
+        let (x0, y0) = (
+            (block % blocks_across) * block_width,
+            (block / blocks_across) * block_height,
+        );
+        if y0 >= nrows {
+            break;
+        }
+        // the last strip may have fewer rows, tiles are padded
+        let rows = if find(tag::TILE_WIDTH).is_some() {
+            block_height
+        } else {
+            block_height.min(nrows - y0)
+        };
+        let compressed = tiff.slice(offset as usize, count as usize)?;
+        let mut data = decompress(compressed, compression)?;
+        let row_len = block_width * size;
+        if data.len() < rows * row_len {
+            return Err(invalid("Truncated GeoTIFF strip or tile".to_string()));
+        }
+        for row in data.chunks_exact_mut(row_len).take(rows) {
+            match predictor {
+                1 => {}
+                2 => undo_horizontal_differencing(row, size, big_endian),
+                3 => undo_floating_point_differencing(row, size, big_endian),
+                _ => {
+                    return Err(invalid(format!(
+                        "Unsupported GeoTIFF predictor {predictor}"
+                    )))
+                }
+            }
+        }
+        for (r, row) in data.chunks_exact(row_len).take(rows).enumerate() {
+            let y = y0 + r;
+            if y >= nrows {
+                break;
+            }
+            for (c, bytes) in row.chunks_exact(size).enumerate() {
+                let x = x0 + c;
+                if x < ncols {
+                    values[y * ncols + x] = sample.read(bytes, big_endian);
+                }
+            }
+        }
+    }
+
+    if let Some(nodata) = find(tag::GDAL_NODATA) {
+        let nodata = parse_number(&tiff.ascii(nodata)?)?;
+        for v in values.iter_mut().filter(|v| **v == nodata) {
+            *v = 0.0;
+        }
+    }
+
+    let bbox = geotiff_bbox(&tiff, &find, ncols, nrows)?;
+    raster(ncols, nrows, values, bbox)
+}
+
+/// Returns the lon/lat extent of a GeoTIFF, or `None` if it has no
+/// georeference or is in projected coordinates
+fn geotiff_bbox(
+    tiff: &Tiff,
+    find: &dyn Fn(u16) -> Option<TiffEntry>,
+    ncols: usize,
+    nrows: usize,
+) -> Result<Option<[f64; 4]>> {
+    // GeoKeyDirectory: a header of 4 shorts, then (key, location, count, value)
+    let geo_keys = find(tag::GEO_KEY_DIRECTORY)
+        .map(|e| tiff.values(e))
+        .transpose()?
+        .unwrap_or_default();
+    let geo_key = |key: f64| {
+        geo_keys
+            .get(4..)
+            .unwrap_or_default()
+            .chunks_exact(4)
+            .find(|k| k[0] == key && k[1] == 0.0)
+            .map(|k| k[3])
+    };
+    // GTModelTypeGeoKey: 1 for projected coordinates
+    if geo_key(1024.0) == Some(1.0) {
+        return Ok(None);
+    }
+    // GTRasterTypeGeoKey: 2 when the tie point is the center of a pixel
+    let pixel_is_point = geo_key(1025.0) == Some(2.0);
+
+    let scale = find(tag::MODEL_PIXEL_SCALE)
+        .map(|e| tiff.values(e))
+        .transpose()?;
+    let tiepoint = find(tag::MODEL_TIEPOINT)
+        .map(|e| tiff.values(e))
+        .transpose()?;
+    let transformation = find(tag::MODEL_TRANSFORMATION)
+        .map(|e| tiff.values(e))
+        .transpose()?;
+    // origin of the north-west corner and cell size
+    let (mut west, mut north, dx, dy) = match (scale, tiepoint, transformation) {
+        (Some(s), Some(t), _) if s.len() >= 2 && t.len() >= 6 => {
+            (t[3] - t[0] * s[0], t[4] + t[1] * s[1], s[0], s[1])
+        }
+        (_, _, Some(m)) if m.len() >= 16 => {
+            if m[1] != 0.0 || m[4] != 0.0 {
+                return Err(invalid(
+                    "Rotated GeoTIFF rasters are not supported".to_string(),
+                ));
+            }
+            (m[3], m[7], m[0], -m[5])
+        }
+        _ => return Ok(None),
+    };
+    if pixel_is_point {
+        west -= dx / 2.0;
+        north += dy / 2.0;
+    }
+    Ok(Some([
+        west,
+        north - nrows as f64 * dy,
+        west + ncols as f64 * dx,
+        north,
+    ]))
+}
+
+/// Decompresses a strip or tile
+fn decompress(data: &[u8], compression: u64) -> Result<Vec<u8>> {
+    match compression {
+        1 => Ok(data.to_vec()),
+        5 => decode_lzw(data),
+        8 | 32946 => {
+            let mut out = Vec::new();
+            ZlibDecoder::new(data).read_to_end(&mut out)?;
+            Ok(out)
+        }
+        _ => Err(invalid(format!(
+            "Unsupported GeoTIFF compression {compression}. Expected none, LZW or Deflate"
+        ))),
+    }
+}
+
+/// Decodes TIFF LZW data: codes of 9 to 12 bits, most significant bit first,
+/// whose width grows one code early
+fn decode_lzw(data: &[u8]) -> Result<Vec<u8>> {
+    const CLEAR: usize = 256;
+    const END: usize = 257;
+    let mut out: Vec<u8> = Vec::with_capacity(data.len() * 2);
+    // strings of the codes from 258, as ranges of the output
+    let mut table: Vec<(usize, usize)> = Vec::with_capacity(4096 - 258);
+    let mut previous: Option<(usize, usize)> = None;
+    let (mut width, mut bit) = (9, 0);
+    while bit + width <= data.len() * 8 {
+        let code = (0..width).fold(0, |code, i| {
+            let b = bit + i;
+            (code << 1) | ((data[b / 8] >> (7 - b % 8)) & 1) as usize
+        });
+        bit += width;
+        match code {
+            CLEAR => {
+                table.clear();
+                width = 9;
+                previous = None;
+                continue;
+            }
+            END => break,
+            _ => {}
+        }
+
+        let start = out.len();
+        let len = if code < 256 {
+            out.push(code as u8);
+            1
+        } else if let Some(&(offset, len)) = table.get(code - 258) {
+            out.extend_from_within(offset..offset + len);
+            len
+        } else if let (true, Some((offset, len))) = (code - 258 == table.len(), previous) {
+            // the code being defined: the previous string and its first byte
+            out.extend_from_within(offset..offset + len);
+            out.push(out[offset]);
+            len + 1
+        } else {
+            return Err(invalid("Corrupt LZW data in GeoTIFF".to_string()));
+        };
+        // the previous string was written just before this one
+        if let Some((offset, len)) = previous {
+            if table.len() < 4096 - 258 {
+                table.push((offset, len + 1));
+            }
+        }
+        if 258 + table.len() + 1 >= 1 << width && width < 12 {
+            width += 1;
+        }
+        previous = Some((start, len));
+    }
+    Ok(out)
+}
+
+/// Undoes predictor 2: each sample of the row is stored as the difference to
+/// the previous one
+fn undo_horizontal_differencing(row: &mut [u8], size: usize, big_endian: bool) {
+    let mut previous = 0u64;
+    for sample in row.chunks_exact_mut(size) {
+        let value = read_uint(sample, big_endian).wrapping_add(previous);
+        write_uint(sample, value, big_endian);
+        previous = value;
+    }
+}
+
+/// Undoes predictor 3: the bytes of the row are stored as differences, with
+/// the most significant bytes of all samples first
+fn undo_floating_point_differencing(row: &mut [u8], size: usize, big_endian: bool) {
+    for i in 1..row.len() {
+        row[i] = row[i].wrapping_add(row[i - 1]);
+    }
+    let planes = row.to_vec();
+    let width = row.len() / size;
+    for (i, sample) in row.chunks_exact_mut(size).enumerate() {
+        for b in 0..size {
+            let byte = planes[b * width + i];
+            sample[if big_endian { b } else { size - 1 - b }] = byte;
+        }
+    }
+}
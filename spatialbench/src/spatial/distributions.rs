@@ -1,14 +1,230 @@
-use crate::spatial::cache::{HierThomasCache, ThomasCache};
-use crate::spatial::geometry::emit_geom;
+use crate::error::{Result, SpatialBenchError};
+use crate::spatial::cache::{DensityGridCache, HierThomasCache, HotspotCache, ThomasCache};
+use crate::spatial::density::load_density_grid;
+use crate::spatial::geometry::{emit_geom, emit_geom_at, emit_parcel};
 use crate::spatial::utils::{
-    build_cdf_from_weights, gauss_around, halton_2d, hash_to_unit_u64, pareto_draw, pick_from_cdf,
-    rand_normal, sample_normal_count, seeded_rng, spider_seed_for_index, u01_from_seed, unit_clamp,
+    apply_affine, build_cdf_from_weights, gauss_around, gauss_around_meters, halton_2d,
+    hash_to_unit_u64, meters_affine, pareto_draw, pick_from_cdf, rand_normal, sample_normal_count,
+    seeded_rng, spider_seed_for_index, u01_from_seed, unit_clamp,
+};
+use crate::spatial::{
+    CityModel, DistributionParams, DistributionType, SizeUnit, SpatialConfig, SpatialGenerator,
 };
-use crate::spatial::{DistributionParams, SpatialConfig};
 use geo::Geometry;
 use rand::rngs::StdRng;
 use rand::Rng;
-use std::sync::OnceLock;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+
+/// A distribution of the geometries of trips and buildings
+///
+/// The built-in distributions are implemented on top of this trait, and library
+/// users can plug their own, e.g. one derived from real data, into a
+/// [`SpatialGenerator`] with [`SpatialGenerator::with_distribution`]. Geometries
+/// must be a pure function of the record index, so every part of a table
+/// generates the same records.
+pub trait SpatialDistribution: fmt::Debug + Send + Sync {
+    /// Builds the caches of the distribution (e.g. cluster weights or a raster)
+    ///
+    /// Called once when the distribution is plugged into a generator, before it
+    /// is sampled, so invalid inputs are reported before generation.
+    fn prepare(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the geometry of the record with the given index
+    ///
+    /// `affine` (`[a, b, c, d, e, f]`) maps the unit square to the lon/lat
+    /// region the record is placed in, as `(a * x + b * y + c, d * x + e * y + f)`.
+    /// Distributions placing records at absolute locations may ignore it.
+    fn sample(&self, index: u64, affine: &[f64; 6]) -> Geometry;
+}
+
+/// Returns the built-in distribution of the config, which generates
+/// `cardinality` records (0 if unknown)
+pub fn builtin_distribution(
+    config: &SpatialConfig,
+    cardinality: u64,
+) -> Arc<dyn SpatialDistribution> {
+    let config = config.clone();
+    match config.dist_type {
+        DistributionType::Uniform => Arc::new(UniformDistribution { config }),
+        DistributionType::Normal => Arc::new(NormalDistribution { config }),
+        DistributionType::Diagonal => Arc::new(DiagonalDistribution { config }),
+        DistributionType::Bit => Arc::new(BitDistribution { config }),
+        DistributionType::Sierpinski => Arc::new(SierpinskiDistribution { config }),
+        DistributionType::Thomas => Arc::new(ThomasDistribution {
+            config,
+            cache: OnceLock::new(),
+        }),
+        DistributionType::HierarchicalThomas | DistributionType::CityModel => {
+            Arc::new(HierarchicalThomasDistribution {
+                config,
+                cache: OnceLock::new(),
+            })
+        }
+        DistributionType::Parcel => Arc::new(ParcelDistribution {
+            config,
+            cardinality,
+        }),
+        DistributionType::DensityGrid => Arc::new(DensityGridDistribution {
+            config,
+            cache: OnceLock::new(),
+        }),
+        DistributionType::Hotspot => Arc::new(HotspotDistribution {
+            config,
+            cache: OnceLock::new(),
+        }),
+    }
+}
+
+/// Defines a built-in distribution without caches, sampled by `$GENERATE`
+macro_rules! define_distribution {
+    ($(#[$DOC:meta])* $NAME:ident, $GENERATE:ident) => {
+        $(#[$DOC])*
+        #[derive(Debug, Clone)]
+        pub struct $NAME {
+            config: SpatialConfig,
+        }
+
+        impl SpatialDistribution for $NAME {
+            fn sample(&self, index: u64, affine: &[f64; 6]) -> Geometry {
+                $GENERATE(index, &self.config, affine)
+            }
+        }
+    };
+}
+
+define_distribution!(
+    /// Uniformly distributed geometries in the unit square
+    UniformDistribution,
+    generate_uniform
+);
+define_distribution!(
+    /// 2D Gaussian around `mu`, clamped to the unit square
+    NormalDistribution,
+    generate_normal
+);
+define_distribution!(
+    /// Geometries on and around the diagonal of the unit square
+    DiagonalDistribution,
+    generate_diagonal
+);
+define_distribution!(
+    /// Geometries on a grid of recursive binary subdivisions
+    BitDistribution,
+    generate_bit
+);
+define_distribution!(
+    /// Geometries on the Sierpinski triangle
+    SierpinskiDistribution,
+    generate_sierpinski
+);
+
+/// Gaussian clusters around Pareto-weighted parents
+#[derive(Debug)]
+pub struct ThomasDistribution {
+    config: SpatialConfig,
+    cache: OnceLock<ThomasCache>,
+}
+
+impl SpatialDistribution for ThomasDistribution {
+    fn prepare(&self) -> Result<()> {
+        let (parents, _sigma, alpha, xm) = thomas_params(&self.config);
+        get_or_create_thomas_cache(&self.cache, parents, alpha, xm, self.config.seed as u64);
+        Ok(())
+    }
+
+    fn sample(&self, index: u64, affine: &[f64; 6]) -> Geometry {
+        generate_thomas(index, &self.config, &self.cache, affine)
+    }
+}
+
+/// Gaussian subclusters of Pareto-weighted cities, of its own or of a shared
+/// [`CityModel`]
+#[derive(Debug)]
+pub struct HierarchicalThomasDistribution {
+    config: SpatialConfig,
+    cache: OnceLock<HierThomasCache>,
+}
+
+impl SpatialDistribution for HierarchicalThomasDistribution {
+    fn prepare(&self) -> Result<()> {
+        let (model, _) = CityModel::of(&self.config).ok_or_else(|| {
+            SpatialBenchError::InvalidConfig(format!(
+                "Expected HierThomas params, got {:?}",
+                self.config.params
+            ))
+        })?;
+        city_model_cache(&model, &self.cache);
+        Ok(())
+    }
+
+    fn sample(&self, index: u64, affine: &[f64; 6]) -> Geometry {
+        generate_hierarchical_thomas(index, &self.config, &self.cache, affine)
+    }
+}
+
+/// One non-overlapping parcel of the unit square per record
+#[derive(Debug, Clone)]
+pub struct ParcelDistribution {
+    config: SpatialConfig,
+    cardinality: u64,
+}
+
+impl SpatialDistribution for ParcelDistribution {
+    fn prepare(&self) -> Result<()> {
+        if self.cardinality == 0 {
+            return Err(SpatialBenchError::InvalidConfig(
+                "The parcel distribution needs the number of records to split the unit square into"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn sample(&self, index: u64, affine: &[f64; 6]) -> Geometry {
+        generate_parcel(index, &self.config, self.cardinality, affine)
+    }
+}
+
+/// Cells of a lon/lat raster, picked by weight
+#[derive(Debug)]
+pub struct DensityGridDistribution {
+    config: SpatialConfig,
+    cache: OnceLock<Arc<DensityGridCache>>,
+}
+
+impl SpatialDistribution for DensityGridDistribution {
+    fn prepare(&self) -> Result<()> {
+        load_density_cache(&self.cache, &self.config).map(|_| ())
+    }
+
+    fn sample(&self, index: u64, _affine: &[f64; 6]) -> Geometry {
+        let cache = self
+            .cache
+            .get()
+            .expect("the density grid is loaded when the distribution is prepared");
+        generate_density_grid(index, &self.config, cache)
+    }
+}
+
+/// Gaussian clusters around lon/lat centers, with an optional background
+#[derive(Debug)]
+pub struct HotspotDistribution {
+    config: SpatialConfig,
+    cache: OnceLock<Arc<HotspotCache>>,
+}
+
+impl SpatialDistribution for HotspotDistribution {
+    fn prepare(&self) -> Result<()> {
+        load_hotspot_cache(&self.cache, &self.config).map(|_| ())
+    }
+
+    fn sample(&self, index: u64, affine: &[f64; 6]) -> Geometry {
+        generate_hotspot(index, &self.config, &self.cache, affine)
+    }
+}
 
 pub fn generate_uniform(index: u64, config: &SpatialConfig, m: &[f64; 6]) -> Geometry {
     let seed = spider_seed_for_index(index, config.seed as u64);
@@ -97,30 +313,208 @@ pub fn generate_sierpinski(index: u64, config: &SpatialConfig, m: &[f64; 6]) ->
     emit_geom((x, y), config.geom_type, config, &mut rng, m)
 }
 
-pub fn generate_thomas(
+/// Parcel distribution: the unit square is recursively split into `cardinality`
+/// non-overlapping boxes, as in Spider's parcel generator
+///
+/// Boxes are split breadth first, each along its longer side at a random
+/// fraction in `[srange, 1 - srange]`, until there are as many boxes as records.
+/// In that split tree the parcels are the nodes `n - 1..2n - 1` of a binary heap,
+/// so the box of the record of key `index` (from 1) is found by walking from the
+/// root to its node, without generating the other parcels. Each box is then
+/// shrunk by a random fraction of up to `dither` in each dimension.
+///
+/// Panics if `index` is not in `1..=cardinality`: the tables generate one
+/// record per parcel.
+pub fn generate_parcel(
     index: u64,
     config: &SpatialConfig,
-    thomas_cache: &OnceLock<ThomasCache>,
+    cardinality: u64,
     m: &[f64; 6],
 ) -> Geometry {
-    let (parents, _mean_offspring, sigma, alpha, xm) = match config.params {
-        DistributionParams::Thomas {
-            parents,
-            mean_offspring,
-            sigma,
-            pareto_alpha,
-            pareto_xm,
-        } => (
-            parents.max(1),
-            mean_offspring.max(1e-9),
-            sigma.max(1e-6),
-            pareto_alpha.max(1e-6),
-            pareto_xm.max(1e-12),
-        ),
-        _ => panic!("Expected Thomas params, got {:?}", config.params),
+    let (srange, dither) = match config.params {
+        DistributionParams::Parcel { srange, dither } => (srange, dither),
+        _ => panic!("Expected Parcel params, got {:?}", config.params),
+    };
+    assert!(
+        (1..=cardinality).contains(&index),
+        "Parcel of record {index} requested, but the unit square is split into {cardinality}"
+    );
+    let node = cardinality - 1 + (index - 1);
+
+    // the splits from the node up to the root, with the side (lower or upper
+    // part) of the split box the node is in; a heap of u64 nodes is at most 64
+    // levels deep
+    let mut sides = [(0, false); 64];
+    let mut depth = 0;
+    let mut child = node;
+    while child > 0 {
+        let parent = (child - 1) / 2;
+        sides[depth] = (parent, child == 2 * parent + 2);
+        depth += 1;
+        child = parent;
+    }
+
+    let (mut x0, mut y0, mut x1, mut y1) = (0.0, 0.0, 1.0, 1.0);
+    for &(parent, upper) in sides[..depth].iter().rev() {
+        let u = hash_to_unit_u64(parent, (config.seed as u64) ^ 0x9A2CE1);
+        let split = srange + u * (1.0 - 2.0 * srange);
+        if x1 - x0 >= y1 - y0 {
+            let x = x0 + split * (x1 - x0);
+            if upper {
+                x0 = x;
+            } else {
+                x1 = x;
+            }
+        } else {
+            let y = y0 + split * (y1 - y0);
+            if upper {
+                y0 = y;
+            } else {
+                y1 = y;
+            }
+        }
+    }
+
+    let mut rng = seeded_rng(spider_seed_for_index(index, config.seed as u64));
+    let (cx, cy) = ((x0 + x1) / 2.0, (y0 + y1) / 2.0);
+    let half_width = (x1 - x0) / 2.0 * (1.0 - dither * rng.gen::<f64>());
+    let half_height = (y1 - y0) / 2.0 * (1.0 - dither * rng.gen::<f64>());
+    emit_parcel((cx, cy), (half_width, half_height), config, &mut rng, m)
+}
+
+/// Density grid distribution: cells of a lon/lat raster are picked with
+/// probability proportional to their weight, and points are spread uniformly
+/// within the cell
+///
+/// Points are placed in the extent of the raster, not in a continent. Box and
+/// polygon sizes are fractions of the raster extent.
+pub fn generate_density_grid(
+    index: u64,
+    config: &SpatialConfig,
+    cache: &DensityGridCache,
+) -> Geometry {
+    let u = hash_to_unit_u64(index, (config.seed as u64) ^ 0xDE45_1717);
+    let cell = cache.cells[pick_from_cdf(&cache.cdf, u)];
+    let (row, col) = (cell / cache.ncols, cell % cache.ncols);
+
+    let mut rng = seeded_rng(spider_seed_for_index(index, config.seed as u64));
+    let center = (
+        (col as f64 + rng.gen::<f64>()) / cache.ncols as f64,
+        (row as f64 + rng.gen::<f64>()) / cache.nrows as f64,
+    );
+    emit_geom(center, config.geom_type, config, &mut rng, &cache.affine)
+}
+
+/// Returns the raster of a density grid, loading it on first use
+pub fn load_density_cache<'a>(
+    density_cache: &'a OnceLock<Arc<DensityGridCache>>,
+    config: &SpatialConfig,
+) -> Result<&'a DensityGridCache> {
+    if density_cache.get().is_none() {
+        let DistributionParams::DensityGrid { path, bbox } = &config.params else {
+            panic!("Expected DensityGrid params, got {:?}", config.params);
+        };
+        let _ = density_cache.set(Arc::new(load_density_grid(path, *bbox)?));
+    }
+    Ok(density_cache.get().expect("density grid was just loaded"))
+}
+
+/// Hotspot distribution: a mixture of Gaussian clusters around lon/lat centers,
+/// optionally combined with a background distribution
+///
+/// A point is drawn from the background with its share of the total weight, and
+/// otherwise from a component picked by weight, at a Gaussian offset in
+/// kilometers from its center. Points of components are placed around the
+/// center, not in a continent; box and polygon sizes are fractions of a square
+/// of six standard deviations, unless they are in meters.
+///
+/// The first keys, starting at 1, are reserved for one point of each component
+/// in the order they are listed, so every hotspot has data at any scale factor.
+pub fn generate_hotspot(
+    index: u64,
+    config: &SpatialConfig,
+    hotspot_cache: &OnceLock<Arc<HotspotCache>>,
+    m: &[f64; 6],
+) -> Geometry {
+    let cache = load_hotspot_cache(hotspot_cache, config)
+        .unwrap_or_else(|e| panic!("Failed to load hotspot background: {e}"));
+    let DistributionParams::Hotspot { components, .. } = &config.params else {
+        panic!("Expected Hotspot params, got {:?}", config.params);
+    };
+
+    let reserved = index
+        .checked_sub(1)
+        .and_then(|i| components.get(usize::try_from(i).ok()?));
+    let component = match reserved {
+        Some(component) => component,
+        None => {
+            if let Some(background) = &cache.background {
+                if hash_to_unit_u64(index, (config.seed as u64) ^ 0xB4C6_0123)
+                    < cache.background_share
+                {
+                    return background.generate(index, m);
+                }
+            }
+            let u = hash_to_unit_u64(index, (config.seed as u64) ^ 0x4075_9071);
+            &components[pick_from_cdf(&cache.cdf, u)]
+        }
     };
 
-    let k = parents as usize;
+    let mut rng = seeded_rng(spider_seed_for_index(index, config.seed as u64));
+    let sigma = component.sigma_km * 1000.0;
+    let (lon, lat) = gauss_around_meters(&mut rng, (component.lon, component.lat), sigma);
+
+    // the unit square is centered on the point
+    let affine = meters_affine(lon, lat, 6.0 * sigma);
+    emit_geom((0.5, 0.5), config.geom_type, config, &mut rng, &affine)
+}
+
+/// Returns the component weights and background generator of a hotspot
+/// distribution, building them on first use
+pub fn load_hotspot_cache<'a>(
+    hotspot_cache: &'a OnceLock<Arc<HotspotCache>>,
+    config: &SpatialConfig,
+) -> Result<&'a HotspotCache> {
+    if hotspot_cache.get().is_none() {
+        let DistributionParams::Hotspot {
+            components,
+            background,
+        } = &config.params
+        else {
+            panic!("Expected Hotspot params, got {:?}", config.params);
+        };
+        let weights: Vec<f64> = components.iter().map(|c| c.weight).collect();
+        let total: f64 = weights.iter().sum();
+        let (background_share, background) = match background {
+            Some(background) => {
+                let generator = SpatialGenerator::try_new(SpatialConfig {
+                    dist_type: background.dist_type,
+                    params: background.params.clone(),
+                    ..config.clone()
+                })?;
+                (
+                    background.weight / (background.weight + total),
+                    Some(generator),
+                )
+            }
+            None => (0.0, None),
+        };
+        let _ = hotspot_cache.set(Arc::new(HotspotCache {
+            cdf: build_cdf_from_weights(weights),
+            background_share,
+            background,
+        }));
+    }
+    Ok(hotspot_cache.get().expect("hotspot cache was just built"))
+}
+
+pub fn generate_thomas(
+    index: u64,
+    config: &SpatialConfig,
+    thomas_cache: &OnceLock<ThomasCache>,
+    m: &[f64; 6],
+) -> Geometry {
+    let (k, sigma, alpha, xm) = thomas_params(config);
     let u = hash_to_unit_u64(index, (config.seed as u64) ^ 0xBADD_F00D);
 
     let pid = match thomas_cache.get() {
@@ -147,11 +541,35 @@ pub fn generate_thomas(
         index,
         (config.seed as u64) ^ 0xC177001,
     ));
+    if config.units == SizeUnit::Meters {
+        let center = gauss_around_meters(&mut rng, apply_affine(cx, cy, m), sigma);
+        return emit_geom_at(center, config.geom_type, config, &mut rng);
+    }
     let center = gauss_around(&mut rng, (cx, cy), sigma);
 
     emit_geom(center, config.geom_type, config, &mut rng, m)
 }
 
+/// Returns the number of parents, sigma and Pareto weights of a Thomas
+/// distribution
+fn thomas_params(config: &SpatialConfig) -> (usize, f64, f64, f64) {
+    match config.params {
+        DistributionParams::Thomas {
+            parents,
+            sigma,
+            pareto_alpha,
+            pareto_xm,
+            ..
+        } => (
+            parents.max(1) as usize,
+            sigma.max(1e-6),
+            pareto_alpha.max(1e-6),
+            pareto_xm.max(1e-12),
+        ),
+        _ => panic!("Expected Thomas params, got {:?}", config.params),
+    }
+}
+
 #[inline]
 fn pick_parent_pareto_once(u: f64, k: usize, alpha: f64, xm: f64, seed: u64) -> usize {
     let mut weights = Vec::with_capacity(k);
@@ -169,49 +587,11 @@ pub fn generate_hierarchical_thomas(
     hier_cache: &OnceLock<HierThomasCache>,
     m: &[f64; 6],
 ) -> Geometry {
-    let (nc, sub_mean, sub_sd, sub_min, sub_max, sigma_city, sigma_sub, a_c, xm_c, a_s, xm_s) =
-        match config.params {
-            DistributionParams::HierarchicalThomas {
-                cities,
-                sub_mean,
-                sub_sd,
-                sub_min,
-                sub_max,
-                sigma_city,
-                sigma_sub,
-                pareto_alpha_city,
-                pareto_xm_city,
-                pareto_alpha_sub,
-                pareto_xm_sub,
-            } => (
-                cities.max(1),
-                sub_mean,
-                sub_sd,
-                sub_min,
-                sub_max,
-                sigma_city.max(1e-6),
-                sigma_sub.max(1e-6),
-                pareto_alpha_city.max(1e-6),
-                pareto_xm_city.max(1e-12),
-                pareto_alpha_sub.max(1e-6),
-                pareto_xm_sub.max(1e-12),
-            ),
-            _ => panic!("Expected HierThomas params, got {:?}", config.params),
-        };
-
-    let cache = get_or_create_hier_cache(
-        hier_cache,
-        nc as usize,
-        sub_mean,
-        sub_sd,
-        sub_min,
-        sub_max,
-        a_c,
-        xm_c,
-        a_s,
-        xm_s,
-        config.seed as u64,
-    );
+    let Some((model, sigma_sub)) = CityModel::of(config) else {
+        panic!("Expected HierThomas params, got {:?}", config.params);
+    };
+    let (sigma_city, sigma_sub) = (model.sigma_city.max(1e-6), sigma_sub.max(1e-6));
+    let cache = city_model_cache(&model, hier_cache);
 
     let u_city = hash_to_unit_u64(index, (config.seed as u64) ^ 0xC17C1CF);
     let u_sub = hash_to_unit_u64(index, (config.seed as u64) ^ 0x53BFACE);
@@ -222,16 +602,42 @@ pub fn generate_hierarchical_thomas(
     let (cx, cy) = halton_2d(city_id as u64 + 1, 2, 3);
     let mut rng_sub = seeded_rng(spider_seed_for_index(
         ((city_id as u64) << 32) | (sub_id as u64),
-        (config.seed as u64) ^ 0x0C17_35FB,
+        (model.seed as u64) ^ 0x0C17_35FB,
     ));
-    let (sx, sy) = gauss_around(&mut rng_sub, (cx, cy), sigma_city);
-
     let mut rng_pt = seeded_rng(spider_seed_for_index(index, (config.seed as u64) ^ 0xF136D));
+
+    if config.units == SizeUnit::Meters {
+        let sub = gauss_around_meters(&mut rng_sub, apply_affine(cx, cy, m), sigma_city);
+        let center = gauss_around_meters(&mut rng_pt, sub, sigma_sub);
+        return emit_geom_at(center, config.geom_type, config, &mut rng_pt);
+    }
+    let (sx, sy) = gauss_around(&mut rng_sub, (cx, cy), sigma_city);
     let center = gauss_around(&mut rng_pt, (sx, sy), sigma_sub);
 
     emit_geom(center, config.geom_type, config, &mut rng_pt, m)
 }
 
+/// Returns the city and subcluster weights of a city model, building them on
+/// first use
+fn city_model_cache<'a>(
+    model: &CityModel,
+    hier_cache: &'a OnceLock<HierThomasCache>,
+) -> &'a HierThomasCache {
+    get_or_create_hier_cache(
+        hier_cache,
+        model.cities.max(1) as usize,
+        model.sub_mean,
+        model.sub_sd,
+        model.sub_min,
+        model.sub_max,
+        model.pareto_alpha_city.max(1e-6),
+        model.pareto_xm_city.max(1e-12),
+        model.pareto_alpha_sub.max(1e-6),
+        model.pareto_xm_sub.max(1e-12),
+        model.seed as u64,
+    )
+}
+
 fn get_or_create_thomas_cache(
     thomas_cache: &OnceLock<ThomasCache>,
     parents: usize,
@@ -0,0 +1,46 @@
+//! Synthetic terrain and building heights for 3D geometries
+//!
+//! Geometries generated with `dim: 3` get the ground elevation at their location
+//! as Z coordinate, and buildings get a number of floors and a height. Both are
+//! derived deterministically, from the location and the building key, so every
+//! part and every run generates the same values.
+
+use crate::spatial::utils::{hash_to_unit_u64, pareto_draw};
+
+/// Salts of the floor count and roof height draws of a building
+const FLOORS_SALT: u64 = 0xF1_0025;
+const ROOF_SALT: u64 = 0x200F_0026;
+
+/// Most floors of a building
+pub const MAX_FLOORS: i32 = 120;
+
+/// Height of a floor, in meters
+const FLOOR_HEIGHT: f64 = 3.2;
+
+/// Ground elevation in meters at the given lon/lat, rounded to centimeters
+///
+/// The terrain is a smooth sum of waves between sea level and about 1,900 m, with
+/// broad plateaus and local hills; it is not a real elevation model.
+pub fn ground_elevation(lon: f64, lat: f64) -> f64 {
+    let (x, y) = (lon.to_radians(), lat.to_radians());
+    let elevation = 700.0 * (1.0 + (3.0 * x + 1.3).sin() * (2.0 * y).cos())
+        + 350.0 * (11.0 * x - 7.0 * y + 0.4).sin()
+        + 120.0 * (37.0 * x + 29.0 * y + 2.1).sin();
+    (elevation.max(0.0) * 100.0).round() / 100.0
+}
+
+/// Number of floors of the building with the given key, from 1 to [`MAX_FLOORS`]
+///
+/// Floors follow a Pareto distribution, so most buildings are low and a few are
+/// towers.
+pub fn building_floors(building_key: u64) -> i32 {
+    let u = hash_to_unit_u64(building_key, FLOORS_SALT);
+    (pareto_draw(u, 1.5, 1.0).floor() as i32).clamp(1, MAX_FLOORS)
+}
+
+/// Height in meters of a building with `floors` floors, rounded to centimeters,
+/// including a roof of up to 3 m
+pub fn building_height(building_key: u64, floors: i32) -> f64 {
+    let roof = 3.0 * hash_to_unit_u64(building_key, ROOF_SALT);
+    ((floors as f64 * FLOOR_HEIGHT + roof) * 100.0).round() / 100.0
+}
@@ -1,47 +1,128 @@
-use crate::spatial::cache::{HierThomasCache, ThomasCache};
-use crate::spatial::distributions::*;
-use crate::spatial::{DistributionType, SpatialConfig};
+use crate::error::Result;
+use crate::spatial::distributions::{builtin_distribution, SpatialDistribution};
+use crate::spatial::{DistributionType, SpatialConfig, Validity};
 use geo::Geometry;
-use std::sync::OnceLock;
+use std::sync::Arc;
 
 #[derive(Clone, Debug)]
 pub struct SpatialGenerator {
-    pub config: SpatialConfig,
-    pub thomas_cache: OnceLock<ThomasCache>,
-    pub hier_cache: OnceLock<HierThomasCache>,
+    /// Configuration of the built-in distribution; the table generators also
+    /// read its `dim` and `validity`, including with a custom distribution
+    config: SpatialConfig,
+    /// Number of records generated, which the parcel distribution splits the
+    /// unit square into (0 if unknown, which the parcel distribution rejects)
+    cardinality: u64,
+    /// Distribution sampled, with its caches shared by the clones of the
+    /// generator
+    distribution: Arc<dyn SpatialDistribution>,
+    /// Whether the distribution was plugged in with [`Self::with_distribution`]
+    /// instead of built from the config
+    custom: bool,
 }
 
 impl SpatialGenerator {
-    pub fn new(
-        config: SpatialConfig,
-        thomas_cache: OnceLock<ThomasCache>,
-        hier_cache: OnceLock<HierThomasCache>,
-    ) -> Self {
+    /// Creates a generator for the given configuration
+    ///
+    /// Panics if the configuration is invalid or its distribution cannot be
+    /// prepared, e.g. a missing density grid file; see [`Self::try_new`].
+    pub fn new(config: SpatialConfig) -> Self {
+        Self::try_new(config).unwrap_or_else(|e| panic!("Invalid spatial config: {e}"))
+    }
+
+    fn build(config: SpatialConfig) -> Self {
         Self {
+            distribution: builtin_distribution(&config, 0),
             config,
-            thomas_cache,
-            hier_cache,
+            cardinality: 0,
+            custom: false,
         }
     }
 
-    pub fn generate(&self, index: u64, continent_affine: &[f64; 6]) -> Geometry {
-        match self.config.dist_type {
-            DistributionType::Uniform => generate_uniform(index, &self.config, continent_affine),
-            DistributionType::Normal => generate_normal(index, &self.config, continent_affine),
-            DistributionType::Diagonal => generate_diagonal(index, &self.config, continent_affine),
-            DistributionType::Bit => generate_bit(index, &self.config, continent_affine),
-            DistributionType::Sierpinski => {
-                generate_sierpinski(index, &self.config, continent_affine)
-            }
-            DistributionType::Thomas => {
-                generate_thomas(index, &self.config, &self.thomas_cache, continent_affine)
-            }
-            DistributionType::HierarchicalThomas => generate_hierarchical_thomas(
-                index,
-                &self.config,
-                &self.hier_cache,
-                continent_affine,
-            ),
+    /// Set the number of records generated
+    pub fn with_cardinality(mut self, cardinality: u64) -> Self {
+        self.cardinality = cardinality;
+        if !self.custom && matches!(self.config.dist_type, DistributionType::Parcel) {
+            self.distribution = builtin_distribution(&self.config, cardinality);
+        }
+        self
+    }
+
+    /// Set the validity guarantees of the generated geometries, see [`Validity`],
+    /// or returns the error preparing the rebuilt distribution
+    ///
+    /// The built-in distribution is rebuilt for the new validity, so its caches
+    /// are prepared again.
+    pub fn with_validity(mut self, validity: Validity) -> Result<Self> {
+        if self.config.validity == validity {
+            return Ok(self);
         }
+        self.config.validity = validity;
+        if !self.custom {
+            let distribution = builtin_distribution(&self.config, self.cardinality);
+            distribution.prepare()?;
+            self.distribution = distribution;
+        }
+        Ok(self)
+    }
+
+    /// Replaces the distribution sampled by a custom one, or returns the error
+    /// of its [`SpatialDistribution::prepare`]
+    ///
+    /// The config still sets the dimension and validity mode of the tables.
+    pub fn with_distribution(mut self, distribution: Box<dyn SpatialDistribution>) -> Result<Self> {
+        distribution.prepare()?;
+        self.distribution = Arc::from(distribution);
+        self.custom = true;
+        Ok(self)
+    }
+
+    /// Creates a generator for the given configuration, or the error if its
+    /// parameters do not match the distribution type
+    ///
+    /// The caches of the distribution are prepared here, so e.g. a missing or
+    /// invalid density grid file is reported instead of failing during
+    /// generation. The parcel distribution needs the number of records, see
+    /// [`Self::try_new_with_cardinality`].
+    pub fn try_new(config: SpatialConfig) -> Result<Self> {
+        Self::try_new_with_cardinality(config, 0)
+    }
+
+    /// Creates a generator for the given configuration that generates
+    /// `cardinality` records, or the error if its parameters do not match the
+    /// distribution type
+    pub fn try_new_with_cardinality(config: SpatialConfig, cardinality: u64) -> Result<Self> {
+        config.validate()?;
+        let generator = Self::build(config).with_cardinality(cardinality);
+        generator.distribution.prepare()?;
+        Ok(generator)
+    }
+
+    /// Whether the built-in parcel distribution is sampled, where each key owns
+    /// one parcel of the unit square
+    pub fn is_parcel(&self) -> bool {
+        !self.custom && matches!(self.config.dist_type, DistributionType::Parcel)
+    }
+
+    /// Configuration of the generator, which is validated and whose
+    /// distribution is prepared when the generator is built
+    ///
+    /// To change it, build a new generator from a modified copy, e.g.
+    /// `SpatialGenerator::try_new(SpatialConfig { dim: 3, ..generator.config().clone() })`.
+    pub fn config(&self) -> &SpatialConfig {
+        &self.config
+    }
+
+    /// Number of records generated, see [`Self::with_cardinality`]
+    pub fn cardinality(&self) -> u64 {
+        self.cardinality
+    }
+
+    /// Whether the distribution was plugged in with [`Self::with_distribution`]
+    pub fn is_custom(&self) -> bool {
+        self.custom
+    }
+
+    pub fn generate(&self, index: u64, continent_affine: &[f64; 6]) -> Geometry {
+        self.distribution.sample(index, continent_affine)
     }
 }
@@ -1,11 +1,27 @@
-use crate::spatial::utils::{apply_affine, round_coordinates};
-use crate::spatial::{GeomType, SpatialConfig};
-use geo::{coord, Geometry, LineString, Point, Polygon};
+use crate::spatial::utils::{
+    apply_affine, meters_affine, meters_to_degrees, rand_normal, round_coordinates,
+};
+use crate::spatial::{
+    DistributionParams, FootprintOptions, GeomType, PolygonStyle, SizeUnit, SpatialConfig, Validity,
+};
+use geo::orient::Direction;
+use geo::{
+    coord, Area, BoundingRect, Coord, Geometry, IsConvex, LineString, MultiPolygon, Orient, Point,
+    Polygon, Validation,
+};
 use rand::rngs::StdRng;
 use rand::Rng;
 use std::f64::consts::PI;
 
-const GEOMETRY_PRECISION: f64 = 1_000_000_000.0;
+pub const GEOMETRY_PRECISION: f64 = 1_000_000_000.0;
+
+/// Smallest area of a polygon in strict validity mode, in squared units of the
+/// region coordinates (square degrees for lon/lat regions)
+pub const MIN_POLYGON_AREA: f64 = 1e-12;
+
+/// Half side of the square replacing a polygon that collapses in strict validity
+/// mode, large enough for [`MIN_POLYGON_AREA`]
+const FALLBACK_HALF_SIDE: f64 = 1e-6;
 
 pub fn emit_geom(
     center01: (f64, f64),
@@ -14,10 +30,109 @@ pub fn emit_geom(
     rng: &mut StdRng,
     m: &[f64; 6],
 ) -> Geometry {
+    if config.units == SizeUnit::Meters {
+        return emit_geom_at(
+            apply_affine(center01.0, center01.1, m),
+            geom_type,
+            config,
+            rng,
+        );
+    }
     match geom_type {
         GeomType::Point => generate_point_geom(center01, m),
         GeomType::Box => generate_box_geom(center01, config, rng, m),
         GeomType::Polygon => generate_polygon_geom(center01, config, rng, m),
+        GeomType::LineString => generate_linestring_geom(center01, config, rng, m),
+    }
+}
+
+/// Emits a geometry centered on a lon/lat, with its sizes in meters
+///
+/// The geometry is generated in a square of meters centered on the point, large
+/// enough to hold it, which is converted to degrees at its latitude.
+pub fn emit_geom_at(
+    (lon, lat): (f64, f64),
+    geom_type: GeomType,
+    config: &SpatialConfig,
+    rng: &mut StdRng,
+) -> Geometry {
+    let walk = config.polysize * config.maxseg.max(1) as f64;
+    let extent = 4.0 * config.width.max(config.height).max(walk).max(1.0);
+    let local = SpatialConfig {
+        dist_type: config.dist_type,
+        geom_type,
+        dim: config.dim,
+        seed: config.seed,
+        width: config.width / extent,
+        height: config.height / extent,
+        maxseg: config.maxseg,
+        polysize: config.polysize / extent,
+        polygon_style: config.polygon_style,
+        params: DistributionParams::None,
+        validity: config.validity,
+        units: SizeUnit::Unit,
+    };
+    emit_geom(
+        (0.5, 0.5),
+        geom_type,
+        &local,
+        rng,
+        &meters_affine(lon, lat, extent),
+    )
+}
+
+/// Emits the geometry of a parcel with the given center and half extents
+///
+/// Boxes fill the parcel; polygons have their vertices on the ellipse inscribed
+/// in it, so neither extends into a neighbouring parcel.
+pub fn emit_parcel(
+    center01: (f64, f64),
+    half_extents: (f64, f64),
+    config: &SpatialConfig,
+    rng: &mut StdRng,
+    m: &[f64; 6],
+) -> Geometry {
+    match config.geom_type {
+        // rejected by `SpatialConfig::validate`
+        GeomType::Point | GeomType::LineString => panic!(
+            "Parcel distribution generates box or polygon geometries, not {:?}",
+            config.geom_type
+        ),
+        GeomType::Box => match config.validity {
+            Validity::Raw => box_geom(center01, half_extents, m),
+            Validity::Strict => strict_polygon(&box_ring(center01, half_extents)[..4], center01, m),
+        },
+        GeomType::Polygon if config.validity == Validity::Strict => {
+            let ring: Vec<_> = strict_polygon_angles(config, rng)
+                .iter()
+                .map(|&ang| {
+                    (
+                        center01.0 + half_extents.0 * ang.cos(),
+                        center01.1 + half_extents.1 * ang.sin(),
+                    )
+                })
+                .collect();
+            strict_polygon(&ring, center01, m)
+        }
+        GeomType::Polygon => {
+            let angles = polygon_angles(config, rng);
+            let mut ring = angles
+                .iter()
+                .map(|&ang| {
+                    let (x, y) = apply_affine(
+                        center01.0 + half_extents.0 * ang.cos(),
+                        center01.1 + half_extents.1 * ang.sin(),
+                        m,
+                    );
+                    let (xr, yr) = round_coordinates(x, y, GEOMETRY_PRECISION);
+                    coord! { x: xr, y: yr }
+                })
+                .collect::<Vec<_>>();
+            if let Some(first) = ring.first().copied() {
+                ring.push(first);
+            }
+            Geometry::Polygon(Polygon::new(LineString::from(ring), vec![]))
+        }
     }
 }
 
@@ -33,18 +148,30 @@ pub fn generate_box_geom(
     rng: &mut StdRng,
     m: &[f64; 6],
 ) -> Geometry {
+    if config.validity == Validity::Strict {
+        // at least a tenth of the configured size, so boxes never collapse
+        let half_width = (0.1 + 0.9 * rng.gen::<f64>()) * config.width / 2.0;
+        let half_height = (0.1 + 0.9 * rng.gen::<f64>()) * config.height / 2.0;
+        return strict_polygon(&box_ring(center, (half_width, half_height))[..4], center, m);
+    }
     let half_width = rng.gen::<f64>() * config.width / 2.0;
     let half_height = rng.gen::<f64>() * config.height / 2.0;
+    box_geom(center, (half_width, half_height), m)
+}
 
-    let corners = [
+/// Closed ring of the corners of a box, counter-clockwise from the lower left
+fn box_ring(center: (f64, f64), (half_width, half_height): (f64, f64)) -> [(f64, f64); 5] {
+    [
         (center.0 - half_width, center.1 - half_height),
         (center.0 + half_width, center.1 - half_height),
         (center.0 + half_width, center.1 + half_height),
         (center.0 - half_width, center.1 + half_height),
         (center.0 - half_width, center.1 - half_height),
-    ];
+    ]
+}
 
-    let coords: Vec<_> = corners
+fn box_geom(center: (f64, f64), half_extents: (f64, f64), m: &[f64; 6]) -> Geometry {
+    let coords: Vec<_> = box_ring(center, half_extents)
         .iter()
         .map(|&(x, y)| apply_affine(x, y, m))
         .map(|(x, y)| round_coordinates(x, y, GEOMETRY_PRECISION))
@@ -60,17 +187,17 @@ pub fn generate_polygon_geom(
     rng: &mut StdRng,
     m: &[f64; 6],
 ) -> Geometry {
-    let min_segs = 3;
-    let num_segments = if config.maxseg <= 3 {
-        3
-    } else {
-        rng.gen_range(0..=(config.maxseg - min_segs)) + min_segs
-    };
-
-    let mut angles: Vec<f64> = (0..num_segments)
-        .map(|_| rng.gen::<f64>() * 2.0 * PI)
-        .collect();
-    angles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    if let PolygonStyle::Footprint {
+        median_size,
+        size_sigma,
+    } = config.polygon_style
+    {
+        return generate_footprint_geom(center, (median_size, size_sigma), config, rng, m);
+    }
+    if config.validity == Validity::Strict {
+        return generate_strict_polygon_geom(center, config, rng, m);
+    }
+    let angles = polygon_angles(config, rng);
 
     let mut ring = angles
         .iter()
@@ -92,3 +219,369 @@ pub fn generate_polygon_geom(
 
     Geometry::Polygon(Polygon::new(LineString::from(ring), vec![]))
 }
+
+/// Emits a polygon with vertices on the circle of radius `polysize`, moved
+/// inside the unit square instead of clamping its vertices to it
+fn generate_strict_polygon_geom(
+    center: (f64, f64),
+    config: &SpatialConfig,
+    rng: &mut StdRng,
+    m: &[f64; 6],
+) -> Geometry {
+    let angles = strict_polygon_angles(config, rng);
+    let r = config.polysize.min(0.5);
+    let center = (center.0.clamp(r, 1.0 - r), center.1.clamp(r, 1.0 - r));
+    let ring: Vec<_> = angles
+        .iter()
+        .map(|&ang| {
+            (
+                center.0 + config.polysize * ang.cos(),
+                center.1 + config.polysize * ang.sin(),
+            )
+        })
+        .collect();
+    strict_polygon(&ring, center, m)
+}
+
+/// Emits a building footprint of [`PolygonStyle::Footprint`], rotated by a random
+/// angle about the mapped center
+///
+/// The outline is built in meters and converted to degrees at the latitude of
+/// the center, so footprints keep their size and shape in every region.
+fn generate_footprint_geom(
+    center: (f64, f64),
+    (median_size, size_sigma): (f64, f64),
+    config: &SpatialConfig,
+    rng: &mut StdRng,
+    m: &[f64; 6],
+) -> Geometry {
+    let length = median_size * rand_normal(rng, 0.0, size_sigma).exp();
+    let width = length * (0.4 + 0.6 * rng.gen::<f64>());
+    let outline = footprint_outline(length, width, config, rng);
+    let (sin, cos) = (rng.gen::<f64>() * 2.0 * PI).sin_cos();
+
+    let (lon, lat) = apply_affine(center.0, center.1, m);
+    let round = |(east, north): (f64, f64)| {
+        let (dlon, dlat) = meters_to_degrees(east, north, lat);
+        let (x, y) = round_coordinates(lon + dlon, lat + dlat, GEOMETRY_PRECISION);
+        coord! { x: x, y: y }
+    };
+    let coords: Vec<Coord> = outline
+        .iter()
+        .map(|&(x, y)| round((x * cos - y * sin, x * sin + y * cos)))
+        .collect();
+    match config.validity {
+        Validity::Raw => Geometry::Polygon(Polygon::new(LineString::from(coords), vec![])),
+        Validity::Strict => strict_ring(coords, round((0.0, 0.0))),
+    }
+}
+
+/// Counter-clockwise outline, in meters about the origin, of a footprint whose
+/// bounding box is `length` by `width`
+///
+/// Most footprints are rectangles, followed by L, T and U shapes, with a few
+/// irregular polygons of 3 to `maxseg` vertices.
+fn footprint_outline(
+    length: f64,
+    width: f64,
+    config: &SpatialConfig,
+    rng: &mut StdRng,
+) -> Vec<(f64, f64)> {
+    let (x, y) = (length / 2.0, width / 2.0);
+    let shape = rng.gen::<f64>();
+    let (a, b) = (rng.gen::<f64>(), rng.gen::<f64>());
+    if shape < 0.55 {
+        vec![(-x, -y), (x, -y), (x, y), (-x, y)]
+    } else if shape < 0.72 {
+        // L: a rectangle with one corner cut out
+        let (cut_x, cut_y) = (length * (0.3 + 0.3 * a), width * (0.3 + 0.3 * b));
+        vec![
+            (-x, -y),
+            (x, -y),
+            (x, y - cut_y),
+            (x - cut_x, y - cut_y),
+            (x - cut_x, y),
+            (-x, y),
+        ]
+    } else if shape < 0.84 {
+        // T: a bar along the top on a centered stem
+        let (stem, bar) = (length * (0.3 + 0.2 * a), width * (0.35 + 0.15 * b));
+        vec![
+            (-stem / 2.0, -y),
+            (stem / 2.0, -y),
+            (stem / 2.0, y - bar),
+            (x, y - bar),
+            (x, y),
+            (-x, y),
+            (-x, y - bar),
+            (-stem / 2.0, y - bar),
+        ]
+    } else if shape < 0.94 {
+        // U: two wings on a base, open to the top
+        let (wing, base) = (length * (0.2 + 0.15 * a), width * (0.3 + 0.2 * b));
+        vec![
+            (-x, -y),
+            (x, -y),
+            (x, y),
+            (x - wing, y),
+            (x - wing, -y + base),
+            (-x + wing, -y + base),
+            (-x + wing, y),
+            (-x, y),
+        ]
+    } else {
+        // irregular: vertices at jittered angles and radii, so the outline is
+        // star-shaped about the origin and never crosses itself
+        strict_polygon_angles(config, rng)
+            .iter()
+            .map(|&ang| {
+                let r = x * (0.6 + 0.4 * rng.gen::<f64>());
+                (r * ang.cos(), r * ang.sin())
+            })
+            .collect()
+    }
+}
+
+/// Maps the vertices of a ring in the unit square to a strictly valid polygon
+///
+/// Rounding can merge vertices, which are removed. A ring that collapses to
+/// fewer than three vertices or less than [`MIN_POLYGON_AREA`] is replaced by a
+/// small square around the center.
+fn strict_polygon(ring: &[(f64, f64)], center: (f64, f64), m: &[f64; 6]) -> Geometry {
+    let round = |(x, y): (f64, f64)| {
+        let (x, y) = apply_affine(x, y, m);
+        let (x, y) = round_coordinates(x, y, GEOMETRY_PRECISION);
+        coord! { x: x, y: y }
+    };
+    strict_ring(ring.iter().copied().map(round).collect(), round(center))
+}
+
+/// Turns the rounded vertices of a ring into a strictly valid polygon, or a
+/// small square around `center` if the ring collapses
+fn strict_ring(mut coords: Vec<Coord>, center: Coord) -> Geometry {
+    coords.dedup();
+    while coords.len() > 1 && coords.first() == coords.last() {
+        coords.pop();
+    }
+
+    let polygon = Polygon::new(LineString::from(coords), vec![]);
+    let polygon = if polygon.exterior().0.len() >= 4
+        && polygon.unsigned_area() >= MIN_POLYGON_AREA
+        && polygon.is_valid()
+    {
+        polygon
+    } else {
+        let (c, h) = (center, FALLBACK_HALF_SIDE);
+        Polygon::new(
+            LineString::from(vec![
+                (c.x - h, c.y - h),
+                (c.x + h, c.y - h),
+                (c.x + h, c.y + h),
+                (c.x - h, c.y + h),
+            ]),
+            vec![],
+        )
+    };
+    Geometry::Polygon(polygon.orient(Direction::Default))
+}
+
+/// Emits a random walk of 1 to `maxseg` segments of length `polysize`, centered
+/// on the bounding box center
+///
+/// Each segment turns by up to 45 degrees from the previous one, so walks look
+/// like roads or rivers rather than scribbles.
+pub fn generate_linestring_geom(
+    center: (f64, f64),
+    config: &SpatialConfig,
+    rng: &mut StdRng,
+    m: &[f64; 6],
+) -> Geometry {
+    let num_segments = if config.maxseg <= 1 {
+        1
+    } else {
+        rng.gen_range(1..=config.maxseg)
+    };
+
+    let mut heading = rng.gen::<f64>() * 2.0 * PI;
+    let mut walk = vec![(0.0, 0.0)];
+    for _ in 0..num_segments {
+        let (x, y) = walk[walk.len() - 1];
+        walk.push((
+            x + config.polysize * heading.cos(),
+            y + config.polysize * heading.sin(),
+        ));
+        heading += (rng.gen::<f64>() - 0.5) * PI / 2.0;
+    }
+
+    let (min_x, max_x) = walk.iter().fold((f64::MAX, f64::MIN), |(lo, hi), &(x, _)| {
+        (lo.min(x), hi.max(x))
+    });
+    let (min_y, max_y) = walk.iter().fold((f64::MAX, f64::MIN), |(lo, hi), &(_, y)| {
+        (lo.min(y), hi.max(y))
+    });
+    let (dx, dy) = (
+        center.0 - (min_x + max_x) / 2.0,
+        center.1 - (min_y + max_y) / 2.0,
+    );
+
+    let coords = walk
+        .iter()
+        .map(|&(x, y)| {
+            let (x1, y1) = ((x + dx).clamp(0.0, 1.0), (y + dy).clamp(0.0, 1.0));
+            let (x2, y2) = apply_affine(x1, y1, m);
+            let (xr, yr) = round_coordinates(x2, y2, GEOMETRY_PRECISION);
+            coord! { x: xr, y: yr }
+        })
+        .collect::<Vec<_>>();
+
+    Geometry::LineString(LineString::from(coords))
+}
+
+/// Number of vertices of a polygon, from 3 to `maxseg`
+fn polygon_vertex_count(config: &SpatialConfig, rng: &mut StdRng) -> i32 {
+    let min_segs = 3;
+    if config.maxseg <= 3 {
+        3
+    } else {
+        rng.gen_range(0..=(config.maxseg - min_segs)) + min_segs
+    }
+}
+
+/// Sorted angles of the vertices of a polygon with 3 to `maxseg` vertices
+fn polygon_angles(config: &SpatialConfig, rng: &mut StdRng) -> Vec<f64> {
+    let num_segments = polygon_vertex_count(config, rng);
+
+    let mut angles: Vec<f64> = (0..num_segments)
+        .map(|_| rng.gen::<f64>() * 2.0 * PI)
+        .collect();
+    angles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    angles
+}
+
+/// Sorted angles of the vertices of a polygon with 3 to `maxseg` vertices,
+/// evenly spaced from a random start and jittered by up to a quarter of the
+/// spacing, so neighbouring vertices stay apart and the polygon keeps its area
+fn strict_polygon_angles(config: &SpatialConfig, rng: &mut StdRng) -> Vec<f64> {
+    let num_segments = polygon_vertex_count(config, rng);
+    let step = 2.0 * PI / num_segments as f64;
+    let start = rng.gen::<f64>() * 2.0 * PI;
+    (0..num_segments)
+        .map(|i| start + (i as f64 + (rng.gen::<f64>() - 0.5) * 0.5) * step)
+        .collect()
+}
+
+/// Turns a footprint polygon into a campus of smaller copies and adds courtyards,
+/// with the probabilities of `options`
+///
+/// Campus parts are laid out side by side along the longer side of the bounding
+/// box of the polygon, and courtyards are shrunk copies of the exterior, so the
+/// footprint stays inside the bounding box and its parts and rings never
+/// overlap. Only convex polygons, such as boxes and star polygons, get
+/// courtyards, since a shrunk copy of an L or U shape can cross its exterior,
+/// and flat polygons, e.g. clamped to the edge of a region, get none.
+pub fn shape_footprint(polygon: Polygon, options: &FootprintOptions, rng: &mut StdRng) -> Geometry {
+    let parts = if rng.gen::<f64>() < options.multipolygon_probability {
+        campus_parts(&polygon, rng.gen_range(2..=options.max_parts))
+    } else {
+        vec![polygon]
+    };
+    let mut parts: Vec<Polygon> = parts
+        .into_iter()
+        .map(|part| {
+            if rng.gen::<f64>() < options.courtyard_probability
+                && part.exterior().is_convex()
+                && part.unsigned_area() > 0.0
+            {
+                with_courtyard(part, 0.3 + rng.gen::<f64>() * 0.3)
+            } else {
+                part
+            }
+        })
+        .collect();
+
+    if parts.len() == 1 {
+        Geometry::Polygon(parts.remove(0))
+    } else {
+        Geometry::MultiPolygon(MultiPolygon::new(parts))
+    }
+}
+
+/// Copies of the polygon, each scaled down to fit its share of the longer side of
+/// the bounding box with a gap to its neighbours
+fn campus_parts(polygon: &Polygon, count: u32) -> Vec<Polygon> {
+    let Some(bbox) = polygon.bounding_rect() else {
+        return vec![polygon.clone()];
+    };
+    let center = bbox.center();
+    let scale = 0.9 / count as f64;
+    let step = if bbox.width() >= bbox.height() {
+        coord! { x: bbox.width(), y: 0.0 }
+    } else {
+        coord! { x: 0.0, y: bbox.height() }
+    };
+    (0..count)
+        .map(|i| {
+            let offset = ((i as f64 + 0.5) / count as f64) - 0.5;
+            let target = center + step * offset;
+            Polygon::new(
+                scale_ring(polygon.exterior(), center, target, scale),
+                vec![],
+            )
+        })
+        .collect()
+}
+
+/// Adds a copy of the exterior scaled by `scale` as an interior ring, with the
+/// opposite orientation
+fn with_courtyard(polygon: Polygon, scale: f64) -> Polygon {
+    let center = ring_center(polygon.exterior());
+    let mut courtyard = scale_ring(polygon.exterior(), center, center, scale);
+    courtyard.0.reverse();
+    let (exterior, mut interiors) = polygon.into_inner();
+    interiors.push(courtyard);
+    Polygon::new(exterior, interiors)
+}
+
+/// Mean of the distinct vertices of a closed ring, inside it if it is convex
+fn ring_center(ring: &LineString) -> Coord {
+    let vertices = &ring.0[..ring.0.len().saturating_sub(1)];
+    let sum = vertices
+        .iter()
+        .fold(coord! { x: 0.0, y: 0.0 }, |sum, c| sum + *c);
+    sum / vertices.len() as f64
+}
+
+/// Scales the ring by `scale` about `center` and moves it to `target`
+fn scale_ring(ring: &LineString, center: Coord, target: Coord, scale: f64) -> LineString {
+    ring.coords()
+        .map(|c| {
+            let p = target + (*c - center) * scale;
+            let (x, y) = round_coordinates(p.x, p.y, GEOMETRY_PRECISION);
+            coord! { x: x, y: y }
+        })
+        .collect()
+}
+
+/// Returns the shaped footprint with counter-clockwise exteriors and clockwise
+/// courtyards, or `polygon`, the footprint it was shaped from, if rounding made
+/// a ring repeat points or collapse, or parts or courtyards touch
+pub fn strict_footprint(footprint: Geometry, polygon: Polygon) -> Geometry {
+    let parts = match &footprint {
+        Geometry::Polygon(p) => std::slice::from_ref(p),
+        Geometry::MultiPolygon(mp) => mp.0.as_slice(),
+        _ => return Geometry::Polygon(polygon),
+    };
+    let repeats = |ring: &LineString| ring.lines().any(|l| l.start == l.end);
+    let usable = parts.iter().all(|p| {
+        p.unsigned_area() >= MIN_POLYGON_AREA
+            && !repeats(p.exterior())
+            && !p.interiors().iter().any(repeats)
+    }) && footprint.is_valid();
+    match footprint {
+        Geometry::Polygon(p) if usable => Geometry::Polygon(p.orient(Direction::Default)),
+        Geometry::MultiPolygon(mp) if usable => {
+            Geometry::MultiPolygon(mp.orient(Direction::Default))
+        }
+        _ => Geometry::Polygon(polygon),
+    }
+}
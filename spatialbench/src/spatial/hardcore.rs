@@ -0,0 +1,172 @@
+//! Hard-core placement of building footprints
+//!
+//! Footprints are placed in key order, each at the first of its candidate
+//! locations that does not intersect a footprint of a lower key, as in a Matérn
+//! type II process whose marks are the keys. Placed footprints are indexed in a
+//! grid hash, so a candidate is only tested against footprints of nearby cells.
+//!
+//! Conflicts are resolved by key over the whole table, so every part places the
+//! same buildings at the same locations, and every building is placed: the table
+//! keeps its row count and keys.
+
+use crate::error::{Result, SpatialBenchError};
+use crate::spatial::utils::spider_seed_for_index;
+use geo::{BoundingRect, Geometry, Intersects, Rect};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Candidate locations tried per building before the placement fails
+pub const MAX_HARDCORE_ATTEMPTS: u32 = 1024;
+
+/// Salt of the keys of the candidate locations after the first one
+const HARDCORE_SALT: u64 = 0x4A2D_C021;
+
+/// Accepted candidate location of every building of a table
+#[derive(Debug)]
+pub struct HardCorePlacement {
+    /// Attempt of the accepted candidate by key, from key 1
+    attempts: Vec<u32>,
+}
+
+impl HardCorePlacement {
+    /// Places the buildings of keys 1 to `count`, whose footprint at a candidate
+    /// key is given by `footprint(building_key, candidate_key)`, or returns the
+    /// first error of `footprint`
+    ///
+    /// Fails if none of the [`MAX_HARDCORE_ATTEMPTS`] candidates of a building is
+    /// free, e.g. with footprints too large for the regions they are placed in.
+    pub fn new(count: u64, footprint: impl Fn(u64, u64) -> Result<Geometry>) -> Result<Self> {
+        let first = (1..=count)
+            .map(|key| footprint(key, key))
+            .collect::<Result<Vec<Geometry>>>()?;
+        let mut grid = FootprintGrid::new(cell_size(&first));
+
+        let mut attempts = Vec::with_capacity(first.len());
+        for (geometry, key) in first.into_iter().zip(1..) {
+            let mut candidate = geometry;
+            let mut attempt = 0;
+            while !grid.insert_if_free(candidate) {
+                attempt += 1;
+                if attempt == MAX_HARDCORE_ATTEMPTS {
+                    return Err(SpatialBenchError::InvalidConfig(format!(
+                        "No location without overlaps found for building {key} in \
+                         {MAX_HARDCORE_ATTEMPTS} attempts; the footprints may be too large \
+                         for the regions they are placed in"
+                    )));
+                }
+                candidate = footprint(key, Self::candidate_key(key, attempt))?;
+            }
+            attempts.push(attempt);
+        }
+        Ok(Self { attempts })
+    }
+
+    /// Returns the placement for the given settings, computed once per process
+    /// and shared by all building generators with the same `settings`
+    pub fn shared(
+        settings: String,
+        count: u64,
+        footprint: impl Fn(u64, u64) -> Result<Geometry>,
+    ) -> Result<Arc<Self>> {
+        static PLACEMENTS: OnceLock<Mutex<HashMap<String, Arc<HardCorePlacement>>>> =
+            OnceLock::new();
+        let placements = PLACEMENTS.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut placements = placements.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(placement) = placements.get(&settings) {
+            return Ok(Arc::clone(placement));
+        }
+
+        let placement = Arc::new(Self::new(count, footprint)?);
+        placements.insert(settings, Arc::clone(&placement));
+        Ok(placement)
+    }
+
+    /// Returns the key of the `attempt`-th candidate location of a building,
+    /// the building key itself for the first one
+    pub fn candidate_key(key: u64, attempt: u32) -> u64 {
+        if attempt == 0 {
+            key
+        } else {
+            spider_seed_for_index(key, HARDCORE_SALT + attempt as u64)
+        }
+    }
+
+    /// Returns the key of the accepted candidate of the building
+    pub fn placed_key(&self, building_key: i64) -> u64 {
+        let attempt = self.attempts[(building_key - 1) as usize];
+        Self::candidate_key(building_key as u64, attempt)
+    }
+
+    /// Number of buildings placed at another location than their first one
+    pub fn moved(&self) -> usize {
+        self.attempts.iter().filter(|&&a| a > 0).count()
+    }
+}
+
+/// Returns twice the median extent of the footprints, so most footprints fall
+/// in at most four cells of the grid
+fn cell_size(footprints: &[Geometry]) -> f64 {
+    let mut extents: Vec<f64> = footprints
+        .iter()
+        .filter_map(|f| f.bounding_rect())
+        .map(|bbox| bbox.width().max(bbox.height()))
+        .collect();
+    if extents.is_empty() {
+        return 1.0;
+    }
+    let mid = extents.len() / 2;
+    let (_, median, _) = extents.select_nth_unstable_by(mid, f64::total_cmp);
+    (2.0 * *median).max(1e-9)
+}
+
+/// Footprints indexed by the grid cells their bounding box covers
+struct FootprintGrid {
+    cell: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    footprints: Vec<(Rect, Geometry)>,
+}
+
+impl FootprintGrid {
+    fn new(cell: f64) -> Self {
+        Self {
+            cell,
+            cells: HashMap::new(),
+            footprints: Vec::new(),
+        }
+    }
+
+    /// Cells covered by the bounding box
+    fn cells_of(&self, bbox: &Rect) -> impl Iterator<Item = (i64, i64)> {
+        let index = |v: f64| (v / self.cell).floor() as i64;
+        let (x0, x1) = (index(bbox.min().x), index(bbox.max().x));
+        let (y0, y1) = (index(bbox.min().y), index(bbox.max().y));
+        (x0..=x1).flat_map(move |x| (y0..=y1).map(move |y| (x, y)))
+    }
+
+    /// Adds the footprint and returns true unless it intersects a footprint of
+    /// the grid
+    fn insert_if_free(&mut self, footprint: Geometry) -> bool {
+        let Some(bbox) = footprint.bounding_rect() else {
+            return false;
+        };
+        let conflict = self.cells_of(&bbox).any(|cell| {
+            self.cells.get(&cell).is_some_and(|placed| {
+                placed.iter().any(|&i| {
+                    let (other_bbox, other) = &self.footprints[i];
+                    other_bbox.intersects(&bbox) && other.intersects(&footprint)
+                })
+            })
+        });
+        if conflict {
+            return false;
+        }
+
+        let i = self.footprints.len();
+        let cells: Vec<_> = self.cells_of(&bbox).collect();
+        for cell in cells {
+            self.cells.entry(cell).or_default().push(i);
+        }
+        self.footprints.push((bbox, footprint));
+        true
+    }
+}
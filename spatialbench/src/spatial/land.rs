@@ -0,0 +1,357 @@
+//! Coarse land mask that keeps generated geometries out of the oceans
+//!
+//! The continent regions are boxes that also cover seas. With the land mask,
+//! trips whose pickup or dropoff, and buildings whose center, falls in water are
+//! drawn again from keys derived from the record key, so a record gets the same
+//! location in every part and every run.
+//!
+//! The bundled outlines are hand traced at roughly 50 km precision: the
+//! continents, the major inland seas as holes, and the larger islands. Small
+//! islands and lakes are ignored.
+
+use crate::spatial::utils::spider_seed_for_index;
+use geo::{BoundingRect, Contains, Coord, Intersects, LineString, Point, Polygon, Rect};
+use std::sync::OnceLock;
+
+/// Candidate locations drawn for a record before failing with an error
+pub const MAX_LAND_ATTEMPTS: u32 = 256;
+
+/// Salt of the keys of the candidate locations after the first one
+const LAND_SALT: u64 = 0x1A4D_0C3A;
+
+/// Land polygons with their bounding boxes
+#[derive(Debug)]
+pub struct LandMask {
+    polygons: Vec<(Rect, Polygon)>,
+}
+
+impl LandMask {
+    /// Returns the mask of the bundled land outlines, built once per process
+    pub fn bundled() -> &'static LandMask {
+        static MASK: OnceLock<LandMask> = OnceLock::new();
+        MASK.get_or_init(|| {
+            let polygons = LAND
+                .iter()
+                .map(|(exterior, holes)| {
+                    let polygon = Polygon::new(
+                        ring(exterior),
+                        holes.iter().map(|hole| ring(hole)).collect(),
+                    );
+                    let bbox = polygon.bounding_rect().expect("land polygon is not empty");
+                    (bbox, polygon)
+                })
+                .collect();
+            LandMask { polygons }
+        })
+    }
+
+    /// Returns true if the lon/lat location is on land
+    pub fn contains(&self, point: &Point) -> bool {
+        self.polygons
+            .iter()
+            .any(|(bbox, polygon)| bbox.intersects(point) && polygon.contains(point))
+    }
+
+    /// Returns the key the `attempt`-th candidate location of the record with
+    /// the given key is drawn from; the first candidate uses the record key
+    pub fn candidate_key(key: u64, attempt: u32) -> u64 {
+        if attempt == 0 {
+            key
+        } else {
+            spider_seed_for_index(key, LAND_SALT + attempt as u64)
+        }
+    }
+}
+
+fn ring(coords: &[(f64, f64)]) -> LineString {
+    LineString::new(coords.iter().map(|&(x, y)| Coord { x, y }).collect())
+}
+
+/// Exterior ring and holes of a land polygon, as (lon, lat) vertices
+type LandPolygon = (&'static [(f64, f64)], &'static [&'static [(f64, f64)]]);
+
+#[rustfmt::skip]
+const LAND: &[LandPolygon] = &[
+    // North and Central America
+    (&[
+        (-166.0, 68.5), (-156.0, 71.3), (-141.0, 69.6), (-128.0, 70.0), (-115.0, 68.5),
+        (-95.0, 68.0), (-94.0, 61.0), (-92.0, 57.0), (-82.0, 55.0), (-79.0, 51.5),
+        (-77.0, 56.0), (-78.0, 62.5), (-73.0, 62.0), (-65.0, 60.5), (-61.0, 56.0),
+        (-56.0, 52.0), (-60.0, 50.0), (-66.0, 49.0), (-64.0, 46.0), (-60.0, 46.0),
+        (-66.0, 43.5), (-70.0, 41.5), (-74.0, 40.5), (-76.0, 35.0), (-81.0, 31.0),
+        (-80.0, 25.5), (-82.5, 27.5), (-84.0, 30.0), (-89.0, 30.0), (-94.0, 29.5),
+        (-97.0, 27.5), (-97.5, 22.0), (-96.0, 19.0), (-91.0, 18.5), (-90.5, 21.0),
+        (-87.0, 21.5), (-87.5, 18.0), (-88.0, 16.0), (-84.0, 15.5), (-83.5, 11.0),
+        (-81.5, 9.0), (-77.5, 8.5), (-80.0, 7.5), (-83.0, 8.2), (-86.0, 11.0),
+        (-88.0, 13.2), (-92.0, 14.5), (-95.0, 16.0), (-99.0, 16.5), (-105.0, 20.0),
+        (-105.5, 23.0), (-109.0, 26.0), (-112.5, 31.0), (-114.8, 31.8), (-113.0, 29.0),
+        (-110.0, 24.0), (-109.5, 23.0), (-112.0, 24.8), (-114.5, 28.0), (-116.5, 31.5),
+        (-117.2, 32.7), (-120.5, 34.5), (-124.0, 40.5), (-124.5, 48.0), (-127.0, 50.5),
+        (-131.0, 54.5), (-136.0, 58.5), (-141.0, 60.0), (-148.0, 60.5), (-152.0, 58.5),
+        (-158.0, 56.5), (-164.0, 54.5), (-157.0, 58.5), (-162.0, 60.0), (-165.0, 62.5),
+        (-162.0, 64.0), (-168.0, 65.6), (-166.0, 68.5),
+    ], &[]),
+    // Greenland
+    (&[
+        (-73.0, 78.0), (-60.0, 82.0), (-32.0, 83.5), (-18.0, 81.5), (-20.0, 75.0),
+        (-22.0, 70.0), (-32.0, 68.0), (-40.0, 65.0), (-43.0, 60.0), (-48.0, 61.0),
+        (-52.0, 65.0), (-54.0, 69.0), (-58.0, 75.5), (-66.0, 76.5), (-73.0, 78.0),
+    ], &[]),
+    // Baffin Island
+    (&[
+        (-78.0, 73.5), (-68.0, 70.5), (-62.0, 66.5), (-65.0, 63.0), (-72.0, 62.5),
+        (-78.0, 64.5), (-85.0, 68.5), (-90.0, 71.0), (-84.0, 73.7), (-78.0, 73.5),
+    ], &[]),
+    // Ellesmere Island
+    (&[
+        (-92.0, 80.0), (-80.0, 83.0), (-62.0, 82.5), (-70.0, 79.0), (-79.0, 76.5),
+        (-92.0, 76.5), (-92.0, 80.0),
+    ], &[]),
+    // Victoria Island
+    (&[
+        (-118.0, 73.0), (-100.0, 73.0), (-101.0, 68.5), (-117.0, 68.5), (-118.0, 73.0),
+    ], &[]),
+    // Cuba
+    (&[
+        (-85.0, 21.9), (-80.0, 23.2), (-74.2, 20.2), (-77.5, 19.8), (-83.0, 21.5),
+        (-85.0, 21.9),
+    ], &[]),
+    // Hispaniola
+    (&[
+        (-74.5, 18.5), (-69.0, 19.8), (-68.4, 18.4), (-71.5, 17.6), (-74.5, 18.5),
+    ], &[]),
+    // South America
+    (&[
+        (-77.3, 8.6), (-75.5, 10.8), (-71.5, 12.3), (-63.0, 10.7), (-60.0, 8.5),
+        (-52.0, 5.0), (-50.0, 0.0), (-44.0, -2.5), (-35.0, -5.0), (-35.0, -9.0),
+        (-39.0, -13.5), (-39.5, -18.0), (-41.0, -22.0), (-48.0, -25.5), (-48.5, -28.5),
+        (-53.0, -33.5), (-57.5, -35.0), (-57.0, -38.0), (-62.0, -39.0), (-65.0, -42.0),
+        (-65.5, -45.0), (-67.5, -46.5), (-65.5, -48.0), (-69.0, -51.0), (-68.5, -52.5),
+        (-66.0, -55.0), (-71.0, -55.5), (-74.5, -52.0), (-75.5, -46.0), (-73.5, -42.0),
+        (-73.5, -37.0), (-71.5, -30.0), (-70.3, -18.5), (-75.5, -15.0), (-79.0, -8.0),
+        (-81.0, -4.5), (-80.0, -1.0), (-80.0, 1.0), (-78.5, 2.5), (-77.5, 7.0),
+        (-77.3, 8.6),
+    ], &[]),
+    // Africa
+    (&[
+        (-17.0, 21.0), (-16.5, 24.0), (-13.0, 27.5), (-9.8, 30.0), (-6.0, 35.8),
+        (-2.0, 35.1), (3.0, 36.8), (10.0, 37.3), (11.0, 35.0), (10.0, 33.7),
+        (15.0, 32.3), (19.0, 30.3), (20.0, 32.0), (23.0, 32.6), (29.0, 30.9),
+        (32.0, 31.3), (34.2, 31.3), (34.9, 29.5), (32.5, 29.9), (33.0, 28.0),
+        (35.5, 24.0), (37.3, 19.0), (39.0, 15.5), (43.3, 12.4), (44.5, 10.5),
+        (51.0, 12.0), (51.0, 10.5), (48.0, 4.5), (41.5, -1.7), (39.3, -6.0),
+        (40.5, -10.5), (40.5, -15.0), (35.5, -22.0), (33.0, -26.0), (32.5, -28.5),
+        (30.0, -31.5), (26.0, -34.0), (20.0, -34.8), (18.3, -34.0), (17.0, -29.0),
+        (15.0, -27.0), (14.5, -22.5), (11.8, -17.0), (13.5, -12.0), (12.0, -5.0),
+        (9.5, -1.0), (9.5, 4.0), (8.5, 4.5), (5.0, 5.8), (2.0, 6.2),
+        (-1.0, 5.0), (-4.0, 5.2), (-7.5, 4.4), (-11.5, 6.9), (-13.3, 9.0),
+        (-15.0, 11.0), (-16.8, 12.5), (-17.5, 14.7), (-16.5, 19.5), (-17.0, 21.0),
+    ], &[]),
+    // Madagascar
+    (&[
+        (49.3, -12.0), (50.5, -15.5), (49.5, -17.5), (47.2, -25.0), (45.0, -25.5),
+        (43.5, -22.0), (44.5, -16.2), (47.5, -13.5), (49.3, -12.0),
+    ], &[]),
+    // Eurasia, without the Black Sea and the Caspian Sea
+    (&[
+        (-9.0, 43.0), (-9.5, 39.0), (-8.9, 37.0), (-6.0, 36.2), (-2.0, 36.7),
+        (0.0, 38.7), (0.5, 40.5), (3.2, 42.0), (3.0, 43.3), (6.0, 43.1),
+        (8.0, 43.8), (10.0, 44.0), (10.5, 43.0), (12.3, 41.7), (15.7, 40.0),
+        (16.0, 38.0), (17.0, 39.0), (18.5, 40.1), (17.0, 41.0), (14.0, 42.6),
+        (12.3, 44.3), (12.3, 45.4), (13.7, 45.6), (15.0, 44.8), (19.0, 42.0),
+        (19.5, 40.0), (21.0, 38.5), (22.5, 36.5), (23.5, 38.0), (22.8, 40.4),
+        (26.0, 40.9), (26.3, 40.0), (26.2, 39.0), (27.3, 37.0), (28.5, 36.6),
+        (30.5, 36.5), (32.5, 36.1), (34.5, 36.8), (36.0, 36.8), (35.9, 35.5),
+        (35.5, 33.8), (34.9, 32.5), (34.2, 31.3), (34.9, 29.5), (36.5, 26.0),
+        (39.0, 22.0), (41.5, 17.5), (42.8, 15.0), (43.5, 12.7), (45.0, 12.8),
+        (49.0, 14.0), (52.2, 15.6), (55.0, 17.5), (57.5, 18.8), (59.8, 22.5),
+        (58.5, 23.7), (56.3, 24.5), (56.4, 26.3), (54.5, 24.2), (51.6, 24.2),
+        (51.3, 26.1), (50.0, 26.5), (48.5, 28.5), (48.0, 30.0), (50.0, 30.2),
+        (51.5, 27.9), (54.0, 26.7), (56.3, 27.1), (57.5, 25.7), (61.5, 25.2),
+        (66.5, 25.4), (67.5, 24.0), (68.8, 22.3), (70.5, 20.7), (72.8, 21.3),
+        (72.8, 19.0), (73.4, 16.0), (74.8, 12.8), (76.3, 9.5), (77.5, 8.0),
+        (78.2, 8.9), (79.8, 10.3), (80.2, 13.5), (80.1, 15.5), (82.3, 17.0),
+        (85.0, 19.5), (87.0, 21.5), (89.0, 21.8), (91.0, 22.5), (92.3, 21.0),
+        (94.3, 16.0), (95.4, 15.8), (97.7, 16.5), (98.5, 13.2), (98.5, 10.0),
+        (98.3, 8.0), (100.3, 5.5), (101.3, 2.8), (103.5, 1.3), (104.2, 1.5),
+        (103.4, 4.0), (102.2, 6.2), (100.3, 7.5), (99.2, 9.2), (100.0, 12.5),
+        (100.9, 13.5), (102.5, 12.0), (104.8, 10.4), (104.8, 8.6), (106.7, 10.4),
+        (109.2, 11.5), (109.3, 13.5), (108.8, 15.4), (106.6, 17.5), (105.7, 19.0),
+        (106.7, 20.7), (108.0, 21.5), (110.5, 21.2), (113.5, 22.2), (117.0, 23.5),
+        (119.6, 25.6), (121.5, 28.5), (121.8, 31.0), (120.5, 33.5), (119.3, 35.0),
+        (120.7, 36.4), (122.5, 37.0), (121.6, 37.5), (119.5, 37.2), (118.0, 38.0),
+        (117.6, 38.9), (118.9, 39.1), (121.2, 40.8), (122.2, 40.4), (121.2, 38.8),
+        (124.3, 39.9), (125.3, 37.7), (126.6, 34.5), (129.3, 35.2), (129.5, 36.8),
+        (128.4, 38.6), (129.7, 41.0), (131.2, 42.6), (135.0, 43.5), (138.5, 47.0),
+        (140.5, 51.0), (141.3, 53.3), (137.0, 54.0), (141.0, 58.5), (147.0, 59.3),
+        (151.0, 59.2), (155.0, 59.5), (158.0, 61.8), (156.5, 57.5), (156.5, 51.2),
+        (158.5, 52.8), (160.0, 54.5), (163.3, 56.2), (162.0, 58.0), (164.5, 59.8),
+        (170.0, 60.0), (174.0, 61.8), (178.5, 62.5), (180.0, 65.0), (180.0, 68.8),
+        (176.0, 69.8), (170.0, 70.0), (161.0, 69.6), (152.0, 70.9), (141.0, 72.7),
+        (130.0, 71.0), (128.5, 72.5), (122.0, 73.0), (113.0, 73.7), (107.0, 73.2),
+        (110.0, 74.5), (113.0, 76.0), (104.0, 77.7), (100.0, 76.0), (89.0, 75.5),
+        (86.0, 74.0), (80.5, 73.6), (80.5, 72.0), (75.0, 72.8), (72.5, 68.5),
+        (69.0, 73.0), (67.0, 70.0), (60.0, 69.0), (54.0, 68.3), (44.0, 68.5),
+        (43.5, 66.0), (41.0, 66.5), (34.0, 69.3), (28.0, 71.0), (24.0, 71.1),
+        (15.0, 68.5), (12.5, 65.5), (10.5, 63.5), (5.0, 62.0), (5.0, 59.0),
+        (6.5, 58.0), (10.5, 59.0), (11.5, 58.2), (12.0, 56.3), (12.8, 55.5),
+        (14.3, 55.6), (16.0, 56.2), (16.5, 57.9), (18.8, 59.5), (17.3, 60.6),
+        (17.5, 62.4), (21.0, 64.6), (22.3, 65.8), (25.3, 65.0), (21.5, 63.0),
+        (21.4, 61.0), (22.7, 59.9), (27.0, 60.4), (30.0, 59.8), (28.0, 59.5),
+        (23.5, 59.2), (23.5, 58.0), (24.3, 57.3), (21.0, 57.4), (21.0, 56.0),
+        (21.2, 55.2), (19.8, 54.4), (18.5, 54.8), (14.5, 53.9), (11.0, 54.0),
+        (10.0, 54.8), (10.5, 56.5), (10.5, 57.7), (8.3, 57.0), (8.1, 55.5),
+        (8.6, 53.9), (7.0, 53.5), (4.8, 52.9), (4.0, 51.9), (2.5, 51.1),
+        (1.6, 50.2), (0.0, 49.7), (-1.9, 49.7), (-1.5, 48.7), (-4.7, 48.4),
+        (-4.3, 47.8), (-2.2, 47.1), (-1.2, 46.0), (-1.4, 44.0), (-1.8, 43.4),
+        (-4.5, 43.4), (-8.0, 43.7), (-9.0, 43.0),
+    ], &[
+        // Black Sea
+        &[
+            (27.5, 42.5), (28.6, 44.0), (30.0, 45.5), (31.0, 46.6), (33.5, 46.0),
+            (32.5, 45.4), (33.5, 44.4), (36.5, 45.3), (39.0, 44.5), (41.5, 41.5),
+            (36.0, 41.7), (31.0, 41.1), (29.0, 41.2), (27.5, 42.5),
+        ],
+        // Caspian Sea
+        &[
+            (46.8, 44.8), (49.0, 46.5), (53.0, 46.9), (53.2, 45.0), (50.5, 44.6),
+            (52.8, 42.1), (54.0, 40.5), (53.0, 39.3), (53.9, 37.3), (51.0, 36.8),
+            (49.2, 37.6), (48.9, 38.4), (49.5, 40.3), (48.2, 42.0), (46.8, 44.8),
+        ],
+    ]),
+    // Chukotka east of the antimeridian
+    (&[
+        (-180.0, 65.0), (-175.0, 64.3), (-172.5, 64.5), (-170.5, 66.0), (-175.0, 67.7),
+        (-180.0, 68.8), (-180.0, 65.0),
+    ], &[]),
+    // Great Britain
+    (&[
+        (-5.7, 50.0), (1.4, 51.2), (1.7, 52.7), (0.2, 53.5), (-1.6, 55.6),
+        (-2.0, 57.6), (-3.5, 58.6), (-5.0, 58.6), (-6.2, 56.5), (-5.0, 55.0),
+        (-3.0, 54.5), (-3.0, 53.4), (-4.5, 52.8), (-5.2, 51.7), (-3.3, 51.4),
+        (-5.7, 50.0),
+    ], &[]),
+    // Ireland
+    (&[
+        (-6.0, 52.0), (-6.0, 54.0), (-7.5, 55.3), (-10.0, 54.2), (-10.3, 51.6),
+        (-8.0, 51.6), (-6.0, 52.0),
+    ], &[]),
+    // Iceland
+    (&[
+        (-22.5, 64.0), (-24.0, 65.5), (-22.0, 66.4), (-16.0, 66.5), (-13.5, 65.0),
+        (-18.0, 63.4), (-22.5, 64.0),
+    ], &[]),
+    // Sicily
+    (&[(12.4, 38.0), (15.6, 38.3), (15.1, 36.7), (12.4, 38.0)], &[]),
+    // Sardinia
+    (&[(8.2, 41.0), (9.8, 41.0), (9.6, 39.0), (8.4, 38.9), (8.2, 41.0)], &[]),
+    // Novaya Zemlya
+    (&[
+        (51.5, 71.5), (57.0, 70.6), (58.8, 74.0), (69.0, 76.8), (68.0, 77.0),
+        (58.0, 75.8), (55.0, 73.3), (51.5, 71.5),
+    ], &[]),
+    // Sri Lanka
+    (&[
+        (79.8, 9.8), (80.3, 9.8), (81.9, 7.3), (81.2, 6.1), (80.0, 6.1),
+        (79.8, 8.0), (79.8, 9.8),
+    ], &[]),
+    // Hainan
+    (&[
+        (108.6, 19.2), (110.5, 20.1), (111.0, 19.6), (110.0, 18.2), (108.7, 18.5),
+        (108.6, 19.2),
+    ], &[]),
+    // Taiwan
+    (&[
+        (120.1, 23.0), (121.0, 25.2), (122.0, 25.0), (121.6, 22.9), (120.8, 21.9),
+        (120.1, 23.0),
+    ], &[]),
+    // Honshu
+    (&[
+        (130.9, 34.0), (132.5, 35.5), (136.0, 35.7), (136.8, 37.3), (139.5, 38.5),
+        (140.0, 40.8), (141.5, 41.4), (142.0, 39.5), (141.0, 37.0), (140.8, 35.5),
+        (139.8, 35.0), (138.0, 34.6), (136.8, 34.3), (135.0, 33.5), (133.0, 34.2),
+        (130.9, 34.0),
+    ], &[]),
+    // Hokkaido
+    (&[
+        (140.0, 41.5), (141.7, 45.5), (145.5, 44.3), (145.0, 43.0), (143.3, 42.0),
+        (141.0, 41.7), (140.0, 41.5),
+    ], &[]),
+    // Kyushu
+    (&[
+        (129.7, 33.5), (131.0, 34.0), (132.0, 33.0), (131.0, 31.3), (130.2, 31.2),
+        (129.7, 32.7), (129.7, 33.5),
+    ], &[]),
+    // Sakhalin
+    (&[
+        (142.0, 46.0), (141.8, 51.5), (142.5, 54.3), (143.5, 52.0), (143.2, 49.0),
+        (142.5, 46.6), (142.0, 46.0),
+    ], &[]),
+    // Luzon
+    (&[
+        (120.0, 18.5), (122.3, 18.5), (122.0, 16.5), (122.5, 14.0), (124.0, 13.0),
+        (121.5, 13.6), (120.6, 14.5), (119.8, 16.4), (120.0, 18.5),
+    ], &[]),
+    // Mindanao
+    (&[
+        (122.0, 7.0), (124.0, 8.5), (125.5, 9.7), (126.5, 7.5), (125.5, 5.7),
+        (124.0, 6.5), (122.0, 6.8), (122.0, 7.0),
+    ], &[]),
+    // Borneo
+    (&[
+        (109.0, 1.5), (110.5, 1.8), (113.0, 3.2), (115.5, 5.2), (117.0, 7.0),
+        (119.3, 5.3), (118.0, 4.3), (118.0, 1.0), (117.5, 0.0), (116.0, -3.5),
+        (114.5, -4.0), (111.5, -3.0), (110.0, -1.5), (109.0, 1.5),
+    ], &[]),
+    // Sumatra
+    (&[
+        (95.2, 5.6), (97.5, 5.2), (100.5, 2.0), (104.5, -1.5), (106.0, -3.0),
+        (105.8, -5.8), (104.5, -5.9), (101.5, -3.0), (98.5, 0.0), (95.5, 3.0),
+        (95.2, 5.6),
+    ], &[]),
+    // Java
+    (&[
+        (105.2, -6.8), (106.5, -6.0), (108.5, -6.5), (111.0, -6.4), (114.5, -7.7),
+        (114.4, -8.7), (111.0, -8.2), (108.0, -7.8), (106.4, -7.4), (105.2, -6.8),
+    ], &[]),
+    // Sulawesi
+    (&[
+        (119.5, -5.5), (119.0, -3.0), (119.8, 0.5), (120.9, 1.3), (124.9, 1.6),
+        (123.0, 0.5), (120.5, 0.4), (121.5, -1.0), (123.3, -1.0), (121.3, -1.9),
+        (122.8, -4.5), (122.0, -5.2), (120.3, -2.8), (120.4, -5.6), (119.5, -5.5),
+    ], &[]),
+    // New Guinea
+    (&[
+        (131.0, -1.3), (134.0, -0.8), (135.5, -3.3), (138.0, -1.6), (141.0, -2.6),
+        (145.8, -5.0), (147.5, -6.5), (148.0, -8.0), (150.5, -10.6), (147.0, -10.0),
+        (144.0, -7.7), (143.0, -9.2), (141.0, -9.1), (138.0, -8.3), (137.5, -6.0),
+        (135.0, -4.4), (132.8, -4.0), (132.0, -2.8), (131.0, -1.3),
+    ], &[]),
+    // Australia
+    (&[
+        (113.5, -22.0), (114.0, -26.5), (115.0, -30.0), (115.0, -33.6), (117.5, -35.0),
+        (121.0, -33.8), (124.0, -33.0), (128.0, -32.0), (131.5, -31.5), (135.0, -33.5),
+        (138.5, -35.6), (140.0, -37.5), (143.5, -38.8), (146.3, -39.1), (150.0, -37.5),
+        (151.3, -33.8), (153.3, -29.0), (153.0, -25.0), (150.8, -22.6), (146.3, -19.0),
+        (145.3, -15.0), (143.5, -14.0), (142.5, -10.7), (141.5, -13.5), (141.6, -17.0),
+        (140.5, -17.5), (139.0, -17.0), (135.5, -15.0), (136.8, -12.2), (132.5, -11.3),
+        (130.0, -12.5), (129.0, -15.0), (126.5, -13.9), (122.2, -17.5), (121.0, -19.5),
+        (117.0, -20.6), (113.5, -22.0),
+    ], &[]),
+    // Tasmania
+    (&[
+        (144.7, -40.7), (148.3, -40.9), (148.0, -43.2), (146.8, -43.6), (145.3, -42.2),
+        (144.7, -40.7),
+    ], &[]),
+    // New Zealand, North Island
+    (&[
+        (172.7, -34.4), (174.6, -36.5), (175.9, -37.4), (178.5, -37.7), (177.0, -39.3),
+        (176.0, -41.3), (174.7, -41.3), (174.5, -39.8), (173.8, -39.1), (174.6, -37.5),
+        (172.7, -34.4),
+    ], &[]),
+    // New Zealand, South Island
+    (&[
+        (172.7, -40.5), (174.3, -41.7), (173.0, -43.7), (171.2, -44.5), (169.0, -46.7),
+        (166.5, -46.0), (168.2, -44.0), (171.0, -42.0), (172.7, -40.5),
+    ], &[]),
+];
@@ -1,12 +1,20 @@
 pub mod cache;
+pub mod city_model;
 pub mod config;
 pub mod defaults;
+pub mod density;
 pub mod distributions;
+pub mod elevation;
 pub mod generator;
 pub mod geometry;
+pub mod hardcore;
+pub mod land;
+pub mod nations;
 pub mod overrides;
 pub mod utils;
 
+pub use city_model::CityModel;
 pub use config::*;
 pub use defaults::*;
+pub use distributions::SpatialDistribution;
 pub use generator::SpatialGenerator;
@@ -0,0 +1,374 @@
+//! Outlines of the nations of the customer and driver tables
+//!
+//! Home and base locations are generated in the bounding box of the outline of
+//! the nation of their record, and drawn again from keys derived from the
+//! record key until they fall inside the outline, so that they agree with its
+//! nation and region columns.
+//!
+//! Like the land mask, the outlines are hand traced at roughly 50 km precision:
+//! the mainland and the larger islands of each nation. Neighbouring nations
+//! share the vertices of their common border, so no location is in two nations.
+
+use crate::distribution::Distribution;
+use crate::spatial::utils::continent::WeightedTarget;
+use crate::spatial::utils::spider_seed_for_index;
+use crate::spatial::Region;
+use geo::{BoundingRect, Contains, Coord, LineString, MultiPolygon, Point, Polygon};
+
+/// Candidate locations drawn for a record before failing with an error
+pub const MAX_NATION_ATTEMPTS: u32 = 256;
+
+/// Salt of the keys of the candidate locations after the first one
+const NATION_SALT: u64 = 0x7A71_0A15;
+
+/// Area of a nation that locations are drawn in
+#[derive(Debug, Clone)]
+pub struct NationArea {
+    /// Target covering the bounding box of the outline
+    pub target: WeightedTarget,
+    /// Outline of the nation, `None` for nations without one, which cover the
+    /// whole world
+    outline: Option<MultiPolygon>,
+}
+
+impl NationArea {
+    /// Returns the area of the nation with the given name
+    pub fn of(name: &str) -> Self {
+        let outline = nation_outline(name);
+        let bbox = outline
+            .as_ref()
+            .and_then(|outline| outline.bounding_rect())
+            .map_or(WORLD_BBOX, |bbox| {
+                [bbox.min().x, bbox.min().y, bbox.max().x, bbox.max().y]
+            });
+        Self {
+            target: WeightedTarget {
+                m: Region::from_bbox(name, bbox, None).affine,
+                cdf: 1.0,
+            },
+            outline,
+        }
+    }
+
+    /// Returns true if the lon/lat location is in the nation
+    pub fn contains(&self, point: &Point) -> bool {
+        self.outline
+            .as_ref()
+            .is_none_or(|outline| outline.contains(point))
+    }
+
+    /// Returns the key the `attempt`-th candidate location of the record with
+    /// the given key is drawn from; the first candidate uses the record key
+    pub fn candidate_key(key: u64, attempt: u32) -> u64 {
+        if attempt == 0 {
+            key
+        } else {
+            spider_seed_for_index(key, NATION_SALT + attempt as u64)
+        }
+    }
+}
+
+/// Returns the outline of the nation with the given name, if known
+pub fn nation_outline(name: &str) -> Option<MultiPolygon> {
+    let (_, rings) = NATIONS.iter().find(|(nation, _)| *nation == name)?;
+    Some(MultiPolygon::new(
+        rings
+            .iter()
+            .map(|ring| {
+                let coords = ring.iter().map(|&(x, y)| Coord { x, y }).collect();
+                Polygon::new(LineString::new(coords), vec![])
+            })
+            .collect(),
+    ))
+}
+
+/// Returns the area of each nation of `nations`, indexed by nation key
+pub fn nation_areas(nations: &Distribution) -> Vec<NationArea> {
+    (0..nations.size())
+        .map(|key| NationArea::of(nations.get_value(key)))
+        .collect()
+}
+
+/// Bounding box of the nations without an outline
+const WORLD_BBOX: [f64; 4] = [-180.0, -90.0, 180.0, 90.0];
+
+/// Outer rings of the polygons of a nation, as (lon, lat) vertices
+type NationOutline = (&'static str, &'static [&'static [(f64, f64)]]);
+
+#[rustfmt::skip]
+const NATIONS: &[NationOutline] = &[
+    ("ALGERIA", &[&[
+        (-2.2, 35.1), (-1.7, 34.0), (-1.8, 32.2), (-3.6, 30.9), (-5.0, 29.8),
+        (-8.7, 28.7), (-8.7, 27.3), (-4.8, 25.0), (1.2, 21.0), (4.2, 19.2),
+        (5.8, 19.4), (12.0, 23.5), (10.0, 25.5), (9.5, 27.0), (9.8, 29.5),
+        (8.2, 32.5), (7.5, 33.5), (8.3, 34.7), (8.6, 36.9), (7.0, 37.1),
+        (5.0, 36.7), (3.0, 36.8), (0.0, 35.9), (-1.2, 35.4), (-2.2, 35.1),
+    ]]),
+    ("ARGENTINA", &[&[
+        (-54.6, -25.6), (-54.0, -27.2), (-56.0, -28.0), (-57.6, -30.2), (-58.4, -33.9),
+        (-57.3, -35.8), (-57.5, -38.0), (-62.0, -39.0), (-62.5, -40.8), (-65.0, -42.0),
+        (-64.5, -43.0), (-65.5, -45.0), (-67.5, -46.5), (-65.8, -48.0), (-69.0, -51.0),
+        (-68.4, -52.3), (-71.9, -52.0), (-72.3, -50.0), (-73.5, -49.0), (-71.6, -44.0),
+        (-71.9, -40.0), (-70.6, -36.0), (-70.0, -33.0), (-69.8, -30.0), (-68.2, -27.0),
+        (-68.6, -24.0), (-67.2, -22.8), (-65.0, -22.1), (-62.8, -22.0), (-61.0, -23.5),
+        (-58.0, -25.5), (-58.6, -27.3), (-55.7, -27.4), (-54.6, -25.6),
+    ]]),
+    ("BRAZIL", &[&[
+        (-60.0, 5.2), (-51.6, 4.2), (-50.0, 1.8), (-48.5, -0.7), (-44.0, -2.5),
+        (-39.0, -3.0), (-35.2, -5.5), (-35.0, -8.5), (-37.0, -11.0), (-39.0, -13.5),
+        (-39.2, -17.7), (-40.9, -22.0), (-43.2, -23.0), (-46.3, -24.0), (-48.6, -26.0),
+        (-48.8, -28.5), (-50.5, -31.0), (-53.4, -33.7), (-53.5, -32.5), (-55.6, -30.9),
+        (-57.6, -30.2), (-56.0, -28.0), (-54.0, -27.2), (-54.6, -25.6), (-54.3, -24.0),
+        (-55.5, -22.3), (-57.9, -22.1), (-58.2, -20.0), (-57.5, -18.0), (-58.4, -16.3),
+        (-60.2, -15.1), (-60.5, -13.5), (-65.3, -10.9), (-69.6, -11.0), (-72.4, -10.0),
+        (-73.2, -9.4), (-74.0, -7.5), (-73.0, -5.0), (-70.0, -4.2), (-69.4, -1.1),
+        (-69.8, 1.1), (-67.0, 1.2), (-64.0, 4.0), (-62.0, 4.2), (-60.0, 5.2),
+    ]]),
+    ("CANADA", &[&[
+        (-141.0, 69.6), (-141.0, 60.3), (-137.5, 59.0), (-133.4, 58.4), (-130.0, 55.9),
+        (-130.6, 54.7), (-128.0, 52.0), (-125.0, 50.0), (-123.0, 49.0), (-95.2, 49.0),
+        (-95.2, 49.4), (-89.6, 48.0), (-84.8, 46.5), (-82.5, 45.3), (-82.4, 43.0),
+        (-83.1, 42.1), (-81.0, 42.2), (-79.0, 42.7), (-79.1, 43.3), (-76.3, 44.1),
+        (-74.7, 45.0), (-71.5, 45.0), (-70.0, 46.7), (-69.2, 47.4), (-67.8, 47.0),
+        (-67.8, 45.7), (-67.0, 44.9), (-66.0, 43.8), (-60.0, 46.0), (-64.3, 48.8),
+        (-59.0, 50.2), (-55.7, 52.0), (-60.5, 55.5), (-64.5, 60.3), (-70.0, 58.8),
+        (-78.0, 62.4), (-77.0, 56.0), (-79.5, 51.5), (-82.3, 52.9), (-88.0, 56.5),
+        (-94.5, 59.0), (-94.0, 61.5), (-88.0, 64.0), (-85.0, 66.5), (-90.0, 68.5),
+        (-95.0, 68.0), (-108.0, 68.0), (-116.0, 69.2), (-128.0, 70.2), (-135.0, 69.3),
+        (-141.0, 69.6),
+    ]]),
+    ("EGYPT", &[&[
+        (25.0, 31.6), (25.0, 22.0), (36.9, 22.0), (35.5, 23.9), (34.0, 26.7),
+        (32.6, 29.9), (33.8, 27.8), (34.9, 29.5), (34.2, 31.3), (32.3, 31.2),
+        (30.0, 31.5), (29.0, 30.9), (25.0, 31.6),
+    ]]),
+    ("ETHIOPIA", &[&[
+        (35.8, 4.6), (34.0, 6.8), (33.0, 8.0), (34.1, 9.5), (34.1, 10.6),
+        (35.2, 12.0), (36.5, 14.3), (37.9, 14.9), (40.0, 14.5), (42.4, 12.5),
+        (41.8, 11.0), (43.0, 9.5), (44.0, 9.0), (47.9, 8.0), (45.0, 5.0),
+        (43.0, 4.5), (41.9, 3.9), (40.8, 4.2), (39.0, 3.4), (37.0, 4.4),
+        (35.8, 4.6),
+    ]]),
+    ("FRANCE", &[
+        &[
+            (2.5, 51.1), (3.1, 50.8), (4.2, 50.3), (4.8, 49.8), (5.8, 49.5),
+            (6.4, 49.5), (8.2, 49.0), (7.6, 47.6), (7.0, 47.5), (6.1, 46.2),
+            (7.0, 45.9), (6.6, 45.1), (7.0, 44.2), (7.5, 43.8), (6.0, 43.1),
+            (4.0, 43.4), (3.1, 42.5), (1.7, 42.5), (-1.8, 43.4), (-1.2, 44.5),
+            (-1.2, 46.1), (-2.2, 47.1), (-4.7, 48.0), (-4.5, 48.6), (-1.6, 48.7),
+            (-1.4, 49.7), (0.2, 49.4), (1.6, 50.2), (2.5, 51.1),
+        ],
+        // Corsica
+        &[(8.6, 41.4), (9.4, 41.4), (9.5, 43.0), (8.6, 42.4), (8.6, 41.4)],
+    ]),
+    ("GERMANY", &[&[
+        (6.0, 51.9), (7.0, 53.3), (8.5, 53.6), (8.6, 54.9), (9.5, 54.8),
+        (10.9, 54.4), (13.0, 54.5), (14.2, 53.9), (14.6, 52.6), (15.0, 51.1),
+        (12.1, 50.3), (13.8, 49.0), (13.0, 47.5), (10.0, 47.3), (7.6, 47.6),
+        (8.2, 49.0), (6.4, 49.5), (6.1, 50.1), (6.0, 50.7), (6.0, 51.9),
+    ]]),
+    ("INDIA", &[&[
+        (77.8, 35.5), (76.0, 35.0), (74.0, 34.5), (75.0, 32.5), (74.6, 31.6),
+        (73.9, 30.0), (72.0, 28.0), (70.4, 28.0), (69.6, 27.0), (71.0, 24.5),
+        (68.8, 24.3), (69.0, 22.3), (70.5, 20.8), (72.8, 21.0), (72.8, 19.0),
+        (73.5, 16.0), (74.8, 12.8), (76.2, 10.0), (77.5, 8.1), (78.2, 8.9),
+        (79.2, 10.3), (79.8, 11.5), (80.3, 13.2), (80.2, 15.5), (82.3, 16.6),
+        (84.8, 19.2), (86.9, 20.5), (87.5, 21.6), (89.0, 21.9), (88.7, 23.0),
+        (88.1, 24.5), (88.5, 26.2), (89.8, 25.9), (92.0, 25.1), (92.4, 24.2),
+        (91.8, 23.0), (92.3, 21.5), (93.2, 22.2), (94.2, 23.9), (95.2, 26.6),
+        (97.4, 28.2), (96.0, 29.4), (92.0, 27.8), (92.0, 26.8), (89.8, 26.7),
+        (88.8, 27.3), (88.1, 26.4), (84.0, 27.4), (80.1, 28.8), (81.0, 30.2),
+        (79.5, 31.0), (78.7, 32.5), (79.0, 34.3), (77.8, 35.5),
+    ]]),
+    ("INDONESIA", &[
+        // Sumatra
+        &[
+            (95.3, 5.6), (97.5, 5.2), (100.3, 2.5), (103.8, 1.0), (104.5, -1.0),
+            (106.0, -3.0), (105.8, -5.9), (104.5, -5.9), (102.3, -4.0), (100.4, -1.0),
+            (98.7, 1.7), (95.2, 5.0), (95.3, 5.6),
+        ],
+        // Java
+        &[
+            (105.2, -6.8), (106.0, -5.9), (108.3, -6.2), (110.4, -6.9), (112.6, -6.9),
+            (114.5, -7.7), (114.4, -8.7), (112.0, -8.4), (108.0, -7.8), (106.4, -7.4),
+            (105.2, -6.8),
+        ],
+        // Kalimantan
+        &[
+            (109.6, 1.8), (111.0, 1.4), (112.5, 1.5), (114.5, 1.5), (115.5, 3.9),
+            (117.6, 4.2), (118.5, 1.0), (117.5, 0.0), (116.5, -2.0), (116.2, -3.9),
+            (114.5, -3.6), (111.5, -3.0), (110.2, -2.8), (109.0, -0.5), (109.0, 1.0),
+            (109.6, 1.8),
+        ],
+        // Sulawesi
+        &[
+            (118.8, -3.0), (119.4, -5.6), (120.5, -5.6), (121.3, -1.0), (123.3, -0.9),
+            (124.9, 1.5), (124.3, 1.0), (120.5, 0.5), (120.0, -0.8), (118.8, -3.0),
+        ],
+        // Papua
+        &[
+            (131.0, -1.2), (132.0, -0.4), (134.0, -0.9), (135.5, -3.3), (137.8, -1.5),
+            (141.0, -2.6), (141.0, -9.1), (139.0, -8.1), (138.0, -8.4), (137.6, -5.1),
+            (135.0, -4.4), (133.0, -4.0), (132.0, -2.9), (131.0, -1.2),
+        ],
+    ]),
+    ("IRAN", &[&[
+        (44.8, 37.2), (44.2, 39.4), (44.8, 39.7), (46.5, 38.9), (48.0, 38.4),
+        (48.9, 38.4), (49.0, 37.5), (50.3, 37.2), (53.9, 36.9), (54.0, 37.4),
+        (57.0, 38.2), (60.0, 36.6), (61.2, 36.6), (61.0, 34.5), (60.5, 33.5),
+        (60.9, 31.5), (61.7, 31.4), (62.5, 29.4), (61.6, 28.0), (63.3, 27.2),
+        (61.6, 25.2), (58.8, 25.6), (57.3, 25.8), (56.2, 27.2), (54.5, 26.6),
+        (51.5, 27.8), (50.2, 30.0), (48.9, 30.4), (48.5, 30.0), (47.6, 31.0),
+        (47.8, 32.5), (46.2, 33.2), (45.5, 34.0), (46.0, 35.0), (45.5, 35.8),
+        (44.8, 37.2),
+    ]]),
+    ("IRAQ", &[&[
+        (38.8, 33.4), (41.2, 34.4), (41.0, 36.0), (42.4, 37.1), (44.8, 37.2),
+        (45.5, 35.8), (46.0, 35.0), (45.5, 34.0), (46.2, 33.2), (47.8, 32.5),
+        (47.6, 31.0), (48.5, 30.0), (48.0, 29.9), (47.7, 29.2), (46.5, 29.1),
+        (44.7, 29.2), (42.1, 31.1), (39.3, 32.2), (38.8, 33.4),
+    ]]),
+    ("JAPAN", &[
+        // Hokkaido
+        &[
+            (140.0, 41.4), (141.2, 41.8), (143.3, 42.0), (145.8, 43.3), (145.3, 44.3),
+            (141.9, 45.5), (141.6, 44.0), (140.3, 43.3), (139.9, 42.5), (140.0, 41.4),
+        ],
+        // Honshu
+        &[
+            (140.9, 41.5), (141.5, 40.5), (142.0, 39.5), (141.0, 38.2), (140.9, 36.9),
+            (140.6, 35.7), (140.0, 34.9), (138.8, 34.6), (137.0, 34.6), (136.0, 33.4),
+            (135.0, 34.6), (133.0, 34.4), (130.9, 34.0), (131.5, 34.7), (133.0, 35.6),
+            (135.5, 35.6), (136.8, 37.3), (138.5, 37.5), (139.9, 39.5), (140.0, 40.5),
+            (140.3, 41.2), (140.9, 41.5),
+        ],
+        // Kyushu
+        &[
+            (130.0, 33.5), (130.9, 33.9), (131.9, 33.0), (131.3, 31.4), (130.6, 31.0),
+            (130.1, 32.0), (129.8, 33.0), (130.0, 33.5),
+        ],
+        // Shikoku
+        &[
+            (132.6, 33.9), (134.1, 34.4), (134.7, 34.2), (134.3, 33.2), (133.0, 32.8),
+            (132.0, 33.0), (132.6, 33.9),
+        ],
+    ]),
+    ("JORDAN", &[&[
+        (35.0, 29.4), (35.5, 31.5), (35.6, 32.7), (38.8, 33.4), (39.3, 32.2),
+        (37.0, 31.5), (38.0, 30.5), (36.5, 29.2), (35.0, 29.4),
+    ]]),
+    ("KENYA", &[&[
+        (34.0, 4.6), (35.8, 4.6), (37.0, 4.4), (39.0, 3.4), (40.8, 4.2),
+        (41.9, 3.9), (41.0, 2.8), (41.0, -0.9), (41.6, -1.7), (40.2, -2.8),
+        (39.2, -4.7), (37.6, -3.0), (34.0, -1.0), (34.0, 1.0), (35.0, 2.5),
+        (34.0, 4.6),
+    ]]),
+    ("MOROCCO", &[&[
+        (-2.2, 35.1), (-5.9, 35.8), (-6.3, 35.0), (-7.6, 33.6), (-9.8, 31.0),
+        (-9.6, 30.4), (-11.5, 28.3), (-13.2, 27.7), (-8.7, 27.7), (-8.7, 28.7),
+        (-5.0, 29.8), (-3.6, 30.9), (-1.8, 32.2), (-1.7, 34.0), (-2.2, 35.1),
+    ]]),
+    ("MOZAMBIQUE", &[&[
+        (32.9, -26.9), (31.9, -25.9), (31.3, -22.4), (32.5, -21.0), (32.9, -18.0),
+        (30.2, -16.0), (30.4, -15.0), (33.2, -14.0), (34.4, -14.4), (35.3, -17.1),
+        (35.8, -16.0), (35.7, -14.5), (34.6, -11.5), (35.9, -11.4), (38.5, -11.2),
+        (40.4, -10.5), (40.6, -14.5), (39.5, -16.8), (36.8, -18.5), (35.0, -20.0),
+        (35.5, -22.1), (35.5, -24.0), (32.6, -25.9), (32.9, -26.9),
+    ]]),
+    ("PERU", &[&[
+        (-70.0, -4.2), (-73.0, -5.0), (-74.0, -7.5), (-73.2, -9.4), (-72.4, -10.0),
+        (-69.6, -11.0), (-68.7, -12.5), (-69.0, -15.0), (-69.4, -16.0), (-69.5, -17.5),
+        (-70.4, -18.4), (-71.5, -17.3), (-76.2, -13.9), (-77.2, -12.0), (-79.5, -7.5),
+        (-81.3, -5.0), (-80.3, -3.4), (-79.0, -4.5), (-78.0, -3.0), (-75.5, -1.0),
+        (-75.2, -0.1), (-72.5, -2.4), (-70.0, -4.2),
+    ]]),
+    ("CHINA", &[
+        &[
+            (130.6, 42.4), (131.2, 43.0), (131.0, 44.9), (133.0, 45.0), (134.7, 48.4),
+            (131.0, 47.7), (127.5, 49.7), (125.0, 53.1), (121.0, 53.3), (120.0, 52.5),
+            (117.8, 49.5), (116.0, 47.0), (111.5, 43.5), (105.0, 41.6), (97.0, 42.7),
+            (95.5, 44.3), (90.5, 45.5), (91.0, 47.0), (87.8, 49.2), (87.3, 49.1),
+            (85.5, 47.0), (82.5, 45.5), (80.0, 44.9), (80.3, 42.8), (75.0, 40.5),
+            (73.5, 39.5), (74.8, 37.2), (75.0, 36.5), (77.8, 35.5), (79.0, 34.3),
+            (78.7, 32.5), (79.5, 31.0), (81.0, 30.2), (85.0, 28.3), (88.0, 27.9),
+            (88.8, 27.3), (92.0, 27.8), (96.0, 29.4), (97.4, 28.2), (98.7, 25.5),
+            (97.7, 24.0), (99.5, 22.2), (101.0, 21.5), (102.1, 22.4), (104.0, 22.8),
+            (106.5, 22.9), (108.0, 21.5), (109.7, 21.5), (110.2, 20.3), (111.0, 21.4),
+            (113.5, 22.2), (117.0, 23.5), (119.5, 25.5), (121.5, 28.3), (122.0, 30.8),
+            (120.8, 32.6), (119.3, 35.0), (120.7, 36.3), (122.5, 37.4), (119.0, 37.2),
+            (117.7, 38.8), (119.5, 39.9), (121.5, 40.9), (124.3, 39.9), (126.0, 41.5),
+            (128.0, 42.0), (130.6, 42.4),
+        ],
+        // Hainan
+        &[(108.6, 19.2), (110.0, 20.1), (111.0, 19.6), (109.6, 18.2), (108.6, 19.2)],
+    ]),
+    ("ROMANIA", &[&[
+        (20.2, 46.1), (21.0, 47.0), (22.9, 47.9), (24.9, 47.8), (26.6, 48.3),
+        (27.5, 47.5), (28.2, 46.5), (28.2, 45.5), (29.7, 45.2), (28.6, 44.0),
+        (28.6, 43.7), (27.0, 44.1), (25.0, 43.7), (23.0, 43.8), (22.5, 44.5),
+        (22.0, 44.6), (21.4, 44.8), (20.3, 45.8), (20.2, 46.1),
+    ]]),
+    ("SAUDI ARABIA", &[&[
+        (35.0, 29.4), (36.5, 29.2), (38.0, 30.5), (37.0, 31.5), (39.3, 32.2),
+        (42.1, 31.1), (44.7, 29.2), (46.5, 29.1), (48.4, 28.5), (49.5, 27.0),
+        (50.2, 26.2), (50.8, 24.7), (51.6, 24.2), (52.6, 22.9), (55.2, 22.7),
+        (55.7, 22.0), (55.0, 20.0), (52.0, 19.0), (49.0, 18.6), (46.5, 17.3),
+        (44.0, 17.4), (43.2, 16.7), (42.8, 16.4), (41.5, 18.6), (39.2, 21.5),
+        (37.0, 25.0), (35.5, 27.8), (34.6, 28.1), (35.0, 29.4),
+    ]]),
+    ("VIETNAM", &[&[
+        (102.1, 22.4), (104.0, 22.8), (106.5, 22.9), (108.0, 21.5), (106.8, 20.7),
+        (105.8, 19.0), (106.6, 17.5), (108.8, 15.3), (109.3, 13.0), (109.2, 11.5),
+        (107.0, 10.4), (105.0, 8.6), (104.8, 10.3), (105.0, 10.9), (106.2, 11.7),
+        (107.5, 12.3), (107.6, 14.5), (107.5, 15.5), (106.0, 17.0), (104.5, 18.5),
+        (103.8, 19.3), (104.5, 20.4), (103.0, 21.0), (102.1, 22.4),
+    ]]),
+    ("RUSSIA", &[&[
+        (28.0, 59.4), (29.5, 59.9), (27.8, 60.5), (30.0, 61.8), (29.5, 64.0),
+        (30.0, 67.0), (28.5, 68.5), (31.0, 69.8), (33.0, 69.4), (41.0, 67.8),
+        (44.0, 68.5), (53.0, 68.3), (60.0, 69.8), (68.0, 69.0), (73.0, 72.5),
+        (80.0, 73.5), (87.0, 75.0), (100.0, 76.5), (104.0, 77.7), (113.0, 73.5),
+        (130.0, 71.0), (140.0, 72.5), (160.0, 69.7), (170.0, 70.0), (180.0, 68.9),
+        (180.0, 65.0), (178.0, 62.5), (170.0, 60.0), (163.0, 59.5), (156.7, 51.0),
+        (156.0, 57.5), (160.0, 61.0), (152.0, 59.3), (143.0, 59.3), (137.5, 54.5),
+        (141.2, 52.8), (140.5, 48.5), (136.0, 44.0), (133.0, 42.8), (130.6, 42.4),
+        (131.2, 43.0), (131.0, 44.9), (133.0, 45.0), (134.7, 48.4), (131.0, 47.7),
+        (127.5, 49.7), (125.0, 53.1), (121.0, 53.3), (120.0, 52.5), (117.8, 49.5),
+        (116.0, 50.0), (110.0, 49.2), (106.5, 50.3), (102.0, 51.5), (98.0, 50.5),
+        (92.0, 50.7), (87.8, 49.2), (87.3, 49.1), (85.0, 50.0), (83.5, 51.0),
+        (80.0, 50.9), (77.8, 53.3), (76.5, 54.2), (73.5, 54.0), (70.0, 55.2),
+        (65.0, 54.6), (61.2, 54.0), (61.0, 52.0), (58.6, 51.2), (55.0, 50.6),
+        (50.5, 51.6), (48.7, 50.0), (46.5, 48.5), (47.2, 47.0), (47.5, 45.6),
+        (47.0, 44.5), (48.5, 41.9), (46.5, 41.9), (44.0, 42.7), (40.0, 43.4),
+        (36.7, 45.2), (38.2, 47.1), (39.8, 47.8), (40.0, 49.6), (38.0, 50.0),
+        (35.5, 51.2), (34.0, 52.2), (31.8, 52.1), (32.5, 53.0), (31.5, 54.5),
+        (30.8, 55.6), (28.2, 56.2), (27.7, 57.5), (28.0, 59.4),
+    ]]),
+    ("UNITED KINGDOM", &[
+        // Great Britain
+        &[
+            (-5.7, 50.0), (-3.5, 50.3), (-1.0, 50.7), (1.4, 51.2), (1.7, 52.7),
+            (0.3, 53.4), (-0.1, 54.5), (-1.5, 55.6), (-2.5, 56.6), (-1.8, 57.6),
+            (-3.0, 58.6), (-5.0, 58.6), (-6.2, 57.5), (-5.6, 56.3), (-5.0, 55.3),
+            (-4.9, 54.6), (-3.2, 54.9), (-3.5, 54.1), (-3.0, 53.3), (-4.6, 52.8),
+            (-4.1, 52.3), (-5.2, 51.8), (-3.0, 51.3), (-4.4, 51.1), (-5.7, 50.0),
+        ],
+        // Northern Ireland
+        &[
+            (-8.2, 54.5), (-7.3, 55.3), (-6.0, 55.2), (-5.5, 54.3), (-6.3, 54.1),
+            (-7.5, 54.1), (-8.2, 54.5),
+        ],
+    ]),
+    ("UNITED STATES", &[&[
+        (-124.7, 48.4), (-123.0, 49.0), (-95.2, 49.0), (-95.2, 49.4), (-89.6, 48.0),
+        (-84.8, 46.5), (-82.5, 45.3), (-82.4, 43.0), (-83.1, 42.1), (-81.0, 42.2),
+        (-79.0, 42.7), (-79.1, 43.3), (-76.3, 44.1), (-74.7, 45.0), (-71.5, 45.0),
+        (-70.0, 46.7), (-69.2, 47.4), (-67.8, 47.0), (-67.8, 45.7), (-67.0, 44.9),
+        (-70.2, 43.6), (-70.0, 41.7), (-74.0, 40.5), (-75.5, 38.5), (-75.5, 35.2),
+        (-81.0, 31.5), (-80.0, 26.5), (-80.4, 25.2), (-81.8, 26.0), (-82.7, 27.8),
+        (-84.0, 30.0), (-89.0, 30.2), (-94.0, 29.6), (-97.2, 27.6), (-97.2, 25.9),
+        (-99.5, 27.5), (-101.4, 29.8), (-103.0, 29.0), (-104.7, 30.0), (-106.5, 31.8),
+        (-108.2, 31.3), (-111.0, 31.3), (-114.8, 32.5), (-117.1, 32.5), (-120.6, 34.5),
+        (-122.5, 37.5), (-124.2, 40.4), (-124.4, 43.0), (-124.0, 46.3), (-124.7, 48.4),
+    ]]),
+];
@@ -1,10 +1,20 @@
-use crate::spatial::SpatialGenerator;
+use crate::spatial::{FootprintOptions, Region, SpatialGenerator, Validity};
+use crate::zone::{ZoneConfig, ZonePlacement};
 use once_cell::sync::OnceCell;
 
 #[derive(Clone, Default)]
 pub struct SpatialOverrides {
     pub trip: Option<SpatialGenerator>,
     pub building: Option<SpatialGenerator>,
+    pub customer: Option<SpatialGenerator>,
+    pub driver: Option<SpatialGenerator>,
+    pub zone: Option<ZoneConfig>,
+    pub trip_zones: Option<ZonePlacement>,
+    pub regions: Option<Vec<Region>>,
+    pub land_mask: bool,
+    pub footprint: Option<FootprintOptions>,
+    pub validity: Option<Validity>,
+    pub non_overlapping: bool,
 }
 
 static OVERRIDES: OnceCell<SpatialOverrides> = OnceCell::new();
@@ -26,3 +36,42 @@ pub fn building_or_default<F: FnOnce() -> SpatialGenerator>(fallback: F) -> Spat
         .and_then(|o| o.building.clone())
         .unwrap_or_else(fallback)
 }
+
+pub fn customer_locations() -> Option<SpatialGenerator> {
+    OVERRIDES.get().and_then(|o| o.customer.clone())
+}
+
+pub fn driver_locations() -> Option<SpatialGenerator> {
+    OVERRIDES.get().and_then(|o| o.driver.clone())
+}
+
+pub fn zone_config_or_default() -> ZoneConfig {
+    OVERRIDES
+        .get()
+        .and_then(|o| o.zone.clone())
+        .unwrap_or_default()
+}
+
+pub fn trip_zone_placement() -> Option<ZonePlacement> {
+    OVERRIDES.get().and_then(|o| o.trip_zones.clone())
+}
+
+pub fn regions() -> Option<Vec<Region>> {
+    OVERRIDES.get().and_then(|o| o.regions.clone())
+}
+
+pub fn land_mask() -> bool {
+    OVERRIDES.get().is_some_and(|o| o.land_mask)
+}
+
+pub fn footprint() -> Option<FootprintOptions> {
+    OVERRIDES.get().and_then(|o| o.footprint)
+}
+
+pub fn validity() -> Option<Validity> {
+    OVERRIDES.get().and_then(|o| o.validity)
+}
+
+pub fn non_overlapping() -> bool {
+    OVERRIDES.get().is_some_and(|o| o.non_overlapping)
+}
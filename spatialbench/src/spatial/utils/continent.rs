@@ -1,4 +1,4 @@
-use crate::spatial::ContinentAffines;
+use crate::spatial::{ContinentAffines, Region};
 use std::f64::consts::PI;
 
 #[derive(Clone, Debug)]
@@ -36,15 +36,38 @@ pub fn build_continent_cdf(aff: &ContinentAffines) -> Vec<(&str, [f64; 6], f64)>
         ("north_north_america", &aff.north_north_america),
     ];
 
-    let mut targets: Vec<(&str, [f64; 6], f64)> = items
+    let targets: Vec<(&str, [f64; 6], f64)> = items
         .iter()
-        .map(|(name, m)| {
-            let (w, e, s, n) = bbox_from_affine(m);
-            let wt = spherical_bbox_weight(w, e, s, n);
-            (*name, **m, wt)
+        .map(|(name, m)| (*name, **m, affine_weight(m)))
+        .collect();
+    cumulative_targets(targets)
+}
+
+/// Builds the cumulative distribution over user-defined regions, weighted by
+/// their explicit weight or else by the spherical area of their bounding box
+pub fn build_region_cdf(regions: &[Region]) -> Vec<WeightedTarget> {
+    let targets: Vec<(&str, [f64; 6], f64)> = regions
+        .iter()
+        .map(|r| {
+            let weight = r.weight.unwrap_or_else(|| affine_weight(&r.affine));
+            (r.name.as_str(), r.affine, weight)
         })
         .collect();
+    cumulative_targets(targets)
+        .into_iter()
+        .map(|(_name, m, cdf)| WeightedTarget { m, cdf })
+        .collect()
+}
+
+#[inline]
+fn affine_weight(m: &[f64; 6]) -> f64 {
+    let (w, e, s, n) = bbox_from_affine(m);
+    spherical_bbox_weight(w, e, s, n)
+}
 
+/// Sorts the targets by descending weight and replaces the weights by their
+/// normalized cumulative sums
+fn cumulative_targets(mut targets: Vec<(&str, [f64; 6], f64)>) -> Vec<(&str, [f64; 6], f64)> {
     targets.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
 
     let total: f64 = targets.iter().map(|(_, _, w)| w).sum();
@@ -1,7 +1,9 @@
 pub mod affine;
 pub mod continent;
 pub mod random;
+pub mod units;
 
 pub use affine::*;
 pub use continent::*;
 pub use random::*;
+pub use units::*;
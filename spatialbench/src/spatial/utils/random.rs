@@ -1,3 +1,4 @@
+use crate::spatial::utils::meters_to_degrees;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 use std::f64::consts::PI;
@@ -50,6 +51,21 @@ pub fn gauss_around(rng: &mut StdRng, center: (f64, f64), sigma: f64) -> (f64, f
     (unit_clamp(cx + dx), unit_clamp(cy + dy))
 }
 
+/// Returns the lon/lat at a Gaussian offset of `sigma` meters east and north of
+/// the center, with the longitude wrapped across the antimeridian and the
+/// latitude clamped to the poles
+pub fn gauss_around_meters(rng: &mut StdRng, (lon, lat): (f64, f64), sigma: f64) -> (f64, f64) {
+    let (dlon, dlat) = meters_to_degrees(
+        rand_normal(rng, 0.0, sigma),
+        rand_normal(rng, 0.0, sigma),
+        lat,
+    );
+    (
+        (lon + dlon + 180.0).rem_euclid(360.0) - 180.0,
+        (lat + dlat).clamp(-90.0, 90.0),
+    )
+}
+
 pub fn halton_2d(i: u64, base_x: u32, base_y: u32) -> (f64, f64) {
     (radical_inverse(i, base_x), radical_inverse(i, base_y))
 }
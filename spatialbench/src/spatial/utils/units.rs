@@ -0,0 +1,32 @@
+//! Conversions between meters and lon/lat degrees on a spherical Earth
+
+/// Meters per degree of latitude, and of longitude at the equator, on a sphere
+/// of the mean Earth radius (6,371,008.8 m)
+pub const METERS_PER_DEGREE: f64 = 111_195.08;
+
+/// Returns the lon/lat offsets in degrees of an offset of `east` and `north`
+/// meters at latitude `lat`
+///
+/// Longitude degrees shrink with the cosine of the latitude, which is bounded
+/// away from zero so offsets stay finite at the poles.
+pub fn meters_to_degrees(east: f64, north: f64, lat: f64) -> (f64, f64) {
+    let cos_lat = lat.to_radians().cos().max(0.01);
+    (
+        east / (METERS_PER_DEGREE * cos_lat),
+        north / METERS_PER_DEGREE,
+    )
+}
+
+/// Returns the affine mapping the unit square to a square of `extent` meters
+/// centered on the given lon/lat
+pub fn meters_affine(lon: f64, lat: f64, extent: f64) -> [f64; 6] {
+    let (width, height) = meters_to_degrees(extent, extent, lat);
+    [
+        width,
+        0.0,
+        lon - width / 2.0,
+        0.0,
+        height,
+        lat - height / 2.0,
+    ]
+}
@@ -0,0 +1,316 @@
+//! Local cache of the Zone table, so the zone source is scanned once per run.
+//!
+//! The first part that needs zones streams every matching division area from the
+//! [`ZoneSource`] into a DuckDB file in the cache directory, in key order, and then
+//! assigns parents one level at a time. Every `(part, part_count)` slice, in this process or
+//! a later one, is then a key range read from that file.
+//!
+//! A cache file is keyed by the source (including the Overture release, or the path,
+//! size and modification time of every local file read) and the subtype set. The
+//! scale factor only limits how many rows are read, so all scale factors with the
+//! same subtypes share one file. Glob sources are not cached, as the files they
+//! match cannot be fingerprinted.
+
+use crate::error::Result;
+use crate::generators::{Zone, ZoneWkb};
+use crate::zone::{hierarchy, subtype_rank, ZoneSource};
+use duckdb::{params, AccessMode, Config, Connection};
+use geo::{InteriorPoint, Point};
+use geozero::wkb::Wkb;
+use geozero::{CoordDimensions, ToGeo, ToWkb};
+use log::{debug, info};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Instant, UNIX_EPOCH};
+
+/// Version of the cache file layout, part of the cache key
+const CACHE_FORMAT_VERSION: u32 = 1;
+
+/// Receives the zones of a new cache file from the function building it
+pub type ZoneSink<'a> = dyn FnMut(Zone) -> Result<()> + 'a;
+
+/// Read-only connections to the cache files opened by this process
+static CONNECTIONS: OnceLock<Mutex<HashMap<PathBuf, Connection>>> = OnceLock::new();
+
+/// Returns the path of the cache file for the zones of `subtypes` read from `source`,
+/// or `None` if the files of the source cannot be fingerprinted
+pub fn cache_path(dir: &Path, source: &ZoneSource, subtypes: &[&str]) -> Option<PathBuf> {
+    let mut subtypes = subtypes.to_vec();
+    subtypes.sort_by_key(|subtype| subtype_rank(subtype));
+    let key = format!(
+        "v{}|{}|{}|{}",
+        CACHE_FORMAT_VERSION,
+        source,
+        source_fingerprint(source)?,
+        subtypes.join(",")
+    );
+    Some(dir.join(format!("zones-{:016x}.duckdb", fnv1a(key.as_bytes()))))
+}
+
+/// Returns the zones with keys `offset + 1..=offset + count` from the cache file at
+/// `path`, first creating the file from the zones passed by `build` if needed.
+///
+/// `build` must pass every zone of the Zone table to the sink it is given, in key
+/// order; their parent keys are ignored and assigned in the cache.
+pub fn load_zones<F>(path: &Path, offset: i64, count: i64, build: F) -> Result<Vec<Zone>>
+where
+    F: FnOnce(&mut ZoneSink) -> Result<()>,
+{
+    read_zones(&connection(path, build)?, offset, count)?
+        .into_iter()
+        .map(|zone| Ok(zone.into_zone()?))
+        .collect()
+}
+
+/// Like [`load_zones`], but returns the boundaries as stored in the cache, as WKB
+pub fn load_zone_wkbs<F>(path: &Path, offset: i64, count: i64, build: F) -> Result<Vec<ZoneWkb>>
+where
+    F: FnOnce(&mut ZoneSink) -> Result<()>,
+{
+    read_zones(&connection(path, build)?, offset, count)
+}
+
+/// Returns the number of zones in the cache file at `path`, first creating the
+/// file from the zones passed by `build` if needed.
+pub fn count_zones<F>(path: &Path, build: F) -> Result<i64>
+where
+    F: FnOnce(&mut ZoneSink) -> Result<()>,
+{
+    let conn = connection(path, build)?;
+    let count = conn.query_row("SELECT count(*) FROM zones", [], |row| row.get(0))?;
+    Ok(count)
+}
+
+/// Returns a read-only connection to the cache file at `path`, creating the file
+/// from the zones passed by `build` if it does not exist
+fn connection<F>(path: &Path, build: F) -> Result<Connection>
+where
+    F: FnOnce(&mut ZoneSink) -> Result<()>,
+{
+    // Held while building, so concurrent parts wait for the first one instead of
+    // scanning the source again
+    let connections = CONNECTIONS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut connections = connections.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(conn) = connections.get(path) {
+        return Ok(conn.try_clone()?);
+    }
+
+    if path.exists() {
+        info!("Reading zones from cache {}", path.display());
+    } else {
+        write_cache(path, build)?;
+    }
+    let conn =
+        Connection::open_with_flags(path, Config::default().access_mode(AccessMode::ReadOnly)?)?;
+    let clone = conn.try_clone()?;
+    connections.insert(path.to_path_buf(), conn);
+    Ok(clone)
+}
+
+/// Writes the complete Zone table to a new cache file at `path`
+///
+/// The zones passed by `build` are appended as they arrive, keeping only their
+/// interior points, and their parents are then assigned one level at a time (see
+/// [`hierarchy::parent_keys`]), so the boundaries of the whole table are never
+/// held in memory. The table is written to a temporary file that is then
+/// renamed, so other processes never see a partially written cache.
+fn write_cache<F>(path: &Path, build: F) -> Result<()>
+where
+    F: FnOnce(&mut ZoneSink) -> Result<()>,
+{
+    let start = Instant::now();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp_path = path.with_extension(format!("duckdb.{}.tmp", std::process::id()));
+    if tmp_path.exists() {
+        fs::remove_file(&tmp_path)?;
+    }
+
+    let points = {
+        let conn = Connection::open(&tmp_path)?;
+        conn.execute_batch(
+            "CREATE TABLE zones (
+                z_zonekey BIGINT PRIMARY KEY,
+                z_gersid VARCHAR NOT NULL,
+                z_country VARCHAR NOT NULL,
+                z_region VARCHAR NOT NULL,
+                z_name VARCHAR NOT NULL,
+                z_subtype VARCHAR NOT NULL,
+                z_level INTEGER NOT NULL,
+                z_parentkey BIGINT,
+                z_boundary BLOB NOT NULL
+            );",
+        )?;
+        let mut points = Vec::new();
+        {
+            let mut appender = conn.appender("zones")?;
+            build(&mut |zone: Zone| {
+                let wkb = zone.z_boundary.to_wkb(CoordDimensions::xy())?;
+                points.push((
+                    zone.z_zonekey,
+                    zone.z_level,
+                    zone.z_boundary.interior_point(),
+                ));
+                appender.append_row(params![
+                    zone.z_zonekey,
+                    zone.z_gersid,
+                    zone.z_country,
+                    zone.z_region,
+                    zone.z_name,
+                    zone.z_subtype,
+                    zone.z_level,
+                    None::<i64>,
+                    wkb
+                ])?;
+                Ok(())
+            })?;
+            appender.flush()?;
+        }
+        write_parent_keys(&conn, &points)?;
+        points
+    };
+
+    fs::rename(&tmp_path, path)?;
+    info!(
+        "Cached {} zones in {} in {:?}",
+        points.len(),
+        path.display(),
+        start.elapsed()
+    );
+    Ok(())
+}
+
+/// Assigns the parent keys of the zones in a new cache file, given the key, level
+/// and interior point of every zone in key order
+fn write_parent_keys(conn: &Connection, points: &[(i64, i32, Option<Point>)]) -> Result<()> {
+    let start = Instant::now();
+    let parents = hierarchy::parent_keys(points, |level| {
+        let mut stmt = conn.prepare(
+            "SELECT z_zonekey, z_boundary FROM zones WHERE z_level = ? ORDER BY z_zonekey",
+        )?;
+        let mut rows = stmt.query(params![level])?;
+        let mut zones = Vec::new();
+        while let Some(row) = rows.next()? {
+            let wkb: Vec<u8> = row.get(1)?;
+            zones.push((row.get(0)?, Wkb(&wkb).to_geo()?));
+        }
+        Ok(zones)
+    })?;
+
+    conn.execute_batch("CREATE TABLE parents (z_zonekey BIGINT, z_parentkey BIGINT);")?;
+    {
+        let mut appender = conn.appender("parents")?;
+        for (&(key, _, _), parent) in points.iter().zip(parents) {
+            if let Some(parent) = parent {
+                appender.append_row(params![key, parent])?;
+            }
+        }
+        appender.flush()?;
+    }
+    conn.execute_batch(
+        "UPDATE zones SET z_parentkey = parents.z_parentkey
+         FROM parents WHERE zones.z_zonekey = parents.z_zonekey;
+         DROP TABLE parents;",
+    )?;
+    debug!("Assigned parent zones in {:?}", start.elapsed());
+    Ok(())
+}
+
+/// Reads the zones with keys `offset + 1..=offset + count`, in key order, with
+/// their boundaries as WKB
+fn read_zones(conn: &Connection, offset: i64, count: i64) -> Result<Vec<ZoneWkb>> {
+    let start = Instant::now();
+    let mut stmt = conn.prepare(
+        "SELECT z_zonekey, z_gersid, z_country, z_region, z_name, z_subtype,
+                z_level, z_parentkey, z_boundary
+         FROM zones
+         WHERE z_zonekey > ? AND z_zonekey <= ?
+         ORDER BY z_zonekey",
+    )?;
+    let mut rows = stmt.query(params![offset, offset + count])?;
+
+    let mut zones = Vec::new();
+    while let Some(row) = rows.next()? {
+        zones.push(ZoneWkb {
+            z_zonekey: row.get(0)?,
+            z_gersid: row.get(1)?,
+            z_country: row.get(2)?,
+            z_region: row.get(3)?,
+            z_name: row.get(4)?,
+            z_subtype: row.get(5)?,
+            z_level: row.get(6)?,
+            z_parentkey: row.get(7)?,
+            z_boundary: row.get(8)?,
+            z_bbox: None,
+        });
+    }
+    debug!("Read {} cached zones in {:?}", zones.len(), start.elapsed());
+    Ok(zones)
+}
+
+/// Identifies the contents of the local files of a source, so the cache is
+/// rebuilt when any of them changes, or returns `None` for a glob (or a missing
+/// path), whose files are only known to DuckDB
+///
+/// A directory is fingerprinted by every parquet file under it, as editing a
+/// file does not change the modification time of its directory.
+fn source_fingerprint(source: &ZoneSource) -> Option<String> {
+    let path = match source {
+        ZoneSource::Parquet(path) | ZoneSource::GeoJson(path) | ZoneSource::NdJson(path) => {
+            Path::new(path)
+        }
+        ZoneSource::Overture { .. } | ZoneSource::Synthetic { .. } => return Some(String::new()),
+    };
+    let mut files = Vec::new();
+    if path.is_dir() {
+        collect_parquet_files(path, &mut files).ok()?;
+        files.sort();
+    } else if path.is_file() {
+        files.push(path.to_path_buf());
+    } else {
+        return None;
+    }
+
+    let fingerprints = files
+        .iter()
+        .map(|file| {
+            let metadata = fs::metadata(file).ok()?;
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_nanos())
+                .unwrap_or_default();
+            Some(format!(
+                "{}:{}@{}",
+                file.display(),
+                metadata.len(),
+                modified
+            ))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(fingerprints.join(";"))
+}
+
+/// Adds the parquet files under `dir`, recursively, to `files`
+fn collect_parquet_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_parquet_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "parquet") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 64-bit FNV-1a hash, stable across builds unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
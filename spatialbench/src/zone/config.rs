@@ -0,0 +1,110 @@
+//! [`ZoneConfig`]: which division areas the Zone table is built from.
+
+use crate::zone::{subtype_rank, ZoneGeometryOptions, ZoneSource, ZONE_SUBTYPES};
+use std::path::PathBuf;
+
+/// Subtypes included in the Zone table from a scale factor upwards
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubtypeTier {
+    /// Smallest scale factor that includes the subtypes of this tier
+    pub min_scale_factor: f64,
+    /// Overture `division_area` subtypes added by this tier
+    pub subtypes: Vec<String>,
+}
+
+impl SubtypeTier {
+    pub fn new(min_scale_factor: f64, subtypes: &[&str]) -> Self {
+        Self {
+            min_scale_factor,
+            subtypes: subtypes.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+/// Configuration of the Zone table: the zone source (and so the Overture release),
+/// the subtype mix at each scale factor, where the loaded table is cached, and how
+/// boundaries are processed.
+///
+/// The number of zones is not configured: it is counted in the source at load time
+/// (scaled down below scale factor 1), so it always matches the release in use.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneConfig {
+    /// Where division areas are read from
+    pub source: ZoneSource,
+    /// Subtype tiers; a scale factor includes the subtypes of every tier whose
+    /// `min_scale_factor` it reaches
+    pub subtype_tiers: Vec<SubtypeTier>,
+    /// Directory of the local zone cache, or `None` (the default) to read the
+    /// source without one
+    pub cache_dir: Option<PathBuf>,
+    /// Processing applied to the boundaries of each run
+    pub geometry: ZoneGeometryOptions,
+}
+
+impl Default for ZoneConfig {
+    fn default() -> Self {
+        Self {
+            source: ZoneSource::default(),
+            subtype_tiers: Self::default_subtype_tiers(),
+            cache_dir: None,
+            geometry: ZoneGeometryOptions::default(),
+        }
+    }
+}
+
+impl ZoneConfig {
+    /// Subtype tiers of the reference benchmark: fine-grained zones at small scale
+    /// factors, coarser administrative units added at larger ones
+    pub fn default_subtype_tiers() -> Vec<SubtypeTier> {
+        vec![
+            SubtypeTier::new(0.0, &["microhood", "macrohood", "county"]),
+            SubtypeTier::new(10.0, &["neighborhood"]),
+            SubtypeTier::new(100.0, &["localadmin", "locality", "region", "dependency"]),
+            SubtypeTier::new(1000.0, &["country"]),
+        ]
+    }
+
+    /// Returns the subtypes included at a scale factor, in tier order
+    pub fn subtypes_for_scale_factor(&self, scale_factor: f64) -> Vec<&str> {
+        let mut subtypes: Vec<&str> = Vec::new();
+        for tier in &self.subtype_tiers {
+            if scale_factor < tier.min_scale_factor {
+                continue;
+            }
+            for subtype in &tier.subtypes {
+                if !subtypes.contains(&subtype.as_str()) {
+                    subtypes.push(subtype);
+                }
+            }
+        }
+        subtypes
+    }
+
+    /// Checks that every tier has a valid scale factor and known subtypes, and that
+    /// the geometry options are valid
+    pub fn validate(&self) -> Result<(), String> {
+        self.geometry.validate()?;
+        for tier in &self.subtype_tiers {
+            if !tier.min_scale_factor.is_finite() || tier.min_scale_factor < 0.0 {
+                return Err(format!(
+                    "Invalid zone subtype tier scale factor {}",
+                    tier.min_scale_factor
+                ));
+            }
+            for subtype in &tier.subtypes {
+                if subtype_rank(subtype) == ZONE_SUBTYPES.len() {
+                    return Err(format!(
+                        "Unknown zone subtype '{}'. Expected one of: {}",
+                        subtype,
+                        ZONE_SUBTYPES
+                            .iter()
+                            .map(|(name, _)| *name)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+//! Optional processing of zone boundaries: simplification, subdivision of polygons
+//! with many vertices, and bounding boxes.
+//!
+//! Real Overture boundaries can have hundreds of thousands of vertices, so queries
+//! over zones tend to be dominated by a few huge polygons. Processing is applied to
+//! each zone of a run after loading, so the cached table always holds the source
+//! boundaries and parent keys are computed from them.
+
+use crate::generators::Zone;
+use geo::{
+    BooleanOps, BoundingRect, CoordsIter, Geometry, MultiPolygon, Polygon, Rect, SimplifyVwPreserve,
+};
+
+/// Smallest vertex budget accepted for [`ZoneGeometryOptions::max_vertices`]
+pub const MIN_MAX_VERTICES: usize = 8;
+
+/// Subdivision stops after this many halvings, even if pieces are over budget
+const MAX_SUBDIVIDE_DEPTH: u32 = 24;
+
+/// Processing applied to zone boundaries, none by default
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ZoneGeometryOptions {
+    /// Simplify boundaries, preserving topology, by removing vertices that form a
+    /// triangle of area below `tolerance²` (in degrees) with their neighbors
+    pub simplify_tolerance: Option<f64>,
+    /// Subdivide polygons with more vertices into pieces of at most this many
+    /// vertices; the zone boundary becomes a `MultiPolygon` of the pieces. Clipping
+    /// snaps the vertices of the pieces to a fine grid (around 1e-10 degrees).
+    pub max_vertices: Option<usize>,
+    /// Fill `z_bbox` with the bounding box of the (processed) boundary
+    pub bbox: bool,
+}
+
+impl ZoneGeometryOptions {
+    /// Returns true if the options leave zones unchanged
+    pub fn is_identity(&self) -> bool {
+        self.simplify_tolerance.is_none() && self.max_vertices.is_none() && !self.bbox
+    }
+
+    /// Checks that the tolerance and vertex budget are usable
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(tolerance) = self.simplify_tolerance {
+            if !tolerance.is_finite() || tolerance <= 0.0 {
+                return Err(format!(
+                    "Invalid zone simplify tolerance {tolerance}, expected a positive number"
+                ));
+            }
+        }
+        if let Some(max_vertices) = self.max_vertices {
+            if max_vertices < MIN_MAX_VERTICES {
+                return Err(format!(
+                    "Invalid zone max vertices {max_vertices}, expected at least {MIN_MAX_VERTICES}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the options to the boundary and bounding box of a zone
+    pub fn apply(&self, zone: &mut Zone) {
+        if let Some(tolerance) = self.simplify_tolerance {
+            zone.z_boundary = simplify(&zone.z_boundary, tolerance * tolerance);
+        }
+        if let Some(max_vertices) = self.max_vertices {
+            zone.z_boundary = subdivide(&zone.z_boundary, max_vertices);
+        }
+        if self.bbox {
+            zone.z_bbox = zone.z_boundary.bounding_rect();
+        }
+    }
+}
+
+/// Topology preserving Visvalingam-Whyatt simplification of polygonal boundaries
+fn simplify(geometry: &Geometry, epsilon: f64) -> Geometry {
+    match geometry {
+        Geometry::Polygon(polygon) => Geometry::Polygon(polygon.simplify_vw_preserve(&epsilon)),
+        Geometry::MultiPolygon(multi) => {
+            Geometry::MultiPolygon(multi.simplify_vw_preserve(&epsilon))
+        }
+        other => other.clone(),
+    }
+}
+
+/// Splits the polygons of a boundary with more than `max_vertices` vertices
+fn subdivide(geometry: &Geometry, max_vertices: usize) -> Geometry {
+    let polygons: Vec<&Polygon> = match geometry {
+        Geometry::Polygon(polygon) => vec![polygon],
+        Geometry::MultiPolygon(multi) => multi.iter().collect(),
+        _ => return geometry.clone(),
+    };
+    if polygons.iter().all(|p| p.coords_count() <= max_vertices) {
+        return geometry.clone();
+    }
+
+    let mut pieces = Vec::new();
+    for polygon in polygons {
+        subdivide_polygon(polygon.clone(), max_vertices, 0, &mut pieces);
+    }
+    Geometry::MultiPolygon(MultiPolygon::new(pieces))
+}
+
+/// Recursively halves a polygon along the longer side of its bounding box until
+/// every piece is within the vertex budget
+fn subdivide_polygon(polygon: Polygon, max_vertices: usize, depth: u32, out: &mut Vec<Polygon>) {
+    if polygon.coords_count() <= max_vertices || depth >= MAX_SUBDIVIDE_DEPTH {
+        out.push(polygon);
+        return;
+    }
+    let Some(rect) = polygon.bounding_rect() else {
+        out.push(polygon);
+        return;
+    };
+
+    let (min, max) = (rect.min(), rect.max());
+    let halves = if rect.width() >= rect.height() {
+        let mid = (min.x + max.x) / 2.0;
+        [
+            Rect::new(min, (mid, max.y).into()),
+            Rect::new((mid, min.y).into(), max),
+        ]
+    } else {
+        let mid = (min.y + max.y) / 2.0;
+        [
+            Rect::new(min, (max.x, mid).into()),
+            Rect::new((min.x, mid).into(), max),
+        ]
+    };
+    for half in halves {
+        for piece in polygon.intersection(&half.to_polygon()) {
+            subdivide_polygon(piece, max_vertices, depth + 1, out);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Zone hierarchy: links each zone to its parent by spatial containment.
+
+use crate::error::Result;
+use crate::generators::Zone;
+use geo::{BoundingRect, Geometry, InteriorPoint, Intersects, Point, Rect};
+use std::ops::Range;
+
+/// Sets `z_parentkey` of each zone to the key of the zone of the nearest coarser
+/// level that contains the zone's interior point.
+///
+/// `zones` must be the Zone table sorted by `z_level`, or the rows of it up to some
+/// key, which hold every coarser level of the zones they include. If several zones
+/// of a level contain the point (Overture areas may overlap), the one with the
+/// smallest key is used. Zones of the coarsest level, or without any containing
+/// zone, have no parent.
+pub fn assign_parent_keys(zones: &mut [Zone]) {
+    let mut levels: Vec<Range<usize>> = Vec::new();
+    for (i, zone) in zones.iter().enumerate() {
+        match levels.last_mut() {
+            Some(level) if zones[level.start].z_level == zone.z_level => level.end = i + 1,
+            _ => levels.push(i..i + 1),
+        }
+    }
+
+    let indexes: Vec<GridIndex> = levels
+        .iter()
+        .map(|level| GridIndex::new(zones[level.clone()].iter().map(|z| &z.z_boundary)))
+        .collect();
+
+    let mut parents = vec![None; zones.len()];
+    for (l, level) in levels.iter().enumerate().skip(1) {
+        for i in level.clone() {
+            let Some(point) = zones[i].z_boundary.interior_point() else {
+                continue;
+            };
+            parents[i] =
+                levels[..l]
+                    .iter()
+                    .zip(&indexes[..l])
+                    .rev()
+                    .find_map(|(parent_level, index)| {
+                        let parents = &zones[parent_level.clone()];
+                        index
+                            .find(point, |p| parents[p].z_boundary.intersects(&point))
+                            .map(|p| parents[p].z_zonekey)
+                    });
+        }
+    }
+
+    for (zone, parent) in zones.iter_mut().zip(parents) {
+        zone.z_parentkey = parent;
+    }
+}
+
+/// Returns the parent key of each zone of a Zone table, like
+/// [`assign_parent_keys`], without holding all the boundaries in memory
+///
+/// `points` holds the key, level and interior point of every zone, sorted by
+/// level, and `load_level` returns the keys and boundaries of the zones of a
+/// level in key order. Only the boundaries of one level are held at a time.
+pub fn parent_keys<F>(
+    points: &[(i64, i32, Option<Point>)],
+    mut load_level: F,
+) -> Result<Vec<Option<i64>>>
+where
+    F: FnMut(i32) -> Result<Vec<(i64, Geometry)>>,
+{
+    let mut levels: Vec<i32> = points.iter().map(|(_, level, _)| *level).collect();
+    levels.dedup();
+
+    // from the finest parent level to the coarsest, so each zone gets the
+    // nearest coarser level that contains it
+    let mut parents = vec![None; points.len()];
+    for &parent_level in levels.iter().rev().skip(1) {
+        let zones = load_level(parent_level)?;
+        let index = GridIndex::new(zones.iter().map(|(_, boundary)| boundary));
+        for (parent, &(_, level, point)) in parents.iter_mut().zip(points) {
+            let Some(point) = point.filter(|_| level > parent_level && parent.is_none()) else {
+                continue;
+            };
+            *parent = index
+                .find(point, |p| zones[p].1.intersects(&point))
+                .map(|p| zones[p].0);
+        }
+    }
+    Ok(parents)
+}
+
+/// Uniform grid over the bounding boxes of the zones of one level
+struct GridIndex {
+    min: (f64, f64),
+    cell_size: (f64, f64),
+    nx: usize,
+    ny: usize,
+    /// Positions of the zones whose bounding box overlaps each cell, in ascending
+    /// order
+    cells: Vec<Vec<usize>>,
+}
+
+impl GridIndex {
+    fn new<'a>(boundaries: impl Iterator<Item = &'a Geometry>) -> Self {
+        let rects: Vec<(usize, Rect)> = boundaries
+            .enumerate()
+            .filter_map(|(i, boundary)| boundary.bounding_rect().map(|r| (i, r)))
+            .collect();
+
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for (_, r) in &rects {
+            min_x = min_x.min(r.min().x);
+            min_y = min_y.min(r.min().y);
+            max_x = max_x.max(r.max().x);
+            max_y = max_y.max(r.max().y);
+        }
+        // about one zone per cell
+        let n = (rects.len() as f64).sqrt().ceil().max(1.0) as usize;
+        let cell_size = (
+            ((max_x - min_x) / n as f64).max(1e-9),
+            ((max_y - min_y) / n as f64).max(1e-9),
+        );
+
+        let mut index = GridIndex {
+            min: (min_x, min_y),
+            cell_size,
+            nx: n,
+            ny: n,
+            cells: vec![Vec::new(); n * n],
+        };
+        for (i, r) in rects {
+            let (x0, y0) = index.cell(r.min().x, r.min().y);
+            let (x1, y1) = index.cell(r.max().x, r.max().y);
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    index.cells[y * index.nx + x].push(i);
+                }
+            }
+        }
+        index
+    }
+
+    fn cell(&self, x: f64, y: f64) -> (usize, usize) {
+        let cx = ((x - self.min.0) / self.cell_size.0).floor().max(0.0) as usize;
+        let cy = ((y - self.min.1) / self.cell_size.1).floor().max(0.0) as usize;
+        (cx.min(self.nx - 1), cy.min(self.ny - 1))
+    }
+
+    /// Returns the position of the first zone of this level for which
+    /// `contains` holds, among the zones whose bounding box may hold `point`
+    fn find(&self, point: Point, contains: impl Fn(usize) -> bool) -> Option<usize> {
+        let (x, y) = self.cell(point.x(), point.y());
+        self.cells[y * self.nx + x]
+            .iter()
+            .copied()
+            .find(|&i| contains(i))
+    }
+}
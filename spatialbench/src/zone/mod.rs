@@ -0,0 +1,49 @@
+//! Support code for the Zone table, which is loaded from Overture `division_area` data
+//! or synthesized from the continent regions.
+
+pub mod cache;
+pub mod config;
+pub mod geometry;
+pub mod hierarchy;
+pub mod placement;
+pub mod source;
+pub mod synthetic;
+
+pub use config::{SubtypeTier, ZoneConfig};
+pub use geometry::ZoneGeometryOptions;
+pub use placement::{ZonePlacement, ZoneSampler, ZoneWeight};
+pub use source::{GeometryEncoding, ZoneSource};
+
+/// Overture `division_area` subtypes from the coarsest to the finest administrative
+/// level, with the number of land zones of each subtype in the reference release.
+///
+/// Zones read from a source are counted at load time; these counts only size the
+/// levels of synthetic zones.
+pub const ZONE_SUBTYPES: [(&str, i64); 9] = [
+    ("country", 378),
+    ("dependency", 105),
+    ("region", 4714),
+    ("county", 39680),
+    ("localadmin", 19007),
+    ("locality", 555834),
+    ("macrohood", 42619),
+    ("neighborhood", 298615),
+    ("microhood", 74797),
+];
+
+/// Returns the number of land zones of a subtype in the reference release
+pub fn subtype_zone_count(subtype: &str) -> i64 {
+    ZONE_SUBTYPES
+        .iter()
+        .find(|(name, _)| *name == subtype)
+        .map(|(_, count)| *count)
+        .unwrap_or(0)
+}
+
+/// Returns the position of a subtype in [`ZONE_SUBTYPES`], coarsest first
+pub fn subtype_rank(subtype: &str) -> usize {
+    ZONE_SUBTYPES
+        .iter()
+        .position(|(name, _)| *name == subtype)
+        .unwrap_or(ZONE_SUBTYPES.len())
+}
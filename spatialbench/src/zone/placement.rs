@@ -0,0 +1,402 @@
+//! Zone-aware trip placement: trip pickups and dropoffs sampled inside the polygons
+//! of the Zone table, so spatial joins between trips and zones have a controlled
+//! selectivity.
+//!
+//! A pickup first picks a subtype (administrative level) with probability
+//! proportional to its configured share, then a zone of that subtype weighted by
+//! [`ZoneWeight`], and finally a uniformly distributed point inside the zone. The
+//! dropoff picks, by the same weight, a zone of the pickup's subtype whose center
+//! is within the trip distance of the pickup (the pickup zone if there is none),
+//! and a point inside it. All draws are hashed from the trip key, so every part
+//! and every run places a trip at the same locations.
+
+use crate::error::{Result, SpatialBenchError};
+use crate::generators::ZoneGenerator;
+use crate::spatial::utils::{hash_to_unit_u64, spider_seed_for_index};
+use crate::zone::{subtype_rank, ZoneConfig, ZONE_SUBTYPES};
+use geo::{BoundingRect, ChamberlainDuquetteArea, Contains, Geometry, InteriorPoint, Point, Rect};
+use log::info;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+
+/// Candidate points drawn in the bounding box of a zone before falling back to
+/// its interior point
+const MAX_SAMPLE_ATTEMPTS: u32 = 64;
+
+/// Salts of the subtype, zone and point draws of a trip
+const SUBTYPE_SALT: u64 = 0x5EED_2011;
+const ZONE_SALT: u64 = 0x5EED_2012;
+const POINT_SALT: u64 = 0x5EED_2013;
+
+/// Salts of the zone and point draws of a dropoff
+const DROPOFF_ZONE_SALT: u64 = 0x5EED_2014;
+const DROPOFF_POINT_SALT: u64 = 0x5EED_2015;
+
+/// How the zones of a subtype are weighted when placing trips
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ZoneWeight {
+    /// Proportional to the (approximate geodesic) area of the zone, so points are
+    /// uniformly spread over the zones of the subtype
+    #[default]
+    Area,
+    /// Every zone equally, so small zones get as many trips as large ones
+    Uniform,
+}
+
+impl fmt::Display for ZoneWeight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZoneWeight::Area => write!(f, "area"),
+            ZoneWeight::Uniform => write!(f, "uniform"),
+        }
+    }
+}
+
+impl FromStr for ZoneWeight {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "area" => Ok(ZoneWeight::Area),
+            "uniform" => Ok(ZoneWeight::Uniform),
+            _ => Err(format!(
+                "Unknown zone weight '{s}'. Expected one of: area, uniform"
+            )),
+        }
+    }
+}
+
+/// Placement of trip pickups and dropoffs inside the zones of the Zone table
+///
+/// Dropoffs are placed in a zone of the pickup's subtype within the trip distance
+/// of the pickup.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ZonePlacement {
+    /// Relative share of the pickups placed in the zones of each subtype. When
+    /// empty, every subtype of the Zone table at the scale factor gets the same
+    /// share.
+    pub subtype_weights: Vec<(String, f64)>,
+    /// How the zones of a subtype are weighted
+    pub weight: ZoneWeight,
+}
+
+impl ZonePlacement {
+    /// Checks that the subtypes are known and the shares are usable
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        for (subtype, weight) in &self.subtype_weights {
+            if subtype_rank(subtype) == ZONE_SUBTYPES.len() {
+                return Err(format!(
+                    "Unknown zone subtype '{}'. Expected one of: {}",
+                    subtype,
+                    ZONE_SUBTYPES
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+            if !weight.is_finite() || *weight < 0.0 {
+                return Err(format!(
+                    "Invalid trip share {weight} for zone subtype '{subtype}'"
+                ));
+            }
+        }
+        if !self.subtype_weights.is_empty() && self.subtype_weights.iter().all(|(_, w)| *w == 0.0) {
+            return Err("Trip shares of the zone subtypes are all zero".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A zone trips can be placed in
+#[derive(Debug)]
+struct SampleZone {
+    boundary: Geometry,
+    bbox: Rect,
+}
+
+/// The zones of one subtype, with the cumulative distribution of their weights
+#[derive(Debug)]
+struct SampleLevel {
+    zones: Vec<SampleZone>,
+    weights: Vec<f64>,
+    cdf: Vec<f64>,
+    centers: CenterGrid,
+}
+
+impl SampleLevel {
+    fn new(zones: Vec<SampleZone>, weights: Vec<f64>) -> Self {
+        let cdf = cumulative(&weights);
+        let centers = CenterGrid::new(&zones);
+        Self {
+            zones,
+            weights,
+            cdf,
+            centers,
+        }
+    }
+
+    /// Returns the zone of the dropoff of the trip with the given key: a zone,
+    /// picked by weight, whose center is within `distance` of the pickup, or the
+    /// pickup zone if there is none
+    fn dropoff_zone(&self, key: u64, pickup: Point, pickup_zone: usize, distance: f64) -> usize {
+        let nearby = self.centers.within(&self.zones, pickup, distance);
+        let total: f64 = nearby.iter().map(|&i| self.weights[i]).sum();
+        if total <= 0.0 {
+            return pickup_zone;
+        }
+        let u = hash_to_unit_u64(key, DROPOFF_ZONE_SALT) * total;
+        let mut sum = 0.0;
+        for &i in &nearby {
+            sum += self.weights[i];
+            if u <= sum {
+                return i;
+            }
+        }
+        nearby[nearby.len() - 1]
+    }
+}
+
+/// Uniform grid over the bounding box centers of the zones of one subtype
+#[derive(Debug)]
+struct CenterGrid {
+    min: (f64, f64),
+    cell_size: (f64, f64),
+    n: usize,
+    /// Indexes of the zones whose center is in each cell, in ascending order
+    cells: Vec<Vec<usize>>,
+}
+
+impl CenterGrid {
+    fn new(zones: &[SampleZone]) -> Self {
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for zone in zones {
+            let center = zone.bbox.center();
+            min_x = min_x.min(center.x);
+            min_y = min_y.min(center.y);
+            max_x = max_x.max(center.x);
+            max_y = max_y.max(center.y);
+        }
+        // about one zone per cell
+        let n = (zones.len() as f64).sqrt().ceil().max(1.0) as usize;
+        let mut grid = CenterGrid {
+            min: (min_x, min_y),
+            cell_size: (
+                ((max_x - min_x) / n as f64).max(1e-9),
+                ((max_y - min_y) / n as f64).max(1e-9),
+            ),
+            n,
+            cells: vec![Vec::new(); n * n],
+        };
+        for (i, zone) in zones.iter().enumerate() {
+            let center = zone.bbox.center();
+            let (x, y) = grid.cell(center.x, center.y);
+            grid.cells[y * n + x].push(i);
+        }
+        grid
+    }
+
+    fn cell(&self, x: f64, y: f64) -> (usize, usize) {
+        let cx = ((x - self.min.0) / self.cell_size.0).floor().max(0.0) as usize;
+        let cy = ((y - self.min.1) / self.cell_size.1).floor().max(0.0) as usize;
+        (cx.min(self.n - 1), cy.min(self.n - 1))
+    }
+
+    /// Returns the zones whose center is within `distance` of `point`
+    fn within(&self, zones: &[SampleZone], point: Point, distance: f64) -> Vec<usize> {
+        let (x0, y0) = self.cell(point.x() - distance, point.y() - distance);
+        let (x1, y1) = self.cell(point.x() + distance, point.y() + distance);
+        let mut nearby = Vec::new();
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                nearby.extend(self.cells[y * self.n + x].iter().copied().filter(|&i| {
+                    let center = zones[i].bbox.center();
+                    (center.x - point.x()).hypot(center.y - point.y()) <= distance
+                }));
+            }
+        }
+        nearby
+    }
+}
+
+/// Samples trip locations inside the zones of the Zone table
+#[derive(Debug)]
+pub struct ZoneSampler {
+    levels: Vec<SampleLevel>,
+    /// Cumulative distribution of the subtype shares
+    cdf: Vec<f64>,
+}
+
+impl ZoneSampler {
+    /// Builds a sampler over the Zone table generated at `scale_factor` with
+    /// `config`, including any geometry processing, so sampled points fall inside
+    /// the zones of the generated table
+    pub fn new(scale_factor: f64, config: ZoneConfig, placement: &ZonePlacement) -> Result<Self> {
+        placement
+            .validate()
+            .map_err(SpatialBenchError::InvalidConfig)?;
+        let start = Instant::now();
+        let table_subtypes: Vec<String> = config
+            .subtypes_for_scale_factor(scale_factor)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let shares: Vec<(String, f64)> = if placement.subtype_weights.is_empty() {
+            table_subtypes.iter().map(|s| (s.clone(), 1.0)).collect()
+        } else {
+            placement.subtype_weights.clone()
+        };
+        for (subtype, _) in &shares {
+            if !table_subtypes.contains(subtype) {
+                return Err(SpatialBenchError::InvalidConfig(format!(
+                    "Zone subtype '{subtype}' is not in the Zone table at scale factor {scale_factor}"
+                )));
+            }
+        }
+
+        let mut by_subtype: HashMap<String, Vec<(SampleZone, f64)>> = HashMap::new();
+        for zone in ZoneGenerator::new_with_config(scale_factor, 1, 1, config).try_iter() {
+            let zone = zone?;
+            let Some(bbox) = zone.z_boundary.bounding_rect() else {
+                continue;
+            };
+            let weight = match placement.weight {
+                ZoneWeight::Area => zone.z_boundary.chamberlain_duquette_unsigned_area(),
+                ZoneWeight::Uniform => 1.0,
+            };
+            if weight > 0.0 {
+                by_subtype.entry(zone.z_subtype).or_default().push((
+                    SampleZone {
+                        boundary: zone.z_boundary,
+                        bbox,
+                    },
+                    weight,
+                ));
+            }
+        }
+
+        let mut levels = Vec::new();
+        let mut level_shares = Vec::new();
+        for (subtype, share) in shares {
+            let zones = by_subtype.remove(&subtype).unwrap_or_default();
+            if share > 0.0 && !zones.is_empty() {
+                let (zones, weights): (Vec<_>, Vec<_>) = zones.into_iter().unzip();
+                levels.push(SampleLevel::new(zones, weights));
+                level_shares.push(share);
+            }
+        }
+        if levels.is_empty() {
+            return Err(SpatialBenchError::ZoneSource(
+                "No zones to place trips in".to_string(),
+            ));
+        }
+
+        info!(
+            "Placing trips in {} zones of {} subtypes, prepared in {:?}",
+            levels.iter().map(|l| l.zones.len()).sum::<usize>(),
+            levels.len(),
+            start.elapsed()
+        );
+        Ok(Self {
+            levels,
+            cdf: cumulative(&level_shares),
+        })
+    }
+
+    /// Returns the sampler for the given settings, built once per process and
+    /// shared by all trip generators
+    pub fn shared(
+        scale_factor: f64,
+        config: ZoneConfig,
+        placement: &ZonePlacement,
+    ) -> Result<Arc<Self>> {
+        static SAMPLERS: OnceLock<Mutex<HashMap<String, Arc<ZoneSampler>>>> = OnceLock::new();
+        let key = format!("{scale_factor}|{config:?}|{placement:?}");
+        let samplers = SAMPLERS.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut samplers = samplers.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(sampler) = samplers.get(&key) {
+            return Ok(Arc::clone(sampler));
+        }
+
+        let sampler = Arc::new(Self::new(scale_factor, config, placement)?);
+        samplers.insert(key, Arc::clone(&sampler));
+        Ok(sampler)
+    }
+
+    /// Returns the pickup location of the trip with the given key
+    ///
+    /// Coordinates are rounded to 8 decimal places, like dropoff locations.
+    pub fn sample(&self, key: u64) -> Point {
+        let (level, zone) = self.pickup_zone(key);
+        sample_in_zone(&level.zones[zone], key, POINT_SALT)
+    }
+
+    /// Returns the dropoff location of the trip with the given key, pickup
+    /// location and distance
+    ///
+    /// The dropoff is inside a zone of the pickup's subtype whose center is within
+    /// `distance` of the pickup, so it is at most about `distance` plus the size
+    /// of a zone away. Coordinates are rounded to 8 decimal places.
+    pub fn sample_dropoff(&self, key: u64, pickup: Point, distance: f64) -> Point {
+        let (level, zone) = self.pickup_zone(key);
+        let zone = level.dropoff_zone(key, pickup, zone, distance);
+        sample_in_zone(&level.zones[zone], key, DROPOFF_POINT_SALT)
+    }
+
+    /// Returns the subtype and the index of the pickup zone of the trip with the
+    /// given key
+    fn pickup_zone(&self, key: u64) -> (&SampleLevel, usize) {
+        let level = &self.levels[pick(&self.cdf, hash_to_unit_u64(key, SUBTYPE_SALT))];
+        (level, pick(&level.cdf, hash_to_unit_u64(key, ZONE_SALT)))
+    }
+}
+
+/// Returns a uniformly distributed point inside the zone, drawn from the key
+/// and the salt
+fn sample_in_zone(zone: &SampleZone, key: u64, salt: u64) -> Point {
+    let mut rng = StdRng::seed_from_u64(spider_seed_for_index(key, salt));
+    let (min, max) = (zone.bbox.min(), zone.bbox.max());
+    let point = (0..MAX_SAMPLE_ATTEMPTS)
+        .map(|_| {
+            round(Point::new(
+                min.x + rng.gen::<f64>() * (max.x - min.x),
+                min.y + rng.gen::<f64>() * (max.y - min.y),
+            ))
+        })
+        .find(|point| zone.boundary.contains(point));
+    // very thin zones may reject every candidate
+    point
+        .or_else(|| zone.boundary.interior_point().map(round))
+        .unwrap_or_else(|| round(zone.bbox.center().into()))
+}
+
+/// Normalized cumulative sums of the weights
+fn cumulative(weights: &[f64]) -> Vec<f64> {
+    let total: f64 = weights.iter().sum();
+    let mut sum = 0.0;
+    weights
+        .iter()
+        .map(|w| {
+            sum += w;
+            sum / total
+        })
+        .collect()
+}
+
+/// Returns the index of the first cumulative weight at or above `u`
+fn pick(cdf: &[f64], u: f64) -> usize {
+    cdf.partition_point(|&c| c < u).min(cdf.len() - 1)
+}
+
+/// Rounds the coordinates of a point to 8 decimal places
+fn round(point: Point) -> Point {
+    Point::new(
+        (point.x() * 100_000_000.0).round() / 100_000_000.0,
+        (point.y() * 100_000_000.0).round() / 100_000_000.0,
+    )
+}
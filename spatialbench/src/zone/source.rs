@@ -0,0 +1,262 @@
+//! [`ZoneSource`]: where the Zone table reads its division areas from.
+
+use crate::zone::synthetic::DEFAULT_SYNTHETIC_ZONE_SEED;
+use std::fmt;
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Overture release used when no release is given
+pub const DEFAULT_OVERTURE_RELEASE: &str = "2025-08-20.1";
+
+const OVERTURE_S3_BUCKET: &str = "overturemaps-us-west-2";
+const OVERTURE_S3_PREFIX: &str = "release";
+
+/// Largest JSON document DuckDB may read when loading a GeoJSON `FeatureCollection`
+const MAX_GEOJSON_OBJECT_SIZE: u64 = 4 * 1024 * 1024 * 1024;
+
+/// How the `geometry` column of a [`ZoneSource::relation_sql`] is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeometryEncoding {
+    /// Well-known binary
+    Wkb,
+    /// GeoJSON geometry object as text
+    GeoJson,
+}
+
+/// Source of the Overture `division_area` rows the Zone table is built from.
+///
+/// Every source is normalized to the same relation (see [`ZoneSource::relation_sql`]),
+/// so subtype filtering, the `is_land` filter and key assignment do not depend on
+/// where the data comes from. A local mirror of an Overture release therefore
+/// produces the same zones as reading the release from S3.
+///
+/// Sources can be parsed from strings, which is how the CLI `--zone-source` flag works:
+///
+/// ```
+/// use spatialbench::zone::ZoneSource;
+///
+/// let source: ZoneSource = "overture".parse().unwrap();
+/// assert_eq!(source, ZoneSource::default());
+///
+/// let source: ZoneSource = "overture:2025-07-23.0".parse().unwrap();
+/// assert_eq!(source, ZoneSource::Overture { release: "2025-07-23.0".into() });
+///
+/// let source: ZoneSource = "synthetic:42".parse().unwrap();
+/// assert_eq!(source, ZoneSource::Synthetic { seed: 42 });
+///
+/// let source: ZoneSource = "parquet:/data/division_area".parse().unwrap();
+/// assert_eq!(source, ZoneSource::Parquet("/data/division_area".into()));
+///
+/// // Without a prefix, the kind of source is inferred from the file extension
+/// let source: ZoneSource = "/data/zones.geojson".parse().unwrap();
+/// assert_eq!(source, ZoneSource::GeoJson("/data/zones.geojson".into()));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZoneSource {
+    /// An Overture release read from the public S3 bucket (requires network access)
+    Overture { release: String },
+    /// A local Overture GeoParquet file, a directory of them, or a glob
+    Parquet(String),
+    /// A local GeoJSON `FeatureCollection` of Overture division areas
+    GeoJson(String),
+    /// A local newline-delimited GeoJSON file with one feature per line
+    NdJson(String),
+    /// Zones generated from the continent regions without any input data
+    /// (see [`synthetic`](crate::zone::synthetic))
+    Synthetic { seed: u64 },
+}
+
+impl Default for ZoneSource {
+    fn default() -> Self {
+        ZoneSource::Overture {
+            release: DEFAULT_OVERTURE_RELEASE.to_string(),
+        }
+    }
+}
+
+impl ZoneSource {
+    /// Returns the DuckDB statements to run before querying this source
+    pub fn setup_sql(&self) -> &'static str {
+        match self {
+            ZoneSource::Overture { .. } => {
+                r#"
+                INSTALL httpfs;
+                LOAD httpfs;
+                INSTALL spatial;
+                LOAD spatial;
+
+                -- Public bucket: force unsigned requests
+                SET s3_access_key_id = '';
+                SET s3_secret_access_key = '';
+                SET s3_session_token = '';
+
+                -- Region + endpoint for the Overture bucket
+                SET s3_region = 'us-west-2';
+                SET s3_endpoint = 's3.us-west-2.amazonaws.com';
+                "#
+            }
+            // Local sources only need the bundled parquet and json extensions, so
+            // nothing has to be downloaded
+            ZoneSource::Parquet(_)
+            | ZoneSource::GeoJson(_)
+            | ZoneSource::NdJson(_)
+            | ZoneSource::Synthetic { .. } => "",
+        }
+    }
+
+    /// Returns how the `geometry` column of [`Self::relation_sql`] is encoded
+    pub fn geometry_encoding(&self) -> GeometryEncoding {
+        match self {
+            ZoneSource::Overture { .. } | ZoneSource::Parquet(_) | ZoneSource::Synthetic { .. } => {
+                GeometryEncoding::Wkb
+            }
+            ZoneSource::GeoJson(_) | ZoneSource::NdJson(_) => GeometryEncoding::GeoJson,
+        }
+    }
+
+    /// Returns a DuckDB query with the normalized columns of this source:
+    /// `id`, `country`, `region`, `name`, `subtype`, `is_land` and `geometry`,
+    /// or `None` for synthetic zones, which are not read through DuckDB.
+    pub fn relation_sql(&self) -> Option<String> {
+        let sql = match self {
+            ZoneSource::Overture { release } => format!(
+                "SELECT id, country, region, names.primary AS name, subtype, is_land,
+                    ST_AsWKB(geometry) AS geometry
+                 FROM read_parquet('{}', hive_partitioning=1)",
+                sql_escape(&overture_url(release))
+            ),
+            // Without the spatial extension loaded, DuckDB returns GeoParquet
+            // geometries as the WKB stored in the file
+            ZoneSource::Parquet(path) => format!(
+                "SELECT id, country, region, names.primary AS name, subtype, is_land, geometry
+                 FROM read_parquet('{}', hive_partitioning=1)",
+                sql_escape(&parquet_glob(path))
+            ),
+            ZoneSource::GeoJson(path) => format!(
+                "SELECT {} FROM (
+                    SELECT unnest(from_json(json->'$.features', '[\"JSON\"]')) AS feature
+                    FROM read_json_objects('{}', format='auto', maximum_object_size={})
+                 )",
+                GEOJSON_FEATURE_COLUMNS,
+                sql_escape(path),
+                MAX_GEOJSON_OBJECT_SIZE
+            ),
+            ZoneSource::NdJson(path) => format!(
+                "SELECT {} FROM (
+                    SELECT json AS feature FROM read_ndjson_objects('{}')
+                 )",
+                GEOJSON_FEATURE_COLUMNS,
+                sql_escape(path)
+            ),
+            ZoneSource::Synthetic { .. } => return None,
+        };
+        Some(sql)
+    }
+}
+
+/// Columns extracted from a GeoJSON feature with Overture properties
+const GEOJSON_FEATURE_COLUMNS: &str = "
+    COALESCE(feature->>'$.properties.id', feature->>'$.id') AS id,
+    feature->>'$.properties.country' AS country,
+    feature->>'$.properties.region' AS region,
+    feature->>'$.properties.names.primary' AS name,
+    feature->>'$.properties.subtype' AS subtype,
+    TRY_CAST(feature->>'$.properties.is_land' AS BOOLEAN) AS is_land,
+    CAST(feature->'$.geometry' AS VARCHAR) AS geometry";
+
+/// Returns the S3 URL of the `division_area` files of an Overture release
+fn overture_url(release: &str) -> String {
+    format!(
+        "s3://{}/{}/{}/theme=divisions/type=division_area/*",
+        OVERTURE_S3_BUCKET, OVERTURE_S3_PREFIX, release
+    )
+}
+
+/// Expands a directory into a recursive glob of the parquet files it contains
+fn parquet_glob(path: &str) -> String {
+    if Path::new(path).is_dir() {
+        format!("{}/**/*.parquet", path.trim_end_matches('/'))
+    } else {
+        path.to_string()
+    }
+}
+
+/// Escapes a string for use inside a single-quoted SQL literal
+fn sql_escape(s: &str) -> String {
+    s.replace('\'', "''")
+}
+
+impl Display for ZoneSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ZoneSource::Overture { release } => write!(f, "overture:{release}"),
+            ZoneSource::Parquet(path) => write!(f, "parquet:{path}"),
+            ZoneSource::GeoJson(path) => write!(f, "geojson:{path}"),
+            ZoneSource::NdJson(path) => write!(f, "ndjson:{path}"),
+            ZoneSource::Synthetic { seed } => write!(f, "synthetic:{seed}"),
+        }
+    }
+}
+
+impl FromStr for ZoneSource {
+    type Err = String;
+
+    /// Parses `overture[:<release>]`, `synthetic[:<seed>]`, `parquet:<path>`,
+    /// `geojson:<path>` or `ndjson:<path>`. A bare path is classified by its
+    /// extension, with directories treated as parquet.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "overture" => return Ok(ZoneSource::default()),
+            "synthetic" => {
+                return Ok(ZoneSource::Synthetic {
+                    seed: DEFAULT_SYNTHETIC_ZONE_SEED,
+                })
+            }
+            _ => {}
+        }
+        if let Some(seed) = s.strip_prefix("synthetic:") {
+            return seed
+                .parse()
+                .map(|seed| ZoneSource::Synthetic { seed })
+                .map_err(|e| format!("Invalid synthetic zone seed '{seed}': {e}"));
+        }
+        if let Some((kind, rest)) = s.split_once(':') {
+            let rest = rest.to_string();
+            match kind {
+                "overture" if !rest.is_empty() => {
+                    return Ok(ZoneSource::Overture { release: rest })
+                }
+                "parquet" if !rest.is_empty() => return Ok(ZoneSource::Parquet(rest)),
+                "geojson" if !rest.is_empty() => return Ok(ZoneSource::GeoJson(rest)),
+                "ndjson" | "geojsonseq" if !rest.is_empty() => return Ok(ZoneSource::NdJson(rest)),
+                "overture" | "parquet" | "geojson" | "ndjson" | "geojsonseq" => {
+                    return Err(format!(
+                        "Missing value after '{kind}:' in zone source '{s}'"
+                    ))
+                }
+                // anything else (e.g. a Windows drive letter) is treated as a path
+                _ => {}
+            }
+        }
+
+        let path = Path::new(s);
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("parquet") => Ok(ZoneSource::Parquet(s.to_string())),
+            Some("geojson") | Some("json") => Ok(ZoneSource::GeoJson(s.to_string())),
+            Some("ndjson") | Some("geojsonl") | Some("geojsonseq") | Some("jsonl") => {
+                Ok(ZoneSource::NdJson(s.to_string()))
+            }
+            _ if path.is_dir() => Ok(ZoneSource::Parquet(s.to_string())),
+            _ => Err(format!(
+                "Cannot determine the zone source for '{s}'. Expected 'overture[:<release>]', \
+                 'synthetic[:<seed>]', 'parquet:<path>', 'geojson:<path>', 'ndjson:<path>' or a path ending in \
+                 .parquet, .geojson or .ndjson"
+            )),
+        }
+    }
+}
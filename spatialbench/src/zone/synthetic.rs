@@ -0,0 +1,436 @@
+//! Synthetic zones: a deterministic, network-free tessellation of the continent regions.
+//!
+//! The continent rectangles of [`ContinentAffines`] are cut again and again by seeded
+//! straight lines, always cutting the tile with the largest spherical area next. After
+//! `k` cuts the leaves tile all continents with `8 + k` convex polygons without gaps or
+//! overlaps, and every later tiling refines the earlier ones. Each zone subtype is the
+//! tiling with as many tiles as the subtype has zones, so the zones of a level with
+//! fewer zones are unions of the zones of any level with more.
+
+use crate::generators::Zone;
+use crate::spatial::geometry::GEOMETRY_PRECISION;
+use crate::spatial::utils::{
+    apply_affine, build_continent_cdf, round_coordinates, seeded_rng, spider_seed_for_index,
+};
+use crate::spatial::ContinentAffines;
+use crate::zone::{subtype_rank, subtype_zone_count};
+use geo::{Coord, Geometry, LineString, Polygon};
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Seed used by [`ZoneSource::Synthetic`](crate::zone::ZoneSource::Synthetic) by default
+pub const DEFAULT_SYNTHETIC_ZONE_SEED: u64 = 20250820;
+
+const NONE: u32 = u32::MAX;
+
+const SYLLABLES: [&str; 32] = [
+    "al", "an", "bar", "bel", "bor", "ca", "dal", "den", "el", "fen", "gar", "har", "is", "kel",
+    "lan", "lor", "mar", "mel", "nor", "os", "pen", "ra", "ren", "sal", "tor", "ul", "val", "ven",
+    "win", "ya", "zan", "tha",
+];
+const ENDINGS: [&str; 12] = [
+    "", "", "", "ia", "ton", "ford", "burg", "ville", "dale", "mont", "stad", "ora",
+];
+const NEIGHBORHOOD_SUFFIXES: [&str; 6] = ["Heights", "Park", "Hill", "Gardens", "Village", "Side"];
+const MICROHOOD_SUFFIXES: [&str; 5] = ["Corner", "Row", "Commons", "Court", "Terrace"];
+
+/// A node of the split tree
+#[derive(Debug)]
+struct Tile {
+    /// Open, counter-clockwise exterior ring in lon/lat
+    ring: Vec<Coord<f64>>,
+    parent: u32,
+    /// The cut that created this tile (0 for the continents)
+    created: u32,
+    /// The cut that split this tile, or [`NONE`] if it is a leaf
+    split_at: u32,
+    children: [u32; 2],
+}
+
+/// Split tree of the continent regions shared by all synthetic zone levels
+#[derive(Debug)]
+pub struct ZoneTessellation {
+    seed: u64,
+    tiles: Vec<Tile>,
+    roots: Vec<u32>,
+    splits: u32,
+    /// Country code of each tile of the country level
+    countries: HashMap<u32, String>,
+    /// Region code of each tile of the region level
+    regions: HashMap<u32, String>,
+}
+
+static TESSELLATIONS: OnceLock<Mutex<HashMap<u64, Arc<ZoneTessellation>>>> = OnceLock::new();
+
+impl ZoneTessellation {
+    /// Returns the (cached) tessellation for `seed` with at least `tile_count` leaves
+    pub fn get(seed: u64, tile_count: usize) -> Arc<ZoneTessellation> {
+        let cache = TESSELLATIONS.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        let min_splits = Self::splits_for_count(Self::min_tile_count().max(tile_count));
+        if let Some(t) = cache.get(&seed) {
+            if t.splits >= min_splits {
+                return Arc::clone(t);
+            }
+        }
+        let t = Arc::new(Self::build(seed, min_splits));
+        cache.insert(seed, Arc::clone(&t));
+        t
+    }
+
+    /// The country and region levels are always built, as every zone refers to them
+    fn min_tile_count() -> usize {
+        subtype_zone_count("country").max(subtype_zone_count("region")) as usize
+    }
+
+    fn root_count() -> usize {
+        build_continent_cdf(&ContinentAffines::default()).len()
+    }
+
+    fn splits_for_count(tile_count: usize) -> u32 {
+        tile_count.saturating_sub(Self::root_count()) as u32
+    }
+
+    fn build(seed: u64, splits: u32) -> Self {
+        let mut tiles = Vec::with_capacity(Self::root_count() + 2 * splits as usize);
+        let mut heap = BinaryHeap::new();
+
+        for (_, m, _) in build_continent_cdf(&ContinentAffines::default()) {
+            let mut ring: Vec<Coord<f64>> = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+                .iter()
+                .map(|&(x, y)| {
+                    let (x, y) = apply_affine(x, y, &m);
+                    Coord { x, y }
+                })
+                .collect();
+            if signed_area(&ring) < 0.0 {
+                ring.reverse();
+            }
+            let id = tiles.len() as u32;
+            heap.push((weight_key(&ring), Reverse(id)));
+            tiles.push(Tile {
+                ring,
+                parent: NONE,
+                created: 0,
+                split_at: NONE,
+                children: [NONE, NONE],
+            });
+        }
+        let roots = (0..tiles.len() as u32).collect();
+
+        for step in 1..=splits {
+            let Some((_, Reverse(id))) = heap.pop() else {
+                break;
+            };
+            let mut rng = seeded_rng(spider_seed_for_index(id as u64, seed));
+            let (left, right) = split_ring(&tiles[id as usize].ring, &mut rng);
+            let mut children = [NONE, NONE];
+            for (child, ring) in children.iter_mut().zip([left, right]) {
+                *child = tiles.len() as u32;
+                heap.push((weight_key(&ring), Reverse(*child)));
+                tiles.push(Tile {
+                    ring,
+                    parent: id,
+                    created: step,
+                    split_at: NONE,
+                    children: [NONE, NONE],
+                });
+            }
+            let tile = &mut tiles[id as usize];
+            tile.split_at = step;
+            tile.children = children;
+        }
+
+        let mut tessellation = ZoneTessellation {
+            seed,
+            tiles,
+            roots,
+            splits,
+            countries: HashMap::new(),
+            regions: HashMap::new(),
+        };
+        tessellation.assign_codes();
+        tessellation
+    }
+
+    /// Assigns ISO-like codes: `AB` to countries and `AB-CD` to regions
+    fn assign_codes(&mut self) {
+        let country_splits = Self::splits_for_count(subtype_zone_count("country") as usize);
+        let region_splits = Self::splits_for_count(subtype_zone_count("region") as usize);
+
+        let countries: HashMap<u32, String> = self
+            .frontier(country_splits)
+            .into_iter()
+            .enumerate()
+            .map(|(i, id)| (id, alpha_code(i)))
+            .collect();
+
+        let mut ordinals: HashMap<u32, usize> = HashMap::new();
+        let mut regions = HashMap::new();
+        for id in self.frontier(region_splits) {
+            let country = self.level_tile(id, country_splits);
+            let ordinal = ordinals.entry(country).or_insert(0);
+            regions.insert(
+                id,
+                format!("{}-{}", countries[&country], alpha_code(*ordinal)),
+            );
+            *ordinal += 1;
+        }
+
+        self.countries = countries;
+        self.regions = regions;
+    }
+
+    /// Returns the tiles of the tiling after `splits` cuts in depth-first order, which
+    /// keeps the tiles of each coarser zone together
+    fn frontier(&self, splits: u32) -> Vec<u32> {
+        let mut out = Vec::new();
+        let mut stack: Vec<u32> = self.roots.iter().rev().copied().collect();
+        while let Some(id) = stack.pop() {
+            let tile = &self.tiles[id as usize];
+            if tile.split_at <= splits {
+                stack.push(tile.children[1]);
+                stack.push(tile.children[0]);
+            } else {
+                out.push(id);
+            }
+        }
+        out
+    }
+
+    /// Returns the tiles of the level with `tile_count` zones, or with one tile
+    /// per continent if `tile_count` is smaller, so that the level covers all
+    /// the continents
+    pub fn level(&self, tile_count: usize) -> Vec<u32> {
+        let mut tiles = self.frontier(Self::splits_for_count(tile_count));
+        tiles.truncate(tile_count.max(self.roots.len()));
+        tiles
+    }
+
+    /// Returns the tile of the tiling after `splits` cuts that contains tile `id`, or
+    /// the first tile inside it if `id` belongs to a coarser tiling
+    fn level_tile(&self, mut id: u32, splits: u32) -> u32 {
+        while self.tiles[id as usize].created > splits {
+            id = self.tiles[id as usize].parent;
+        }
+        while self.tiles[id as usize].split_at <= splits {
+            id = self.tiles[id as usize].children[0];
+        }
+        id
+    }
+
+    /// Returns the zone for tile `id` of the `subtype` level, without a parent
+    pub fn zone(&self, z_zonekey: i64, subtype: &str, id: u32) -> Zone {
+        let country_splits = Self::splits_for_count(subtype_zone_count("country") as usize);
+        let region_splits = Self::splits_for_count(subtype_zone_count("region") as usize);
+        let salt = self.seed ^ ((subtype_rank(subtype) as u64) << 56);
+        let mut rng = seeded_rng(spider_seed_for_index(id as u64, salt));
+
+        let mut coords: Vec<Coord<f64>> = self.tiles[id as usize]
+            .ring
+            .iter()
+            .map(|c| {
+                let (x, y) = round_coordinates(c.x, c.y, GEOMETRY_PRECISION);
+                Coord { x, y }
+            })
+            .collect();
+        coords.push(coords[0]);
+
+        Zone {
+            z_zonekey,
+            z_gersid: gers_id(&mut rng),
+            z_country: self.countries[&self.level_tile(id, country_splits)].clone(),
+            z_region: self.regions[&self.level_tile(id, region_splits)].clone(),
+            z_name: zone_name(subtype, &mut rng),
+            z_subtype: subtype.to_string(),
+            z_level: subtype_rank(subtype) as i32,
+            z_parentkey: None,
+            z_boundary: Geometry::Polygon(Polygon::new(LineString::new(coords), vec![])),
+            z_bbox: None,
+        }
+    }
+}
+
+/// Returns the smallest number of zones of a synthetic level, one per continent
+pub fn min_level_count() -> i64 {
+    ZoneTessellation::root_count() as i64
+}
+
+/// Generates the synthetic zones with keys `offset + 1 ..= offset + limit`
+///
+/// `levels` lists the subtypes of the table in row order with their zone counts.
+/// The parent of a zone is the zone that contains it on the nearest coarser level
+/// with at most as many zones, as only the tilings with fewer tiles are unions of
+/// its tiles. Zones without such a level have no parent.
+pub fn generate_zones(seed: u64, levels: &[(&str, i64)], offset: i64, limit: i64) -> Vec<Zone> {
+    let max_count = levels.iter().map(|(_, n)| *n).max().unwrap_or(0).max(0);
+    let tessellation = ZoneTessellation::get(seed, max_count as usize);
+
+    let mut zones = Vec::new();
+    let end = offset + limit;
+    let mut level_start = 0i64;
+    // first key and zone count of each level generated so far
+    let mut coarser_levels: Vec<(i64, i64)> = Vec::new();
+    for &(subtype, count) in levels {
+        let level_end = level_start + count;
+        if level_end > offset && level_start < end {
+            let tiles = tessellation.level(count as usize);
+            // keys of the tiles of the parent level, by tile
+            let parents = coarser_levels
+                .iter()
+                .rev()
+                .find(|(_, parent_count)| *parent_count <= count)
+                .map(|&(parent_start, parent_count)| {
+                    let positions: HashMap<u32, i64> = tessellation
+                        .level(parent_count as usize)
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, id)| (id, parent_start + i as i64 + 1))
+                        .collect();
+                    (
+                        ZoneTessellation::splits_for_count(parent_count as usize),
+                        positions,
+                    )
+                });
+
+            let from = (offset.max(level_start) - level_start) as usize;
+            let to = (end.min(level_end) - level_start) as usize;
+            for (i, &id) in tiles.iter().enumerate().take(to).skip(from) {
+                let key = level_start + i as i64 + 1;
+                let mut zone = tessellation.zone(key, subtype, id);
+                if let Some((splits, positions)) = &parents {
+                    zone.z_parentkey = positions
+                        .get(&tessellation.level_tile(id, *splits))
+                        .copied();
+                }
+                zones.push(zone);
+            }
+        }
+        coarser_levels.push((level_start, count));
+        level_start = level_end;
+    }
+    zones
+}
+
+/// Cuts a convex ring in two with a seeded line across its longer side
+fn split_ring(ring: &[Coord<f64>], rng: &mut StdRng) -> (Vec<Coord<f64>>, Vec<Coord<f64>>) {
+    let (min_x, max_x, min_y, max_y) = ring.iter().fold(
+        (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+        |(a, b, c, d), p| (a.min(p.x), b.max(p.x), c.min(p.y), d.max(p.y)),
+    );
+    // measure east-west extents in (approximate) local distance
+    let k = ((min_y + max_y) / 2.0).to_radians().cos().max(0.05);
+    let base = if (max_x - min_x) * k >= max_y - min_y {
+        0.0
+    } else {
+        PI / 2.0
+    };
+    let theta = base + rng.gen_range(-0.4..0.4);
+    let (nx, ny) = (k * theta.cos(), theta.sin());
+    let t = |p: &Coord<f64>| nx * p.x + ny * p.y;
+
+    let (t_min, t_max) = ring.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+        (lo.min(t(p)), hi.max(t(p)))
+    });
+    let t_cut = t_min + (t_max - t_min) * rng.gen_range(0.3..0.7);
+
+    let mut left = Vec::with_capacity(ring.len() + 2);
+    let mut right = Vec::with_capacity(ring.len() + 2);
+    for (i, a) in ring.iter().enumerate() {
+        let b = &ring[(i + 1) % ring.len()];
+        let (da, db) = (t(a) - t_cut, t(b) - t_cut);
+        if da <= 0.0 {
+            left.push(*a);
+        }
+        if da >= 0.0 {
+            right.push(*a);
+        }
+        if (da < 0.0 && db > 0.0) || (da > 0.0 && db < 0.0) {
+            // both halves share the exact same intersection point, so no gaps
+            let s = da / (da - db);
+            let p = Coord {
+                x: a.x + (b.x - a.x) * s,
+                y: a.y + (b.y - a.y) * s,
+            };
+            left.push(p);
+            right.push(p);
+        }
+    }
+    (left, right)
+}
+
+fn signed_area(ring: &[Coord<f64>]) -> f64 {
+    let mut sum = 0.0;
+    for (i, a) in ring.iter().enumerate() {
+        let b = &ring[(i + 1) % ring.len()];
+        sum += a.x * b.y - b.x * a.y;
+    }
+    sum / 2.0
+}
+
+/// Heap key that orders tiles by their area on the sphere (approximately)
+fn weight_key(ring: &[Coord<f64>]) -> u64 {
+    let lat = ring.iter().map(|p| p.y).sum::<f64>() / ring.len() as f64;
+    let weight = signed_area(ring).abs() * lat.to_radians().cos().abs();
+    // the bit patterns of non-negative floats sort like the floats
+    weight.to_bits()
+}
+
+/// Returns `AA`, `AB`, ..., `ZZ`, `BAA`, ... for 0, 1, ...
+fn alpha_code(mut i: usize) -> String {
+    let mut letters = vec![(b'A' + (i % 26) as u8) as char];
+    i /= 26;
+    letters.push((b'A' + (i % 26) as u8) as char);
+    i /= 26;
+    while i > 0 {
+        letters.push((b'A' + (i % 26) as u8) as char);
+        i /= 26;
+    }
+    letters.iter().rev().collect()
+}
+
+/// Returns a random id formatted like an Overture GERS id
+fn gers_id(rng: &mut StdRng) -> String {
+    let hi: u64 = rng.gen();
+    let lo: u64 = rng.gen();
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        hi >> 32,
+        (hi >> 16) & 0xffff,
+        hi & 0xffff,
+        lo >> 48,
+        lo & 0xffff_ffff_ffff
+    )
+}
+
+fn zone_name(subtype: &str, rng: &mut StdRng) -> String {
+    let syllables = rng.gen_range(2..=3);
+    let mut base: String = (0..syllables)
+        .map(|_| SYLLABLES[rng.gen_range(0..SYLLABLES.len())])
+        .collect();
+    base.push_str(ENDINGS[rng.gen_range(0..ENDINGS.len())]);
+    let mut chars = base.chars();
+    let base = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => base,
+    };
+
+    match subtype {
+        "county" => format!("{base} County"),
+        "localadmin" => format!("{base} Township"),
+        "macrohood" => format!("{base} District"),
+        "neighborhood" => format!(
+            "{base} {}",
+            NEIGHBORHOOD_SUFFIXES[rng.gen_range(0..NEIGHBORHOOD_SUFFIXES.len())]
+        ),
+        "microhood" => format!(
+            "{base} {}",
+            MICROHOOD_SUFFIXES[rng.gen_range(0..MICROHOOD_SUFFIXES.len())]
+        ),
+        _ => base,
+    }
+}
@@ -0,0 +1,190 @@
+//! Validity test suite: every building generated in strict validity mode must be
+//! an OGC-valid footprint, checked with the validity predicates of `geo`.
+
+use geo::{Area, Geometry, LineString, Polygon, Validation, Winding};
+use spatialbench::distribution::Distributions;
+use spatialbench::generators::BuildingGenerator;
+use spatialbench::spatial::geometry::MIN_POLYGON_AREA;
+use spatialbench::spatial::{
+    DistributionParams, DistributionType, FootprintOptions, GeomType, PolygonStyle, SizeUnit,
+    SpatialConfig, SpatialGenerator, Validity,
+};
+use spatialbench::text::TextPool;
+
+/// Panics unless the footprint is valid, has no repeated points, is oriented
+/// counter-clockwise with clockwise holes and has at least the minimum area
+fn assert_strictly_valid(key: i64, footprint: &Geometry) {
+    let polygons: Vec<&Polygon> = match footprint {
+        Geometry::Polygon(polygon) => vec![polygon],
+        Geometry::MultiPolygon(multi) => multi.0.iter().collect(),
+        other => panic!("building {key} is not a polygon: {other:?}"),
+    };
+    let repeats = |ring: &LineString| ring.lines().any(|line| line.start == line.end);
+
+    assert!(
+        footprint.is_valid(),
+        "building {key} is invalid: {:?}",
+        footprint.validation_errors()
+    );
+    for polygon in polygons {
+        assert!(polygon.exterior().is_ccw(), "building {key}: {polygon:?}");
+        assert!(!repeats(polygon.exterior()), "building {key}: {polygon:?}");
+        for interior in polygon.interiors() {
+            assert!(interior.is_cw(), "building {key}: {polygon:?}");
+            assert!(!repeats(interior), "building {key}: {polygon:?}");
+        }
+        assert!(
+            polygon.unsigned_area() >= MIN_POLYGON_AREA,
+            "building {key}: {polygon:?}"
+        );
+    }
+}
+
+fn assert_all_strictly_valid(generator: &BuildingGenerator) -> usize {
+    let mut count = 0;
+    for building in generator {
+        assert_strictly_valid(building.b_buildingkey, &building.footprint());
+        count += 1;
+    }
+    count
+}
+
+fn building_generator(scale_factor: f64, config: SpatialConfig) -> BuildingGenerator<'static> {
+    BuildingGenerator::new_with_distributions_and_text_pool(
+        scale_factor,
+        1,
+        1,
+        Distributions::static_default(),
+        TextPool::get_or_init_default(),
+        SpatialGenerator::try_new_with_cardinality(
+            config,
+            BuildingGenerator::calculate_row_count(scale_factor, 1, 1) as u64,
+        )
+        .unwrap(),
+    )
+}
+
+fn config(
+    dist_type: DistributionType,
+    geom_type: GeomType,
+    params: DistributionParams,
+) -> SpatialConfig {
+    SpatialConfig {
+        dist_type,
+        geom_type,
+        dim: 2,
+        seed: 2024,
+        width: 0.0002,
+        height: 0.0001,
+        maxseg: 8,
+        polysize: 0.0001,
+        polygon_style: PolygonStyle::Star,
+        params,
+        validity: Validity::Strict,
+        units: SizeUnit::Unit,
+    }
+}
+
+#[test]
+fn test_default_buildings_strictly_valid() {
+    for scale_factor in [1.0, 3.0, 10.0] {
+        let generator = BuildingGenerator::new(scale_factor, 1, 1)
+            .with_validity(Validity::Strict)
+            .unwrap();
+        let count = assert_all_strictly_valid(&generator);
+        assert_eq!(
+            count as i64,
+            BuildingGenerator::calculate_row_count(scale_factor, 1, 1)
+        );
+    }
+}
+
+#[test]
+fn test_footprint_buildings_strictly_valid() {
+    for scale_factor in [1.0, 5.0] {
+        let generator = BuildingGenerator::new(scale_factor, 1, 1)
+            .with_validity(Validity::Strict)
+            .unwrap()
+            .with_footprint(FootprintOptions {
+                courtyard_probability: 0.5,
+                multipolygon_probability: 0.3,
+                max_parts: 6,
+            });
+        assert_all_strictly_valid(&generator);
+    }
+}
+
+#[test]
+fn test_distribution_buildings_strictly_valid() {
+    let configs = [
+        config(
+            DistributionType::Uniform,
+            GeomType::Box,
+            DistributionParams::None,
+        ),
+        config(
+            DistributionType::Normal,
+            GeomType::Polygon,
+            DistributionParams::Normal {
+                mu: 0.5,
+                sigma: 0.3,
+            },
+        ),
+        SpatialConfig {
+            polygon_style: PolygonStyle::Footprint {
+                median_size: 15.0,
+                size_sigma: 0.8,
+            },
+            ..config(
+                DistributionType::Uniform,
+                GeomType::Polygon,
+                DistributionParams::None,
+            )
+        },
+        config(
+            DistributionType::Parcel,
+            GeomType::Box,
+            DistributionParams::Parcel {
+                srange: 0.1,
+                dither: 0.5,
+            },
+        ),
+        config(
+            DistributionType::Parcel,
+            GeomType::Polygon,
+            DistributionParams::Parcel {
+                srange: 0.1,
+                dither: 0.5,
+            },
+        ),
+    ];
+    for config in configs {
+        for scale_factor in [1.0, 2.0] {
+            assert_all_strictly_valid(&building_generator(scale_factor, config.clone()));
+        }
+    }
+}
+
+#[test]
+fn test_raw_buildings_keep_clamped_vertices() {
+    // normal distribution with a large sigma puts many polygons on the edge of the
+    // unit square, where raw vertices are clamped onto each other
+    let mut config = config(
+        DistributionType::Normal,
+        GeomType::Polygon,
+        DistributionParams::Normal {
+            mu: 0.5,
+            sigma: 1.0,
+        },
+    );
+    config.validity = Validity::Raw;
+    let raw = building_generator(1.0, config.clone());
+    assert!(raw.iter().any(|building| building
+        .b_boundary
+        .exterior()
+        .lines()
+        .any(|l| l.start == l.end)));
+
+    config.validity = Validity::Strict;
+    assert_all_strictly_valid(&building_generator(1.0, config));
+}